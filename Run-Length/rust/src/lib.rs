@@ -0,0 +1,796 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use encoding_common::symbol::{read_u32_le_from, write_u32_le_to};
+pub use encoding_common::{DecodeLimits, Error};
+
+mod packbits;
+pub use packbits::{
+    packbits_decode, packbits_decode_with_limit, packbits_encode, rle_decode_packbits,
+    rle_decode_packbits_with_limit, rle_encode_packbits,
+};
+
+// 简单的 Run-Length 编码实现。
+// 编码格式：反复写入 4 字节小端无符号整数 count + 1 字节 value，直到输入结束。
+// 三种语言实现使用完全相同的格式，便于交叉验证与基准测试。
+
+// LEB128：每字节低 7 位为数据、最高位表示后面还有字节，小于 128 的值只占 1 字节。
+fn write_varint<W: Write>(w: &mut W, mut v: u32) -> io::Result<()> {
+    let mut buf = [0u8; 5];
+    let mut n = 0;
+    while v >= 0x80 {
+        buf[n] = (v as u8) | 0x80;
+        v >>= 7;
+        n += 1;
+    }
+    buf[n] = v as u8;
+    w.write_all(&buf[..=n])
+}
+
+// 返回 Ok(None) 表示正常 EOF（一个字节都没读到）。超过 5 字节或数值超出 u32 的 varint 返回 InvalidData。
+fn read_varint<R: Read>(r: &mut R) -> Result<Option<u32>, Error> {
+    let mut v: u32 = 0;
+    for i in 0..5 {
+        let mut byte = [0u8; 1];
+        match r.read_exact(&mut byte) {
+            Ok(()) => {}
+            Err(e) if i == 0 && e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(Error::from_read(e, "rle: truncated count field")),
+        }
+        let bits = (byte[0] & 0x7F) as u32;
+        // 第 5 字节只能提供 u32 剩下的 4 位。
+        if i == 4 && (byte[0] & 0x80 != 0 || bits > 0x0F) {
+            return Err(Error::InvalidData("rle: varint count overflows u32"));
+        }
+        v |= bits << (7 * i);
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(v));
+        }
+    }
+    unreachable!()
+}
+
+fn check_max_run(max_run: u32) -> Result<(), Error> {
+    if max_run == 0 {
+        return Err(Error::InvalidInput("rle: max_run must be positive"));
+    }
+    Ok(())
+}
+
+// 增量 RLE 编码器：由调用方逐字节 push，每当一段 run 结束就写出对应记录，
+// 适合事件驱动的数据源。输出与 rle_encode_file 对同一字节序列的结果完全相同。
+pub struct RleEncoder<W: Write> {
+    writer: W,
+    current: u8,
+    count: u32,
+    max_run: u32,
+}
+
+impl<W: Write> RleEncoder<W> {
+    pub fn new(writer: W) -> Self {
+        RleEncoder {
+            writer,
+            current: 0,
+            count: 0,
+            max_run: u32::MAX,
+        }
+    }
+
+    // 单条记录的 count 不超过 max_run，语义同 rle_encode_file_with_max_run。
+    pub fn with_max_run(writer: W, max_run: u32) -> Result<Self, Error> {
+        check_max_run(max_run)?;
+        let mut encoder = Self::new(writer);
+        encoder.max_run = max_run;
+        Ok(encoder)
+    }
+
+    pub fn push(&mut self, b: u8) -> Result<(), Error> {
+        if self.count > 0 && b == self.current && self.count < self.max_run {
+            self.count += 1;
+            return Ok(());
+        }
+        self.write_record()?;
+        self.current = b;
+        self.count = 1;
+        Ok(())
+    }
+
+    // 写出最后一段并返回底层 writer（不会 flush，由调用方决定）。
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.write_record()?;
+        Ok(self.writer)
+    }
+
+    fn write_record(&mut self) -> Result<(), Error> {
+        if self.count == 0 {
+            return Ok(());
+        }
+        write_u32_le_to(&mut self.writer, self.count)?;
+        self.writer.write_all(&[self.current])?;
+        Ok(())
+    }
+}
+
+// 在内存中编码，输出与 rle_encode_file 对同一内容的结果逐字节相同。
+pub fn rle_encode(data: &[u8]) -> Vec<u8> {
+    let mut encoder = RleEncoder::new(Vec::with_capacity(data.len() / 4 + 5));
+    for &b in data {
+        // 写入 Vec 不会失败。
+        encoder.push(b).expect("rle: writing to Vec failed");
+    }
+    encoder.finish().expect("rle: writing to Vec failed")
+}
+
+// 在内存中解码 rle_encode / rle_encode_file 的输出，截断或非法的记录返回 Err。
+pub fn rle_decode(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::with_capacity(data.len());
+    rle_decode_reader(data, &mut out, &DecodeLimits::unlimited())?;
+    Ok(out)
+}
+
+// 输出超过 max_output 字节时返回 LimitExceeded；在分配越界记录的输出之前拒绝，
+// 声称 count = u32::MAX 的损坏记录不会导致巨大的分配。
+pub fn rle_decode_with_limit(data: &[u8], max_output: u64) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::with_capacity(data.len());
+    rle_decode_reader(data, &mut out, &DecodeLimits::new(max_output))?;
+    Ok(out)
+}
+
+// 带魔数的封装：魔数 RLE0 + rle_encode 的输出。经典格式没有魔数，与其他语言实现互通时仍用
+// rle_encode；需要从数据本身识别格式（如 codec::decode_any）时使用这一对函数。
+const FRAMED_MAGIC: &[u8; 4] = b"RLE0";
+
+pub fn rle_encode_framed(data: &[u8]) -> Vec<u8> {
+    let mut out = FRAMED_MAGIC.to_vec();
+    out.extend_from_slice(&rle_encode(data));
+    out
+}
+
+pub fn rle_decode_framed(data: &[u8]) -> Result<Vec<u8>, Error> {
+    rle_decode_framed_with_limit(data, u64::MAX)
+}
+
+pub fn rle_decode_framed_with_limit(data: &[u8], max_output: u64) -> Result<Vec<u8>, Error> {
+    match data.strip_prefix(FRAMED_MAGIC) {
+        Some(body) => rle_decode_with_limit(body, max_output),
+        None if data.len() < 4 => Err(Error::Truncated("rle: truncated header")),
+        None => Err(Error::BadMagic("rle: bad magic")),
+    }
+}
+
+// varint 变体：记录为 LEB128 varint count + 1 字节 value，count 小于 128 时每条记录只占 2 字节
+// （定长格式为 5 字节），短 run 很多的数据上输出明显更小。count 仍不能为 0。
+pub fn rle_encode_varint(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() / 2 + 2);
+    for run in data.chunk_by(|a, b| a == b) {
+        for part in run.chunks(u32::MAX as usize) {
+            // 写入 Vec 不会失败。
+            write_varint(&mut out, part.len() as u32).expect("rle: writing to Vec failed");
+            out.push(part[0]);
+        }
+    }
+    out
+}
+
+pub fn rle_decode_varint(data: &[u8]) -> Result<Vec<u8>, Error> {
+    rle_decode_varint_with_limit(data, u64::MAX)
+}
+
+// 与 rle_decode_with_limit 相同：输出将超过 max_output 字节时在展开该记录之前返回 LimitExceeded，
+// 6 字节的 count = u32::MAX 记录不会导致 4 GiB 的分配。
+pub fn rle_decode_varint_with_limit(data: &[u8], max_output: u64) -> Result<Vec<u8>, Error> {
+    let mut reader = data;
+    let capacity = usize::try_from(max_output).unwrap_or(usize::MAX);
+    let mut out = Vec::with_capacity(data.len().saturating_mul(2).min(capacity));
+    while let Some(count) = read_varint(&mut reader)? {
+        if count == 0 {
+            return Err(Error::InvalidData("rle: zero count"));
+        }
+        let (&value, rest) = reader
+            .split_first()
+            .ok_or(Error::Truncated("rle: missing value byte"))?;
+        if out.len() as u64 + count as u64 > max_output {
+            return Err(Error::LimitExceeded("rle: output limit exceeded"));
+        }
+        out.resize(out.len() + count as usize, value);
+        reader = rest;
+    }
+    Ok(out)
+}
+
+// 16 位单元变体：输入视为小端 u16 序列（16 位音频采样、UTF-16 文本等），run 不会在采样中间断开。
+// 格式：魔数 RLEW + 1 字节单元宽度（2）+ 若干条 4 字节小端 count + 2 字节小端 value。
+const WORD_MAGIC: &[u8; 4] = b"RLEW";
+const WORD_WIDTH: u8 = 2;
+
+// 输入字节数为奇数时返回 InvalidInput，不会丢弃末尾的字节。
+pub fn rle_encode_u16_le(data: &[u8]) -> Result<Vec<u8>, Error> {
+    if !data.len().is_multiple_of(2) {
+        return Err(Error::InvalidInput(
+            "rle: odd input length for 16-bit units",
+        ));
+    }
+    let mut out = WORD_MAGIC.to_vec();
+    out.push(WORD_WIDTH);
+    let units: Vec<&[u8]> = data.chunks_exact(2).collect();
+    for run in units.chunk_by(|a, b| a == b) {
+        for part in run.chunks(u32::MAX as usize) {
+            out.extend_from_slice(&(part.len() as u32).to_le_bytes());
+            out.extend_from_slice(part[0]);
+        }
+    }
+    Ok(out)
+}
+
+pub fn rle_decode_u16_le(data: &[u8]) -> Result<Vec<u8>, Error> {
+    rle_decode_u16_le_with_limit(data, u64::MAX)
+}
+
+// 输出将超过 max_output 字节时在展开该记录之前返回 LimitExceeded；每条记录最多展开 8 GiB。
+pub fn rle_decode_u16_le_with_limit(data: &[u8], max_output: u64) -> Result<Vec<u8>, Error> {
+    let body = match data.strip_prefix(WORD_MAGIC) {
+        Some(rest) => rest,
+        None if data.len() < 4 => return Err(Error::Truncated("rle: truncated header")),
+        None => return Err(Error::BadMagic("rle: bad magic")),
+    };
+    let (&width, records) = body
+        .split_first()
+        .ok_or(Error::Truncated("rle: truncated header"))?;
+    if width != WORD_WIDTH {
+        return Err(Error::InvalidData("rle: unsupported unit width"));
+    }
+    let mut out = Vec::with_capacity(records.len());
+    for record in records.chunks(6) {
+        if record.len() < 4 {
+            return Err(Error::Truncated("rle: truncated count field"));
+        }
+        if record.len() < 6 {
+            return Err(Error::Truncated("rle: missing value bytes"));
+        }
+        let count = u32::from_le_bytes(record[..4].try_into().unwrap());
+        if count == 0 {
+            return Err(Error::InvalidData("rle: zero count"));
+        }
+        let run_len = count as u64 * WORD_WIDTH as u64;
+        if out.len() as u64 + run_len > max_output {
+            return Err(Error::LimitExceeded("rle: output limit exceeded"));
+        }
+        out.reserve(run_len as usize);
+        out.extend(std::iter::repeat_n([record[4], record[5]], count as usize).flatten());
+    }
+    Ok(out)
+}
+
+// 文件接口的路径为 "-" 时，输入读自标准输入、输出写到标准输出，便于在管道中使用。
+// 两者都按原始字节读写，不做任何换行或编码转换。
+const STDIO_PATH: &str = "-";
+
+fn open_input(input_path: &str) -> io::Result<BufReader<Box<dyn Read>>> {
+    let input: Box<dyn Read> = if input_path == STDIO_PATH {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(File::open(input_path)?)
+    };
+    Ok(BufReader::new(input))
+}
+
+fn create_output(output_path: &str) -> io::Result<BufWriter<Box<dyn Write>>> {
+    let output: Box<dyn Write> = if output_path == STDIO_PATH {
+        Box::new(io::stdout().lock())
+    } else {
+        Box::new(File::create(output_path)?)
+    };
+    Ok(BufWriter::new(output))
+}
+
+// 对整个文件进行 Run-Length 编码。
+pub fn rle_encode_file(input_path: &str, output_path: &str) -> Result<(), Error> {
+    rle_encode_file_with_max_run(input_path, output_path, u32::MAX)
+}
+
+// 与 rle_encode_file 相同，但单条记录的 count 不超过 max_run，
+// 更长的 run 会被拆成多条记录，从而限制解码端每条记录的输出延迟。
+// 输出格式不变，可被任何解码器直接解码。
+pub fn rle_encode_file_with_max_run(
+    input_path: &str,
+    output_path: &str,
+    max_run: u32,
+) -> Result<(), Error> {
+    check_max_run(max_run)?;
+
+    let mut reader = open_input(input_path)?;
+    let mut encoder = RleEncoder::with_max_run(create_output(output_path)?, max_run)?;
+
+    let mut buf = [0u8; 4096];
+
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        for &b in &buf[..n] {
+            encoder.push(b)?;
+        }
+    }
+
+    encoder.finish()?.flush()?;
+    Ok(())
+}
+
+// 将 RLE 编码文件解码回原始字节流。
+pub fn rle_decode_file(input_path: &str, output_path: &str) -> Result<(), Error> {
+    rle_decode_file_with_limits(input_path, output_path, &DecodeLimits::unlimited())
+}
+
+// 在 limits 约束下解码：解码总字节数超过上限时返回 LimitExceeded，且不写出越界的记录。
+pub fn rle_decode_file_with_limits(
+    input_path: &str,
+    output_path: &str,
+    limits: &DecodeLimits,
+) -> Result<(), Error> {
+    let mut writer = create_output(output_path)?;
+    rle_decode_reader(open_input(input_path)?, &mut writer, limits)
+}
+
+// 额外限制单条记录的 count 不超过 max_run（同 rle_encode_file_with_max_run），超出时返回 InvalidData。
+// 已知编码端 max_run 的数据可借此把损坏的 count 当作格式错误尽早拒绝，而不是写到总量上限为止。
+pub fn rle_decode_file_with_max_run(
+    input_path: &str,
+    output_path: &str,
+    limits: &DecodeLimits,
+    max_run: u32,
+) -> Result<(), Error> {
+    check_max_run(max_run)?;
+    let mut writer = create_output(output_path)?;
+    decode_records(open_input(input_path)?, &mut writer, limits, max_run)
+}
+
+// 从任意 reader 解码 RLE 数据流并写入 writer，limits 的含义同 rle_decode_file_with_limits；
+// 用于内存缓冲区、网络流等不经过文件的场景。
+pub fn rle_decode_reader<R: Read, W: Write>(
+    reader: R,
+    writer: &mut W,
+    limits: &DecodeLimits,
+) -> Result<(), Error> {
+    decode_records(reader, writer, limits, u32::MAX)
+}
+
+fn decode_records<R: Read, W: Write>(
+    mut reader: R,
+    writer: &mut W,
+    limits: &DecodeLimits,
+    max_run: u32,
+) -> Result<(), Error> {
+    let mut remaining_output = limits.max_memory();
+
+    const BUF_SIZE: usize = 4096;
+    let mut buf = [0u8; BUF_SIZE];
+
+    loop {
+        let count_opt = read_u32_le_from(&mut reader)
+            .map_err(|e| Error::from_read(e, "rle: truncated count field"))?;
+        let count = match count_opt {
+            Some(c) => c,
+            None => break, // 正常 EOF
+        };
+        if count == 0 {
+            return Err(Error::InvalidData("rle: zero count"));
+        }
+        if count > max_run {
+            return Err(Error::InvalidData("rle: count exceeds max_run"));
+        }
+        remaining_output = remaining_output
+            .checked_sub(count as u64)
+            .ok_or(Error::LimitExceeded("rle: output limit exceeded"))?;
+
+        let mut value_buf = [0u8; 1];
+        reader
+            .read_exact(&mut value_buf)
+            .map_err(|e| Error::from_read(e, "rle: missing value byte"))?;
+        let value = value_buf[0];
+
+        let mut remaining = count;
+        while remaining > 0 {
+            let chunk = remaining.min(BUF_SIZE as u32) as usize;
+            buf[..chunk].fill(value);
+            writer.write_all(&buf[..chunk])?;
+            remaining -= chunk as u32;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_common::test_util::assert_bytes_eq;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+
+    // 在临时目录中写入给定的 RLE 数据，并返回 (输入路径, 输出路径)。
+    fn write_temp(name: &str, data: &[u8]) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!("rle_test_{}_{name}.rle", process::id()));
+        let output = dir.join(format!("rle_test_{}_{name}.out", process::id()));
+        fs::write(&input, data).unwrap();
+        (input, output)
+    }
+
+    fn decode_bytes(name: &str, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let (input, output) = write_temp(name, data);
+        let result = rle_decode_file(input.to_str().unwrap(), output.to_str().unwrap())
+            .and_then(|_| Ok(fs::read(&output)?));
+        let _ = fs::remove_file(&input);
+        let _ = fs::remove_file(&output);
+        result
+    }
+
+    #[test]
+    fn decode_valid_records() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.push(b'a');
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.push(b'b');
+        assert_eq!(decode_bytes("valid", &data).unwrap(), b"aaab");
+    }
+
+    #[test]
+    fn encode_with_max_run_splits_long_runs() {
+        let mut data = vec![b'a'; 1000];
+        data.extend_from_slice(b"bc");
+        data.extend(vec![b'd'; 255]);
+        let (input, encoded) = write_temp("max_run", &data);
+        rle_encode_file_with_max_run(input.to_str().unwrap(), encoded.to_str().unwrap(), 255)
+            .unwrap();
+        let enc = fs::read(&encoded).unwrap();
+
+        let records: Vec<(u32, u8)> = enc
+            .chunks(5)
+            .map(|r| (u32::from_le_bytes([r[0], r[1], r[2], r[3]]), r[4]))
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                (255, b'a'),
+                (255, b'a'),
+                (255, b'a'),
+                (235, b'a'),
+                (1, b'b'),
+                (1, b'c'),
+                (255, b'd'),
+            ]
+        );
+        assert_bytes_eq(&data, &decode_bytes("max_run_decode", &enc).unwrap());
+        let _ = fs::remove_file(&input);
+        let _ = fs::remove_file(&encoded);
+    }
+
+    #[test]
+    fn push_encoder_matches_file_encoder() {
+        let mut data = vec![b'a'; 300];
+        data.extend_from_slice(b"abba");
+        data.extend(vec![0u8; 70]);
+        data.push(b'z');
+        for max_run in [u32::MAX, 64] {
+            let (input, encoded) = write_temp("push", &data);
+            rle_encode_file_with_max_run(
+                input.to_str().unwrap(),
+                encoded.to_str().unwrap(),
+                max_run,
+            )
+            .unwrap();
+            let expected = fs::read(&encoded).unwrap();
+            let _ = fs::remove_file(&input);
+            let _ = fs::remove_file(&encoded);
+
+            let mut encoder = RleEncoder::with_max_run(Vec::new(), max_run).unwrap();
+            for &b in &data {
+                encoder.push(b).unwrap();
+            }
+            assert_eq!(encoder.finish().unwrap(), expected);
+        }
+        assert!(RleEncoder::new(Vec::new()).finish().unwrap().is_empty());
+    }
+
+    #[test]
+    fn encode_with_zero_max_run_rejected() {
+        let (input, encoded) = write_temp("max_run_zero", b"abc");
+        let err =
+            rle_encode_file_with_max_run(input.to_str().unwrap(), encoded.to_str().unwrap(), 0)
+                .unwrap_err();
+        assert!(matches!(err, Error::InvalidInput(_)), "{err}");
+        let _ = fs::remove_file(&input);
+        let _ = fs::remove_file(&encoded);
+    }
+
+    #[test]
+    fn decode_missing_value_byte() {
+        let data = 5u32.to_le_bytes();
+        let err = decode_bytes("missing_value", &data).unwrap_err();
+        assert!(
+            matches!(err, Error::Truncated("rle: missing value byte")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn decode_embedded_zero_count() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.push(b'x');
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.push(b'y');
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.push(b'z');
+        let err = decode_bytes("embedded_zero", &data).unwrap_err();
+        assert!(
+            matches!(err, Error::InvalidData("rle: zero count")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn decode_zero_count_at_eof() {
+        // 末尾的 count 为 0 且缺少 value：优先报告 count 非法，而不是截断。
+        let mut data = Vec::new();
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.push(b'x');
+        data.extend_from_slice(&0u32.to_le_bytes());
+        let err = decode_bytes("zero_at_eof", &data).unwrap_err();
+        assert!(
+            matches!(err, Error::InvalidData("rle: zero count")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn decode_limits_enforced() {
+        // 9 字节的输入声称要解出 4 GiB：在写出之前就按上限拒绝。
+        let mut data = Vec::new();
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.push(b'a');
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        data.push(b'b');
+        let (input, output) = write_temp("limits", &data);
+        let decode = |max_memory: u64| {
+            rle_decode_file_with_limits(
+                input.to_str().unwrap(),
+                output.to_str().unwrap(),
+                &DecodeLimits::new(max_memory),
+            )
+        };
+        let err = decode(1 << 20).unwrap_err();
+        assert!(matches!(err, Error::LimitExceeded(_)), "{err}");
+        assert_eq!(fs::metadata(&output).unwrap().len(), 3);
+
+        data.truncate(5);
+        fs::write(&input, &data).unwrap();
+        decode(3).unwrap();
+        assert!(matches!(decode(2), Err(Error::LimitExceeded(_))));
+        let _ = fs::remove_file(&input);
+        let _ = fs::remove_file(&output);
+    }
+
+    #[test]
+    fn oversized_counts_rejected() {
+        // 手工构造的记录声称 count = u32::MAX。
+        let mut data = rle_encode(b"ok");
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        data.push(b'x');
+        let err = rle_decode_with_limit(&data, 1 << 20).unwrap_err();
+        assert!(matches!(err, Error::LimitExceeded(_)), "{err}");
+        assert_eq!(rle_decode_with_limit(&data[..10], 2).unwrap(), b"ok");
+        assert!(matches!(
+            rle_decode_with_limit(&data[..10], 1),
+            Err(Error::LimitExceeded(_))
+        ));
+
+        let (input, output) = write_temp("max_run_ceiling", &data);
+        let decode = |max_run: u32| {
+            rle_decode_file_with_max_run(
+                input.to_str().unwrap(),
+                output.to_str().unwrap(),
+                &DecodeLimits::unlimited(),
+                max_run,
+            )
+        };
+        assert!(matches!(
+            decode(65535),
+            Err(Error::InvalidData("rle: count exceeds max_run"))
+        ));
+        assert!(matches!(decode(0), Err(Error::InvalidInput(_))));
+        fs::write(&input, &data[..10]).unwrap();
+        decode(1).unwrap();
+        assert_eq!(fs::read(&output).unwrap(), b"ok");
+        let _ = fs::remove_file(&input);
+        let _ = fs::remove_file(&output);
+    }
+
+    #[test]
+    fn word_mode_keeps_sample_runs() {
+        // 两种 16 位采样交替成段：字节 RLE 的每个采样拆成两条记录，16 位模式每段一条。
+        let samples: Vec<u8> = (0..4000u32)
+            .flat_map(|i| if i / 100 % 2 == 0 { 0x1234u16 } else { 0xFF00 }.to_le_bytes())
+            .collect();
+        let encoded = rle_encode_u16_le(&samples).unwrap();
+        assert_eq!(&encoded[..5], b"RLEW\x02");
+        assert_eq!(encoded.len(), 5 + 40 * 6);
+        assert!(encoded.len() * 50 < rle_encode(&samples).len());
+        assert_bytes_eq(&samples, &rle_decode_u16_le(&encoded).unwrap());
+
+        let mixed = b"\x01\x01\x01\x01\x01\x02ab";
+        let encoded = rle_encode_u16_le(mixed).unwrap();
+        assert_eq!(encoded.len(), 5 + 3 * 6);
+        assert_eq!(rle_decode_u16_le(&encoded).unwrap(), mixed);
+        assert_eq!(rle_encode_u16_le(&[]).unwrap(), b"RLEW\x02");
+        assert!(rle_decode_u16_le(b"RLEW\x02").unwrap().is_empty());
+
+        assert!(matches!(
+            rle_encode_u16_le(b"abc"),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            rle_decode_u16_le(&encoded[..encoded.len() - 1]),
+            Err(Error::Truncated(_))
+        ));
+        let mut wide = encoded.clone();
+        wide[4] = 4;
+        assert!(matches!(
+            rle_decode_u16_le(&wide),
+            Err(Error::InvalidData(_))
+        ));
+        assert!(matches!(
+            rle_decode_u16_le(&rle_encode_framed(b"xy")),
+            Err(Error::BadMagic(_))
+        ));
+
+        // 一条 count = u32::MAX 的记录声明 8 GiB：在展开之前拒绝。
+        let mut bomb = b"RLEW\x02".to_vec();
+        bomb.extend_from_slice(&u32::MAX.to_le_bytes());
+        bomb.extend_from_slice(b"ab");
+        assert!(matches!(
+            rle_decode_u16_le_with_limit(&bomb, 1 << 20),
+            Err(Error::LimitExceeded(_))
+        ));
+        let words = rle_encode_u16_le(&samples).unwrap();
+        let limit = samples.len() as u64;
+        assert_bytes_eq(
+            &samples,
+            &rle_decode_u16_le_with_limit(&words, limit).unwrap(),
+        );
+        assert!(matches!(
+            rle_decode_u16_le_with_limit(&words, limit - 1),
+            Err(Error::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn decode_partial_count_bytes() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.push(b'x');
+        data.extend_from_slice(&[0x01, 0x00]);
+        let err = decode_bytes("partial_count", &data).unwrap_err();
+        assert!(
+            matches!(err, Error::Truncated("rle: truncated count field")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn missing_input_reports_io_error() {
+        use std::error::Error as _;
+        let (input, output) = write_temp("missing", b"");
+        let _ = fs::remove_file(&input);
+        let missing = std::env::temp_dir().join(format!("rle_test_{}_absent", process::id()));
+        let err = rle_decode_file(missing.to_str().unwrap(), output.to_str().unwrap()).unwrap_err();
+        assert!(matches!(err, Error::Io(_)), "{err}");
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn slice_api_matches_file_format() {
+        let data = b"aaaabcccccccd\0\0".repeat(50);
+        let encoded = rle_encode(&data);
+        assert_bytes_eq(&data, &rle_decode(&encoded).unwrap());
+        assert_bytes_eq(&data, &decode_bytes("slice", &encoded).unwrap());
+        assert!(rle_encode(b"").is_empty());
+        assert!(rle_decode(b"").unwrap().is_empty());
+
+        // 去掉最后一条记录的取值字节。
+        let err = rle_decode(&encoded[..encoded.len() - 1]).unwrap_err();
+        assert!(
+            matches!(err, Error::Truncated("rle: missing value byte")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn framed_format_adds_magic() {
+        let data = b"aaaabbbcccd".repeat(10);
+        let framed = rle_encode_framed(&data);
+        assert_eq!(&framed[..4], b"RLE0");
+        assert_eq!(&framed[4..], rle_encode(&data));
+        assert_bytes_eq(&data, &rle_decode_framed(&framed).unwrap());
+        assert!(matches!(rle_decode_framed(b"RL"), Err(Error::Truncated(_))));
+        assert!(matches!(
+            rle_decode_framed(&rle_encode(&data)),
+            Err(Error::BadMagic(_))
+        ));
+    }
+
+    #[test]
+    fn varint_counts_shrink_short_runs() {
+        // 典型的短 run：大多数 count 小于 128，每条记录 2 字节而不是 5 字节。
+        let mut data = Vec::new();
+        for i in 0..2000u32 {
+            data.extend(std::iter::repeat_n(
+                b'a' + (i % 7) as u8,
+                1 + (i % 5) as usize,
+            ));
+        }
+        data.extend(vec![b'z'; 100_000]);
+        let varint = rle_encode_varint(&data);
+        assert_bytes_eq(&data, &rle_decode_varint(&varint).unwrap());
+        let fixed = rle_encode(&data);
+        assert!(
+            varint.len() * 2 < fixed.len(),
+            "{} vs {}",
+            varint.len(),
+            fixed.len()
+        );
+        // 100000 = 0xA0 0x8D 0x06。
+        assert_eq!(&varint[varint.len() - 4..], [0xA0, 0x8D, 0x06, b'z']);
+
+        for data in [&b""[..], b"a", b"abc", &[7u8; 128], &[7u8; 129]] {
+            assert_bytes_eq(data, &rle_decode_varint(&rle_encode_varint(data)).unwrap());
+        }
+        assert!(matches!(
+            rle_decode_varint(&[0x00, b'a']),
+            Err(Error::InvalidData(_))
+        ));
+        assert!(matches!(
+            rle_decode_varint(&[0x85]),
+            Err(Error::Truncated(_))
+        ));
+        assert!(matches!(
+            rle_decode_varint(&[0x03]),
+            Err(Error::Truncated(_))
+        ));
+        // u32::MAX 可以表示，再多一位即溢出。
+        let mut max = Vec::new();
+        write_varint(&mut max, u32::MAX).unwrap();
+        assert_eq!(max, [0xFF, 0xFF, 0xFF, 0xFF, 0x0F]);
+        assert_eq!(read_varint(&mut &max[..]).unwrap(), Some(u32::MAX));
+        assert!(matches!(
+            rle_decode_varint(&[0xFF, 0xFF, 0xFF, 0xFF, 0x1F, b'a']),
+            Err(Error::InvalidData(_))
+        ));
+        assert!(matches!(
+            rle_decode_varint(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x01]),
+            Err(Error::InvalidData(_))
+        ));
+
+        // 6 字节的记录声明 u32::MAX 个字节：在展开之前拒绝。
+        let mut bomb = max.clone();
+        bomb.push(b'a');
+        assert!(matches!(
+            rle_decode_varint_with_limit(&bomb, 1 << 20),
+            Err(Error::LimitExceeded(_))
+        ));
+        let varint = rle_encode_varint(&data);
+        let limit = data.len() as u64;
+        assert_bytes_eq(
+            &data,
+            &rle_decode_varint_with_limit(&varint, limit).unwrap(),
+        );
+        assert!(matches!(
+            rle_decode_varint_with_limit(&varint, limit - 1),
+            Err(Error::LimitExceeded(_))
+        ));
+    }
+}
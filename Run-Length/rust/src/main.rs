@@ -0,0 +1,39 @@
+use encoding_common::{cli, selftest};
+use rle::{rle_decode_file, rle_encode_file};
+use std::env;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() == 2 && args[1] == "selftest" {
+        let ok = selftest::run("rle", |data| {
+            selftest::roundtrip_via_files(data, rle_encode_file, rle_decode_file)
+        });
+        process::exit(if ok { 0 } else { 1 });
+    }
+    if args.len() != 4 {
+        eprintln!("用法: {} encode|decode input output", args[0]);
+        eprintln!("      {} selftest", args[0]);
+        eprintln!("input / output 为 - 时读写标准输入 / 标准输出");
+        process::exit(1);
+    }
+
+    let mode = &args[1];
+    let input_path = &args[2];
+    let output_path = &args[3];
+
+    let result = match mode.as_str() {
+        "encode" => rle_encode_file(input_path, output_path),
+        "decode" => rle_decode_file(input_path, output_path),
+        _ => {
+            eprintln!("未知模式，应为 encode 或 decode");
+            process::exit(1);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("运行失败: {e}");
+        process::exit(1);
+    }
+    cli::report_sizes(mode, input_path, output_path);
+}
@@ -0,0 +1,210 @@
+use crate::Error;
+
+// Apple PackBits（Macintosh Technical Note TN1023，TIFF 6.0 第 9 节压缩方式 32773）。
+// 控制字节 n 视为有符号 8 位整数：
+//   0..=127   之后 n + 1 个字节按原样复制；
+//   -127..=-1 之后的 1 个字节重复 1 - n 次（无符号即 257 - n 次）；
+//   -128      空操作，解码时跳过。
+// 单条记录最多 128 字节。编码器不产生 -128。
+const MAX_RECORD: usize = 128;
+const NOOP: u8 = 0x80;
+
+// 长度不少于 3 的重复段编码为重复记录；长度为 2 的重复段在没有待写字面量时也编码为重复记录
+// （2 字节，不劣于字面量），否则并入当前字面量，避免打断字面量记录。
+pub fn packbits_encode(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len() + input.len() / MAX_RECORD + 1);
+    let mut literal_start = 0;
+    let mut i = 0;
+    while i < input.len() {
+        let mut run = 1;
+        while i + run < input.len() && run < MAX_RECORD && input[i + run] == input[i] {
+            run += 1;
+        }
+        if run >= 3 || (run == 2 && literal_start == i) {
+            flush_literal(&mut out, &input[literal_start..i]);
+            out.push((257 - run) as u8);
+            out.push(input[i]);
+            i += run;
+            literal_start = i;
+        } else {
+            i += run;
+            if i - literal_start >= MAX_RECORD {
+                flush_literal(&mut out, &input[literal_start..literal_start + MAX_RECORD]);
+                literal_start += MAX_RECORD;
+            }
+        }
+    }
+    flush_literal(&mut out, &input[literal_start..]);
+    out
+}
+
+fn flush_literal(out: &mut Vec<u8>, literal: &[u8]) {
+    for chunk in literal.chunks(MAX_RECORD) {
+        out.push((chunk.len() - 1) as u8);
+        out.extend_from_slice(chunk);
+    }
+}
+
+pub fn packbits_decode(encoded: &[u8]) -> Result<Vec<u8>, Error> {
+    packbits_decode_with_limit(encoded, u64::MAX)
+}
+
+// 与 packbits_decode 相同，但输出将超过 max_output 字节时在展开该记录之前返回 LimitExceeded。
+pub fn packbits_decode_with_limit(encoded: &[u8], max_output: u64) -> Result<Vec<u8>, Error> {
+    let capacity = usize::try_from(max_output).unwrap_or(usize::MAX);
+    let mut out = Vec::with_capacity(encoded.len().saturating_mul(2).min(capacity));
+    let mut pos = 0;
+    while pos < encoded.len() {
+        let control = encoded[pos];
+        pos += 1;
+        match control {
+            0..=127 => {
+                let len = control as usize + 1;
+                let literal = encoded
+                    .get(pos..pos + len)
+                    .ok_or(Error::Truncated("rle: truncated packbits literal"))?;
+                if (out.len() + len) as u64 > max_output {
+                    return Err(Error::LimitExceeded("rle: output limit exceeded"));
+                }
+                out.extend_from_slice(literal);
+                pos += len;
+            }
+            NOOP => {}
+            _ => {
+                let &value = encoded
+                    .get(pos)
+                    .ok_or(Error::Truncated("rle: missing value byte"))?;
+                let run = 257 - control as usize;
+                if (out.len() + run) as u64 > max_output {
+                    return Err(Error::LimitExceeded("rle: output limit exceeded"));
+                }
+                out.resize(out.len() + run, value);
+                pos += 1;
+            }
+        }
+    }
+    Ok(out)
+}
+
+// 带魔数的封装：魔数 RLPB + packbits_encode 的输出，便于与经典 RLE 格式区分。
+// 不可压缩的数据每 128 字节只多 1 个控制字节，而经典格式中每个字节都要 5 字节。
+const PACKBITS_MAGIC: &[u8; 4] = b"RLPB";
+
+pub fn rle_encode_packbits(input: &[u8]) -> Vec<u8> {
+    let mut out = PACKBITS_MAGIC.to_vec();
+    out.extend_from_slice(&packbits_encode(input));
+    out
+}
+
+pub fn rle_decode_packbits(encoded: &[u8]) -> Result<Vec<u8>, Error> {
+    rle_decode_packbits_with_limit(encoded, u64::MAX)
+}
+
+pub fn rle_decode_packbits_with_limit(encoded: &[u8], max_output: u64) -> Result<Vec<u8>, Error> {
+    match encoded.strip_prefix(PACKBITS_MAGIC) {
+        Some(body) => packbits_decode_with_limit(body, max_output),
+        None if encoded.len() < 4 => Err(Error::Truncated("rle: truncated header")),
+        None => Err(Error::BadMagic("rle: bad magic")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_apple_reference_vector() {
+        // TN1023 中的示例。
+        let unpacked = [
+            0xAA, 0xAA, 0xAA, 0x80, 0x00, 0x2A, 0xAA, 0xAA, 0xAA, 0xAA, 0x80, 0x00, 0x2A, 0x22,
+            0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA,
+        ];
+        let packed = [
+            0xFE, 0xAA, 0x02, 0x80, 0x00, 0x2A, 0xFD, 0xAA, 0x03, 0x80, 0x00, 0x2A, 0x22, 0xF7,
+            0xAA,
+        ];
+        assert_eq!(packbits_encode(&unpacked), packed);
+        assert_eq!(packbits_decode(&packed).unwrap(), unpacked);
+
+        // 128 为空操作，可出现在任意记录之间。
+        let with_noop = [0x80, 0xFE, 0xAA, 0x80, 0x80, 0x00, 0x2A];
+        assert_eq!(
+            packbits_decode(&with_noop).unwrap(),
+            [0xAA, 0xAA, 0xAA, 0x2A]
+        );
+    }
+
+    #[test]
+    fn record_limits_and_errors() {
+        // 130 字节的 run 拆成 128 + 2；129 字节的字面量拆成 128 + 1。
+        let run = vec![b'A'; 130];
+        assert_eq!(packbits_encode(&run), [0x81, b'A', 0xFF, b'A']);
+        let literal: Vec<u8> = (0..129u32).map(|i| (i % 2) as u8).collect();
+        let packed = packbits_encode(&literal);
+        assert_eq!(packed.len(), 1 + 128 + 1 + 1);
+        assert_eq!((packed[0], packed[129]), (0x7F, 0x00));
+        for data in [
+            &run[..],
+            &literal[..],
+            b"",
+            b"a",
+            b"aab",
+            b"abba",
+            b"abbbcc",
+        ] {
+            assert_eq!(packbits_decode(&packbits_encode(data)).unwrap(), data);
+        }
+
+        assert!(matches!(
+            packbits_decode(&[0x02, 1, 2]),
+            Err(Error::Truncated(_))
+        ));
+        assert!(matches!(packbits_decode(&[0xFE]), Err(Error::Truncated(_))));
+
+        // 上限按整条记录检查：130 字节的 run 在第二条记录处超出。
+        let packed = packbits_encode(&run);
+        assert_eq!(packbits_decode_with_limit(&packed, 130).unwrap(), run);
+        for max_output in [129, 128, 0] {
+            assert!(matches!(
+                packbits_decode_with_limit(&packed, max_output),
+                Err(Error::LimitExceeded(_))
+            ));
+        }
+        assert!(matches!(
+            rle_decode_packbits_with_limit(&rle_encode_packbits(&literal), 128),
+            Err(Error::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn framed_packbits_bounds_expansion() {
+        let random = encoding_common::selftest::pseudo_random(10_000, 0x5EED);
+        let encoded = rle_encode_packbits(&random);
+        assert_eq!(&encoded[..4], b"RLPB");
+        // 随机数据中偶尔出现的长度为 2 的重复并入字面量，开销只有控制字节。
+        assert!(encoded.len() <= 4 + random.len() + random.len().div_ceil(MAX_RECORD));
+        assert_eq!(rle_decode_packbits(&encoded).unwrap(), random);
+
+        let same = vec![0x42u8; 10_000];
+        let encoded = rle_encode_packbits(&same);
+        assert_eq!(encoded.len(), 4 + 2 * same.len().div_ceil(MAX_RECORD));
+        assert_eq!(rle_decode_packbits(&encoded).unwrap(), same);
+
+        // 交替的两个字节没有重复，整段作为字面量。
+        let alternating: Vec<u8> = (0..1000u32)
+            .map(|i| if i % 2 == 0 { 1 } else { 2 })
+            .collect();
+        let encoded = rle_encode_packbits(&alternating);
+        assert_eq!(encoded.len(), 4 + 1000 + 1000usize.div_ceil(MAX_RECORD));
+        assert_eq!(rle_decode_packbits(&encoded).unwrap(), alternating);
+
+        assert!(matches!(
+            rle_decode_packbits(b"RL"),
+            Err(Error::Truncated(_))
+        ));
+        assert!(matches!(
+            rle_decode_packbits(&packbits_encode(&same)),
+            Err(Error::BadMagic(_))
+        ));
+    }
+}
@@ -0,0 +1,12 @@
+use std::process::Command;
+
+#[test]
+fn selftest_passes_on_healthy_build() {
+    let output = Command::new(env!("CARGO_BIN_EXE_rle_rust"))
+        .arg("selftest")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("PASS") && !stdout.contains("FAIL"), "{stdout}");
+}
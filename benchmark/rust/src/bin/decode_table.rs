@@ -0,0 +1,69 @@
+use encoding_bench::{measure, TrackingAllocator};
+use encoding_common::selftest::pseudo_random;
+use rangecoder::{decode_with_options, encode, DecodeOptions};
+use std::time::Instant;
+
+#[global_allocator]
+static GLOBAL: TrackingAllocator = TrackingAllocator;
+
+// 每种数据 8 MiB，取 5 次中最快的一次，数据由固定种子生成，结果便于逐次对比。
+const SIZE: usize = 8 << 20;
+const RUNS: usize = 5;
+
+// 稀疏（16 个符号）与完整（256 个符号）字母表，各有均匀与偏斜两种分布。
+fn cases() -> Vec<(&'static str, Vec<u8>)> {
+    let random = pseudo_random(SIZE, 0x2545_F491_4F6C_DD1D);
+    vec![
+        ("sparse-uniform", random.iter().map(|&r| r & 0x0F).collect()),
+        (
+            "sparse-skewed",
+            random
+                .iter()
+                .map(|&r| ((r as u32).pow(3) >> 20) as u8)
+                .collect(),
+        ),
+        ("full-uniform", random.clone()),
+        (
+            "full-skewed",
+            random
+                .iter()
+                .map(|&r| ((r as u32).pow(3) >> 16) as u8)
+                .collect(),
+        ),
+    ]
+}
+
+// 返回 (MiB/s, 解码期间的峰值内存)。
+fn bench(encoded: &[u8], expected: &[u8], opts: &DecodeOptions) -> (f64, usize) {
+    let (decoded, stats) = measure(|| decode_with_options(encoded, opts).expect("decode failed"));
+    assert!(decoded == expected, "decode mismatch");
+    drop(decoded);
+    let mut best = f64::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let decoded = decode_with_options(encoded, opts).expect("decode failed");
+        best = best.min(start.elapsed().as_secs_f64());
+        drop(decoded);
+    }
+    (expected.len() as f64 / (1024.0 * 1024.0) / best, stats.peak)
+}
+
+fn main() {
+    println!("Range decode: binary search vs lookup table");
+    println!("Input size: {} bytes per case, best of {} runs", SIZE, RUNS);
+    let search = DecodeOptions::new();
+    let table = DecodeOptions::new().with_lookup_table(true);
+    for (name, data) in cases() {
+        let encoded = encode(&data).expect("encode failed");
+        let (search_speed, search_peak) = bench(&encoded, &data, &search);
+        let (table_speed, table_peak) = bench(&encoded, &data, &table);
+        println!(
+            "{:<16} search: {:>8.2} MiB/s  table: {:>8.2} MiB/s  speedup: {:>5.2}x  table memory: {:>6} bytes",
+            name,
+            search_speed,
+            table_speed,
+            table_speed / search_speed,
+            table_peak.saturating_sub(search_peak)
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use encoding_bench::{measure, AllocStats, TrackingAllocator};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+#[global_allocator]
+static GLOBAL: TrackingAllocator = TrackingAllocator;
+
+fn make_test_data(size: usize) -> Vec<u8> {
+    let mut v = Vec::with_capacity(size);
+    for i in 0..size {
+        v.push(((i as u32 * 31 + 7) & 0xFF) as u8);
+    }
+    v
+}
+
+fn report(codec: &str, op: &str, stats: AllocStats) {
+    println!(
+        "{:<8} {:<12} peak: {:>12} bytes  total allocated: {:>12} bytes",
+        codec, op, stats.peak, stats.total
+    );
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("encoding_memory_{}_{name}", process::id()))
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let data = if args.len() >= 2 {
+        fs::read(&args[1]).unwrap_or_else(|e| {
+            eprintln!("无法读取输入文件 {}: {e}", args[1]);
+            process::exit(1);
+        })
+    } else {
+        make_test_data(16 << 20) // 16 MiB
+    };
+
+    let input = temp_path("input.bin");
+    let encoded = temp_path("encoded.bin");
+    let decoded = temp_path("decoded.bin");
+    fs::write(&input, &data).expect("write input failed");
+    let (input_s, encoded_s, decoded_s) = (
+        input.to_str().unwrap(),
+        encoded.to_str().unwrap(),
+        decoded.to_str().unwrap(),
+    );
+
+    println!("Peak memory benchmark");
+    println!("Input size: {} bytes", data.len());
+
+    // Range coder 以内存切片为输入/输出，统计包含输出缓冲区。
+    let (range_enc, stats) = measure(|| rangecoder::encode(&data).expect("range encode failed"));
+    report("range", "encode", stats);
+    let (range_dec, stats) = measure(|| rangecoder::decode(&range_enc).expect("range decode failed"));
+    report("range", "decode", stats);
+    assert_eq!(range_dec, data);
+    drop(range_enc);
+    drop(range_dec);
+
+    // 大量小消息：逐条 decode 与复用缓冲区的 RangeCoder::decode_reuse 对比累计分配量。
+    let messages: Vec<Vec<u8>> = data
+        .chunks(64)
+        .take(10_000)
+        .map(|chunk| rangecoder::encode(chunk).expect("range encode failed"))
+        .collect();
+    let (_, stats) = measure(|| {
+        for msg in &messages {
+            rangecoder::decode(msg).expect("range decode failed");
+        }
+    });
+    report("range", "decode-small", stats);
+    let mut coder = rangecoder::RangeCoder::new();
+    let mut out = Vec::new();
+    let (_, stats) = measure(|| {
+        for msg in &messages {
+            coder
+                .decode_reuse(msg, &mut out)
+                .expect("range decode failed");
+        }
+    });
+    report("range", "reuse-small", stats);
+    drop(messages);
+
+    // Huffman 与 RLE 为文件级接口，统计的是编码表与读写缓冲区。
+    let (res, stats) = measure(|| huffman::huffman_encode_file(input_s, encoded_s));
+    res.expect("huffman encode failed");
+    report("huffman", "encode", stats);
+    let (res, stats) = measure(|| huffman::huffman_decode_file(encoded_s, decoded_s));
+    res.expect("huffman decode failed");
+    report("huffman", "decode", stats);
+    assert_eq!(fs::read(&decoded).expect("read decoded failed"), data);
+
+    let (res, stats) = measure(|| rle::rle_encode_file(input_s, encoded_s));
+    res.expect("rle encode failed");
+    report("rle", "encode", stats);
+    let (res, stats) = measure(|| rle::rle_decode_file(encoded_s, decoded_s));
+    res.expect("rle decode failed");
+    report("rle", "decode", stats);
+    assert_eq!(fs::read(&decoded).expect("read decoded failed"), data);
+
+    let _ = fs::remove_file(&input);
+    let _ = fs::remove_file(&encoded);
+    let _ = fs::remove_file(&decoded);
+}
@@ -0,0 +1,94 @@
+use encoding_common::Error;
+use huffman::{huffman_decode, huffman_encode};
+use rle::{rle_decode, rle_encode};
+use std::time::Instant;
+
+// 与 range/rust/src/bin/bench.rs 相同的规模与输出格式，三个编码器的结果可以直接对比。
+// 全部使用内存接口，计时不含文件读写。
+const SIZE: usize = 1 << 20; // 1 MiB
+const ITERATIONS: usize = 20;
+
+type Encoder = fn(&[u8]) -> Result<Vec<u8>, Error>;
+type Decoder = fn(&[u8]) -> Result<Vec<u8>, Error>;
+
+// 与 range bench 的 make_test_data 相同的 i*31+7 序列。
+fn make_test_data(size: usize) -> Vec<u8> {
+    let mut v = Vec::with_capacity(size);
+    for i in 0..size {
+        v.push(((i as u32 * 31 + 7) & 0xFF) as u8);
+    }
+    v
+}
+
+// 伪序列对三个编码器的差异体现不出来，另外加入英文文本与长游程两种更接近实际的数据。
+fn cases() -> Vec<(&'static str, Vec<u8>)> {
+    let text = b"It was the best of times, it was the worst of times, it was the age of \
+                 wisdom, it was the age of foolishness, it was the epoch of belief. "
+        .iter()
+        .cycle()
+        .take(SIZE)
+        .copied()
+        .collect();
+    let runs = (0..SIZE)
+        .map(|i| b"aaaabbbbbbbbcc\x00"[i / 37 % 15])
+        .collect();
+    vec![
+        ("sequence", make_test_data(SIZE)),
+        ("text", text),
+        ("runs", runs),
+    ]
+}
+
+fn codecs() -> [(&'static str, Encoder, Decoder); 3] {
+    [
+        ("range coder", rangecoder::encode, rangecoder::decode),
+        ("huffman", |d| Ok(huffman_encode(d)), huffman_decode),
+        ("rle", |d| Ok(rle_encode(d)), rle_decode),
+    ]
+}
+
+fn bench(name: &str, case: &str, data: &[u8], encode: Encoder, decode: Decoder) {
+    let start_enc = Instant::now();
+    let mut encoded = Vec::new();
+    for _ in 0..ITERATIONS {
+        encoded = encode(data).expect("encode failed");
+    }
+    let enc_dur = start_enc.elapsed();
+
+    let start_dec = Instant::now();
+    let mut decoded = Vec::new();
+    for _ in 0..ITERATIONS {
+        decoded = decode(&encoded).expect("decode failed");
+    }
+    let dec_dur = start_dec.elapsed();
+
+    assert!(decoded == data, "{name}/{case}: decode mismatch");
+
+    let total_mb = (data.len() as f64 * ITERATIONS as f64) / (1024.0 * 1024.0);
+    let enc_secs = enc_dur.as_secs_f64();
+    let dec_secs = dec_dur.as_secs_f64();
+
+    println!("Rust {name} benchmark ({case})");
+    println!("Input size: {} bytes", data.len());
+    println!("Iterations: {}", ITERATIONS);
+    println!("Encoded size (last run): {} bytes", encoded.len());
+    println!(
+        "Encode time: {:.6} s, throughput: {:.2} MiB/s",
+        enc_secs,
+        total_mb / enc_secs
+    );
+    println!(
+        "Decode time: {:.6} s, throughput: {:.2} MiB/s",
+        dec_secs,
+        total_mb / dec_secs
+    );
+    println!();
+}
+
+fn main() {
+    for (case, data) in cases() {
+        for (name, encode, decode) in codecs() {
+            bench(name, case, &data, encode, decode);
+        }
+    }
+}
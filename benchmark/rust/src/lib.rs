@@ -0,0 +1,91 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// 统计内存分配的全局分配器包装。
+// 在二进制中加入下面一行即可启用（每个程序只能有一个全局分配器）：
+//
+//     #[global_allocator]
+//     static GLOBAL: encoding_bench::TrackingAllocator = encoding_bench::TrackingAllocator;
+//
+// 之后在待测操作前调用 reset()，操作结束后读取 snapshot()。
+pub struct TrackingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static TOTAL: AtomicUsize = AtomicUsize::new(0);
+
+fn record_alloc(size: usize) {
+    let now = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(now, Ordering::Relaxed);
+    TOTAL.fetch_add(size, Ordering::Relaxed);
+}
+
+fn record_dealloc(size: usize) {
+    CURRENT.fetch_sub(size, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record_dealloc(layout.size());
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocStats {
+    // 自 reset() 以来的内存占用高水位（相对 reset 时刻的基线）。
+    pub peak: usize,
+    // 自 reset() 以来累计分配的字节数。
+    pub total: usize,
+}
+
+// 以当前占用为基线，清零高水位与累计分配计数。
+pub fn reset() {
+    PEAK.store(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed);
+    TOTAL.store(0, Ordering::Relaxed);
+}
+
+// 读取自 reset() 以来的统计；baseline 为 reset 时刻的 current_bytes()。
+pub fn snapshot(baseline: usize) -> AllocStats {
+    AllocStats {
+        peak: PEAK.load(Ordering::Relaxed).saturating_sub(baseline),
+        total: TOTAL.load(Ordering::Relaxed),
+    }
+}
+
+pub fn current_bytes() -> usize {
+    CURRENT.load(Ordering::Relaxed)
+}
+
+// 运行 f 并返回其结果及期间的分配统计。
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, AllocStats) {
+    let baseline = current_bytes();
+    reset();
+    let value = f();
+    (value, snapshot(baseline))
+}
@@ -0,0 +1,568 @@
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+use encoding_common::symbol::{EOF_SYMBOL, SYMBOL_LIMIT};
+use rangecoder::lz;
+
+mod stream;
+pub use stream::{CompressWriter, DecompressReader};
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use encoding_common::{DecodeLimits, Error};
+
+// 本仓库 Rust 实现的文件格式：三种基础编码器、组合 LZSS 与区间编码的 LzRange，
+// 以及不压缩、原样保存的 Stored。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Codec {
+    Rle,
+    Huffman,
+    Range,
+    LzRange,
+    Stored,
+}
+
+impl Codec {
+    pub const ALL: [Codec; 5] = [
+        Codec::Rle,
+        Codec::Huffman,
+        Codec::Range,
+        Codec::LzRange,
+        Codec::Stored,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Codec::Rle => "rle",
+            Codec::Huffman => "huffman",
+            Codec::Range => "range",
+            Codec::LzRange => "lzrange",
+            Codec::Stored => "stored",
+        }
+    }
+
+    pub fn encode_file(self, input_path: &str, output_path: &str) -> Result<(), Error> {
+        match self {
+            Codec::Rle => rle::rle_encode_file(input_path, output_path),
+            Codec::Huffman => huffman::huffman_encode_file(input_path, output_path),
+            Codec::Range => rangecoder::encode_file(input_path, output_path),
+            Codec::LzRange => {
+                let encoded = lz::encode_lz(&fs::read(input_path)?, lz::DEFAULT_LZ_WINDOW_BITS)?;
+                fs::write(output_path, encoded).map_err(Into::into)
+            }
+            Codec::Stored => copy_file(input_path, output_path),
+        }
+    }
+
+    pub fn decode_file(self, input_path: &str, output_path: &str) -> Result<(), Error> {
+        match self {
+            Codec::Rle => rle::rle_decode_file(input_path, output_path),
+            Codec::Huffman => huffman::huffman_decode_file(input_path, output_path),
+            Codec::Range => rangecoder::decode_file(input_path, output_path),
+            Codec::LzRange => {
+                self.decode_file_with_limits(input_path, output_path, &DecodeLimits::unlimited())
+            }
+            Codec::Stored => copy_file(input_path, output_path),
+        }
+    }
+
+    // 在内存中编码，输出与 encode_file 对同一数据的结果相同。
+    pub fn encode_bytes(self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            Codec::Rle => Ok(rle::rle_encode(input)),
+            Codec::Huffman => Ok(huffman::huffman_encode(input)),
+            Codec::Range => rangecoder::encode(input),
+            Codec::LzRange => lz::encode_lz(input, lz::DEFAULT_LZ_WINDOW_BITS),
+            Codec::Stored => Ok(input.to_vec()),
+        }
+    }
+
+    // 在内存中解码，limits 的含义同 decode_file_with_limits。
+    pub fn decode_bytes(self, encoded: &[u8], limits: &DecodeLimits) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::new();
+        match self {
+            Codec::Rle => rle::rle_decode_reader(encoded, &mut out, limits)?,
+            Codec::Huffman => huffman::huffman_decode_reader(encoded, &mut out, limits)?,
+            Codec::Range => out = rangecoder::decode_with_limits(encoded, limits)?,
+            Codec::LzRange => out = lz::decode_lz_with_limits(encoded, limits)?,
+            Codec::Stored => {
+                if encoded.len() as u64 > limits.max_memory() {
+                    return Err(Error::LimitExceeded("codec: output limit exceeded"));
+                }
+                out = encoded.to_vec();
+            }
+        }
+        Ok(out)
+    }
+
+    // 在 limits 约束下解码，各编码器按各自的表大小与输出大小计入上限。
+    pub fn decode_file_with_limits(
+        self,
+        input_path: &str,
+        output_path: &str,
+        limits: &DecodeLimits,
+    ) -> Result<(), Error> {
+        match self {
+            Codec::Rle => rle::rle_decode_file_with_limits(input_path, output_path, limits),
+            Codec::Huffman => {
+                huffman::huffman_decode_file_with_limits(input_path, output_path, limits)
+            }
+            Codec::Range => rangecoder::decode_file_with_limits(input_path, output_path, limits),
+            Codec::LzRange => {
+                let decoded = lz::decode_lz_with_limits(&fs::read(input_path)?, limits)?;
+                fs::write(output_path, decoded).map_err(Into::into)
+            }
+            Codec::Stored => {
+                if fs::metadata(input_path)?.len() > limits.max_memory() {
+                    return Err(Error::LimitExceeded("codec: output limit exceeded"));
+                }
+                copy_file(input_path, output_path)
+            }
+        }
+    }
+}
+
+// 内存编解码的统一接口，可以存为 Box<dyn ByteCodec> 在运行时切换算法，
+// 调用方也可以为自己的编码器实现它，与内置编码器放在一起使用。
+// 内置编码器即 Codec 的各个取值（Codec 枚举占用了 Codec 这个名字）：
+//     let codecs: Vec<Box<dyn ByteCodec>> = vec![Box::new(Codec::Range), Box::new(Codec::Huffman)];
+pub trait ByteCodec {
+    fn name(&self) -> &str;
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>, Error>;
+    fn decode(&self, encoded: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+impl ByteCodec for Codec {
+    fn name(&self) -> &str {
+        Codec::name(*self)
+    }
+
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        self.encode_bytes(input)
+    }
+
+    fn decode(&self, encoded: &[u8]) -> Result<Vec<u8>, Error> {
+        self.decode_bytes(encoded, &DecodeLimits::unlimited())
+    }
+}
+
+fn copy_file(input_path: &str, output_path: &str) -> Result<(), Error> {
+    fs::copy(input_path, output_path)?;
+    Ok(())
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Codec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Codec::ALL
+            .into_iter()
+            .find(|c| c.name() == s)
+            .ok_or(Error::InvalidInput("codec: unknown codec name"))
+    }
+}
+
+// recommend_codec 不做试编码，而是由直方图与 run 数估计各编码器的输出大小：
+//   - RLE：每段 run 一条 5 字节记录（4 字节计数 + 1 字节取值）；
+//   - Huffman：1032 字节的频率表头部 + 按实际哈夫曼码长计算的比特数；
+//   - range：1036 字节的频率表头部 + 按零阶熵计算的比特数（区间编码几乎达到熵）。
+// 取估计最小者。Huffman 码长与熵之差即两种熵编码器的差距，range 只有比 Huffman
+// 至少小 RANGE_MARGIN 时才选（Huffman 解码更快）；最佳估计不小于原长的 STORED_RATIO 时
+// 压缩收益不足，选 Stored。
+const HUFFMAN_HEADER_BYTES: f64 = 1032.0;
+const RANGE_HEADER_BYTES: f64 = 1036.0;
+const RLE_RECORD_BYTES: f64 = 5.0;
+const RANGE_MARGIN: f64 = 0.01;
+const STORED_RATIO: f64 = 0.95;
+
+pub fn recommend_codec(input: &[u8]) -> Codec {
+    if input.is_empty() {
+        return Codec::Stored;
+    }
+    let mut freq = vec![0u32; SYMBOL_LIMIT];
+    let mut runs = 1usize;
+    for (i, &b) in input.iter().enumerate() {
+        freq[b as usize] += 1;
+        if i > 0 && b != input[i - 1] {
+            runs += 1;
+        }
+    }
+    freq[EOF_SYMBOL] = 1;
+
+    let n = input.len() as f64;
+    let lengths = huffman::build_code_lengths(&freq);
+    let mut huffman_bits = 0.0;
+    let mut entropy_bits = 0.0;
+    for (&f, &len) in freq[..EOF_SYMBOL]
+        .iter()
+        .zip(&lengths)
+        .filter(|(&f, _)| f > 0)
+    {
+        huffman_bits += f as f64 * len as f64;
+        entropy_bits -= f as f64 * (f as f64 / n).log2();
+    }
+    let huffman = HUFFMAN_HEADER_BYTES + huffman_bits / 8.0;
+    let range = RANGE_HEADER_BYTES + entropy_bits / 8.0;
+    let rle = runs as f64 * RLE_RECORD_BYTES;
+
+    let (entropy_codec, entropy_size) = match range < huffman * (1.0 - RANGE_MARGIN) {
+        true => (Codec::Range, range),
+        false => (Codec::Huffman, huffman),
+    };
+    let (best, size) = match rle < entropy_size {
+        true => (Codec::Rle, rle),
+        false => (entropy_codec, entropy_size),
+    };
+    match size < n * STORED_RATIO {
+        true => best,
+        false => Codec::Stored,
+    }
+}
+
+// 按 recommend_codec 的建议压缩 input_path，返回所用的编码器，解码时需用同一编码器。
+pub fn compress_file(input_path: &str, output_path: &str) -> Result<Codec, Error> {
+    let codec = recommend_codec(&fs::read(input_path)?);
+    codec.encode_file(input_path, output_path)?;
+    Ok(codec)
+}
+
+// 用 from 解码 input_path，再用 to 编码写入 output_path，原始字节保持不变。
+// 中间结果写入 output_path 旁的临时文件，结束后删除。
+pub fn transcode(input_path: &str, from: Codec, to: Codec, output_path: &str) -> Result<(), Error> {
+    let temp_path = format!("{output_path}.transcode.tmp");
+    let result = from
+        .decode_file(input_path, &temp_path)
+        .and_then(|_| to.encode_file(&temp_path, output_path));
+    let _ = fs::remove_file(&temp_path);
+    result
+}
+
+// decode_any 默认的资源上限（1 GiB）。RCNS、RLE 等格式只用几个字节就能声明任意长度的输出。
+pub const DEFAULT_MAX_OUTPUT: u64 = 1 << 30;
+
+// 按数据开头的 4 字节魔数识别格式并解码，适合不知道数据由哪个编码器产生的场合：
+//   - RLE0 / RLPB / RLEW：带魔数的 RLE、PackBits 与 16 位单元 RLE（经典 RLE 没有魔数，无法识别）；
+//   - HFMN / HFM2 / HFMC / HFMA：Huffman 的默认、canonical 码长、带 CRC32 与自适应（FGK）格式；
+//   - RCNL：LZSS + 区间编码；RCN1：order-1 上下文模型；RCNZ：定长区间编码；RCNK：上下文混合；
+//     RCNT：带检查点的流；RCNR：游程模型；RCNP：并行分块；
+//   - 其余 RCN* 交给 rangecoder::decode（RCNC、稀疏表、单字节、外层容器等）。需要额外参数的格式
+//     （字典 RCND、预置前缀 RCNH、差分 RCNX、自定义模型 RCNA）无法仅凭数据解码，多路交错 RCNI 解出的是多个流，
+//     都由 rangecoder::decode 报告错误。
+// 无法识别时返回 BadMagic，不足 4 字节时返回 Truncated。
+// 输入多为不可信数据，输出以 DEFAULT_MAX_OUTPUT 为上限，超出时返回 LimitExceeded。
+pub fn decode_any(input: &[u8]) -> Result<Vec<u8>, Error> {
+    decode_any_with_limits(input, &DecodeLimits::new(DEFAULT_MAX_OUTPUT))
+}
+
+// 与 decode_any 相同，但 limits 交给识别出的解码器，各解码器按各自的表大小与输出大小计入上限；
+// 只接受输出上限的解码器以 max_memory 作为输出上限。
+pub fn decode_any_with_limits(input: &[u8], limits: &DecodeLimits) -> Result<Vec<u8>, Error> {
+    let magic: &[u8; 4] = input
+        .get(..4)
+        .and_then(|m| m.try_into().ok())
+        .ok_or(Error::Truncated("codec: input too short"))?;
+    let max_output = limits.max_memory();
+    let max_bytes = usize::try_from(max_output).unwrap_or(usize::MAX);
+    match magic {
+        b"RLE0" => rle::rle_decode_framed_with_limit(input, max_output),
+        b"RLPB" => rle::rle_decode_packbits_with_limit(input, max_output),
+        b"RLEW" => rle::rle_decode_u16_le_with_limit(input, max_output),
+        b"HFMN" => {
+            let mut out = Vec::new();
+            huffman::huffman_decode_reader(input, &mut out, limits)?;
+            Ok(out)
+        }
+        b"HFM2" => huffman::huffman_decode_canonical_with_limit(input, max_bytes),
+        b"HFMC" => huffman::huffman_decode_checked_with_limit(input, max_bytes),
+        b"HFMA" => huffman::huffman_decode_adaptive_with_limit(input, max_bytes),
+        b"RCNL" => lz::decode_lz_with_limits(input, limits),
+        b"RCN1" => rangecoder::order1::decode_order1_with_limits(input, limits),
+        b"RCNZ" => rangecoder::decode_sized_with_limits(input, limits),
+        b"RCNK" => rangecoder::cm::decode_cm_with_limits(input, limits),
+        b"RCNT" => rangecoder::decode_checkpointed_with_limits(input, limits),
+        b"RCNR" => rangecoder::decode_rle_model_with_limits(input, limits),
+        b"RCNP" => rangecoder::decode_parallel_with_limits(input, limits),
+        [b'R', b'C', b'N', _] => rangecoder::decode_with_limits(input, limits),
+        _ => Err(Error::BadMagic("codec: unknown format")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_common::selftest::pseudo_random;
+    use encoding_common::test_util::assert_bytes_eq;
+    use std::path::PathBuf;
+    use std::process;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("codec_test_{}_{name}", process::id()))
+    }
+
+    // 调用方自定义的编码器：逐字节取反。
+    struct Inverted;
+
+    impl ByteCodec for Inverted {
+        fn name(&self) -> &str {
+            "inverted"
+        }
+
+        fn encode(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+            Ok(input.iter().map(|b| !b).collect())
+        }
+
+        fn decode(&self, encoded: &[u8]) -> Result<Vec<u8>, Error> {
+            self.encode(encoded)
+        }
+    }
+
+    #[test]
+    fn boxed_codecs_roundtrip_interchangeably() {
+        let mut data = b"swap algorithms at runtime ".repeat(40);
+        data.extend(pseudo_random(2000, 0xB0C5));
+        let mut codecs: Vec<Box<dyn ByteCodec>> = Codec::ALL
+            .into_iter()
+            .map(|c| Box::new(c) as Box<dyn ByteCodec>)
+            .collect();
+        codecs.push(Box::new(Inverted));
+        for codec in &codecs {
+            let encoded = codec.encode(&data).unwrap();
+            assert_bytes_eq(&data, &codec.decode(&encoded).unwrap());
+        }
+        let names: Vec<&str> = codecs.iter().map(|c| c.name()).collect();
+        assert_eq!(
+            names,
+            ["rle", "huffman", "range", "lzrange", "stored", "inverted"]
+        );
+        // 与 encode_bytes 的输出相同。
+        assert_eq!(
+            ByteCodec::encode(&Codec::Huffman, &data).unwrap(),
+            Codec::Huffman.encode_bytes(&data).unwrap()
+        );
+    }
+
+    #[test]
+    fn decode_any_detects_format_by_magic() {
+        let mut data = b"sniffed by magic ".repeat(50);
+        data.extend(vec![7u8; 300]);
+        data.extend(pseudo_random(500, 0x0A11));
+        let encodings = [
+            rle::rle_encode_framed(&data),
+            rle::rle_encode_packbits(&data),
+            rle::rle_encode_u16_le(&data).unwrap(),
+            huffman::huffman_encode(&data),
+            huffman::huffman_encode_canonical(&data),
+            huffman::huffman_encode_checked(&data),
+            huffman::huffman_encode_adaptive(&data),
+            rangecoder::encode(&data).unwrap(),
+            rangecoder::encode_sized(&data),
+            rangecoder::order1::encode_order1(&data),
+            lz::encode_lz(&data, lz::DEFAULT_LZ_WINDOW_BITS).unwrap(),
+            rangecoder::cm::encode_cm(&data, 2).unwrap(),
+            rangecoder::encode_rle_model(&data).unwrap(),
+            rangecoder::encode_parallel(&data, 256).unwrap(),
+        ];
+        for encoded in &encodings {
+            assert_bytes_eq(&data, &decode_any(encoded).unwrap());
+            assert!(matches!(
+                decode_any_with_limits(encoded, &DecodeLimits::new(100)),
+                Err(Error::LimitExceeded(_))
+            ));
+        }
+        // 很短的输入声明了巨大的输出：按默认上限拒绝，不分配、不展开。
+        assert!(matches!(
+            decode_any(b"RLE0\xFF\xFF\xFF\xFFx"),
+            Err(Error::LimitExceeded(_))
+        ));
+        let mut words = b"RLEW\x02".to_vec();
+        words.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, b'a', b'b']);
+        let limits = DecodeLimits::new(1 << 20);
+        assert!(matches!(
+            decode_any_with_limits(&words, &limits),
+            Err(Error::LimitExceeded(_))
+        ));
+        assert_bytes_eq(
+            b"zz",
+            &decode_any(&rangecoder::encode(b"zz").unwrap()).unwrap(),
+        );
+
+        assert!(matches!(
+            decode_any(b"garbage that matches no magic"),
+            Err(Error::BadMagic(_))
+        ));
+        // 经典 RLE 没有魔数，同样无法识别。
+        assert!(decode_any(&rle::rle_encode(&data)).is_err());
+        assert!(matches!(decode_any(b"RC"), Err(Error::Truncated(_))));
+        // 识别出格式后，数据本身的错误照常报告。
+        assert!(matches!(
+            decode_any(b"RCNC\x00\x00\x00\x00"),
+            Err(Error::InvalidModel(_))
+        ));
+    }
+
+    #[test]
+    fn transcode_chain_preserves_bytes() {
+        let mut data = b"legacy archive ".repeat(40);
+        data.extend(vec![0u8; 500]);
+        data.extend((0..=255u8).cycle().take(1000));
+
+        let original = temp_path("original");
+        let rle_path = temp_path("a.rle");
+        let range_path = temp_path("b.rc");
+        let huffman_path = temp_path("c.huf");
+        let back_path = temp_path("d.rle");
+        let decoded = temp_path("decoded");
+        let p = |p: &PathBuf| p.to_str().unwrap().to_string();
+
+        fs::write(&original, &data).unwrap();
+        Codec::Rle
+            .encode_file(&p(&original), &p(&rle_path))
+            .unwrap();
+        transcode(&p(&rle_path), Codec::Rle, Codec::Range, &p(&range_path)).unwrap();
+        transcode(
+            &p(&range_path),
+            Codec::Range,
+            Codec::Huffman,
+            &p(&huffman_path),
+        )
+        .unwrap();
+        transcode(
+            &p(&huffman_path),
+            Codec::Huffman,
+            Codec::Rle,
+            &p(&back_path),
+        )
+        .unwrap();
+
+        assert_eq!(fs::read(&back_path).unwrap(), fs::read(&rle_path).unwrap());
+        Codec::Rle
+            .decode_file(&p(&back_path), &p(&decoded))
+            .unwrap();
+        assert_bytes_eq(&data, &fs::read(&decoded).unwrap());
+        assert!(!PathBuf::from(format!("{}.transcode.tmp", p(&back_path))).exists());
+
+        let err = transcode(&p(&rle_path), Codec::Huffman, Codec::Range, &p(&range_path));
+        assert!(matches!(err, Err(Error::BadMagic(_))));
+        for path in [
+            original,
+            rle_path,
+            range_path,
+            huffman_path,
+            back_path,
+            decoded,
+        ] {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn decode_limits_apply_to_every_codec() {
+        let data = vec![7u8; 100_000];
+        let original = temp_path("limits.in");
+        let encoded = temp_path("limits.enc");
+        let decoded = temp_path("limits.out");
+        let p = |p: &PathBuf| p.to_str().unwrap().to_string();
+        fs::write(&original, &data).unwrap();
+        for codec in Codec::ALL {
+            codec.encode_file(&p(&original), &p(&encoded)).unwrap();
+            let err = codec.decode_file_with_limits(
+                &p(&encoded),
+                &p(&decoded),
+                &DecodeLimits::new(50_000),
+            );
+            assert!(matches!(err, Err(Error::LimitExceeded(_))), "{codec}");
+            codec
+                .decode_file_with_limits(&p(&encoded), &p(&decoded), &DecodeLimits::new(1 << 20))
+                .unwrap();
+            assert_eq!(fs::read(&decoded).unwrap(), data, "{codec}");
+        }
+        for path in [original, encoded, decoded] {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn recommendation_follows_data_shape() {
+        let mut runs = Vec::new();
+        for (i, r) in pseudo_random(400, 3).into_iter().enumerate() {
+            runs.extend(std::iter::repeat_n(i as u8, 20 + r as usize % 40));
+        }
+        assert_eq!(recommend_codec(&runs), Codec::Rle);
+
+        // 偏斜分布：约一半为同一字节，其余分散在少数字节上，且几乎没有连续重复。
+        let skewed: Vec<u8> = pseudo_random(50_000, 5)
+            .into_iter()
+            .map(|r| match r % 2 {
+                0 => b'e',
+                _ => b"tainosr"[r as usize % 7],
+            })
+            .collect();
+        let codec = recommend_codec(&skewed);
+        assert!(matches!(codec, Codec::Huffman | Codec::Range), "{codec}");
+
+        assert_eq!(recommend_codec(&pseudo_random(50_000, 9)), Codec::Stored);
+        assert_eq!(recommend_codec(b""), Codec::Stored);
+
+        let input = temp_path("recommend.in");
+        let encoded = temp_path("recommend.enc");
+        let decoded = temp_path("recommend.out");
+        let p = |p: &PathBuf| p.to_str().unwrap().to_string();
+        for data in [&runs, &skewed] {
+            fs::write(&input, data).unwrap();
+            let codec = compress_file(&p(&input), &p(&encoded)).unwrap();
+            assert!(
+                fs::metadata(&encoded).unwrap().len() < data.len() as u64,
+                "{codec}"
+            );
+            codec.decode_file(&p(&encoded), &p(&decoded)).unwrap();
+            assert_bytes_eq(data, &fs::read(&decoded).unwrap());
+        }
+        for path in [input, encoded, decoded] {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn lz_range_beats_each_component() {
+        let text = b"It is a truth universally acknowledged, that a single man in possession \
+                     of a good fortune, must be in want of a wife. "
+            .repeat(100);
+        let source = include_bytes!("lib.rs").to_vec();
+        let input = temp_path("lz.in");
+        let encoded = temp_path("lz.enc");
+        let decoded = temp_path("lz.out");
+        let p = |p: &PathBuf| p.to_str().unwrap().to_string();
+        for data in [text, source] {
+            fs::write(&input, &data).unwrap();
+            let mut sizes = Vec::new();
+            for codec in [Codec::Rle, Codec::Huffman, Codec::Range, Codec::LzRange] {
+                codec.encode_file(&p(&input), &p(&encoded)).unwrap();
+                sizes.push(fs::metadata(&encoded).unwrap().len());
+            }
+            Codec::LzRange
+                .decode_file(&p(&encoded), &p(&decoded))
+                .unwrap();
+            assert_bytes_eq(&data, &fs::read(&decoded).unwrap());
+            let lz = sizes.pop().unwrap();
+            assert!(sizes.iter().all(|&s| lz < s), "{lz} vs {sizes:?}");
+        }
+        for path in [input, encoded, decoded] {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn codec_names_roundtrip() {
+        for codec in Codec::ALL {
+            assert_eq!(codec.name().parse::<Codec>().unwrap(), codec);
+        }
+        assert!("lzma".parse::<Codec>().is_err());
+    }
+}
@@ -0,0 +1,41 @@
+use codec::{transcode, Codec};
+use encoding_common::selftest;
+use std::env;
+use std::process;
+
+fn parse_codec(name: &str) -> Codec {
+    name.parse().unwrap_or_else(|_| {
+        eprintln!("未知编码器: {name}，应为 rle、huffman、range、lzrange 或 stored");
+        process::exit(1);
+    })
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() == 2 && args[1] == "selftest" {
+        let mut ok = true;
+        for codec in Codec::ALL {
+            ok &= selftest::run(codec.name(), |data| {
+                selftest::roundtrip_via_files(
+                    data,
+                    |i, o| codec.encode_file(i, o),
+                    |i, o| codec.decode_file(i, o),
+                )
+            });
+        }
+        process::exit(if ok { 0 } else { 1 });
+    }
+    if args.len() != 5 {
+        eprintln!("用法: {} from to input output", args[0]);
+        eprintln!("      {} selftest", args[0]);
+        eprintln!("      from/to 为 rle、huffman、range、lzrange 或 stored");
+        process::exit(1);
+    }
+    let from = parse_codec(&args[1]);
+    let to = parse_codec(&args[2]);
+
+    if let Err(e) = transcode(&args[3], from, to, &args[4]) {
+        eprintln!("运行失败: {e}");
+        process::exit(1);
+    }
+}
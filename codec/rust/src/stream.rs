@@ -0,0 +1,289 @@
+// 通用的 Read / Write 适配器：把任意 Codec 包装成分块压缩流，
+// 写入端按块缓冲并编码，读取端逐块解码，对调用方透明。
+//
+// 格式：魔数 CDCB + 1 字节编码器名称长度 + 名称（Codec::name，如 "range"），随后是若干块：
+//   4 字节小端原始长度 n + 4 字节小端编码长度 m + 4 字节小端原始数据 CRC32 + m 字节编码数据，
+// 每块由该编码器独立编码（与 Codec::encode_bytes 的输出相同）。
+// 以 n = m = 0、CRC 为 0 的块结束。解码时校验每块的原始长度与 CRC32。
+
+use std::io::{self, Read, Write};
+
+use encoding_common::checksum::crc32;
+
+use crate::{Codec, DecodeLimits, Error};
+
+const STREAM_MAGIC: &[u8; 4] = b"CDCB";
+// 默认块大小：Huffman / range 每块约 1 KiB 的频率表头部在此规模下可以忽略。
+pub const DEFAULT_BLOCK_SIZE: usize = 1 << 20;
+pub const MAX_BLOCK_SIZE: usize = 1 << 26;
+// 解码一块时在原始长度之外留给各编码器模型表的内存。
+const TABLE_ALLOWANCE: u64 = 1 << 16;
+
+// 分块压缩写入器。写完后必须调用 finish 写出最后一块与结束标记。
+pub struct CompressWriter<W: Write> {
+    inner: W,
+    codec: Codec,
+    block_size: usize,
+    buffer: Vec<u8>,
+    header_written: bool,
+}
+
+impl<W: Write> CompressWriter<W> {
+    pub fn new(inner: W, codec: Codec) -> Self {
+        CompressWriter {
+            inner,
+            codec,
+            block_size: DEFAULT_BLOCK_SIZE,
+            buffer: Vec::new(),
+            header_written: false,
+        }
+    }
+
+    // 每块的原始字节数，取值 1..=MAX_BLOCK_SIZE。
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size.clamp(1, MAX_BLOCK_SIZE);
+        self
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if !self.header_written {
+            let name = self.codec.name().as_bytes();
+            self.inner.write_all(STREAM_MAGIC)?;
+            self.inner.write_all(&[name.len() as u8])?;
+            self.inner.write_all(name)?;
+            self.header_written = true;
+        }
+        Ok(())
+    }
+
+    fn write_block(&mut self) -> io::Result<()> {
+        self.write_header()?;
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let encoded = self.codec.encode_bytes(&self.buffer)?;
+        let encoded_len = u32::try_from(encoded.len())
+            .map_err(|_| Error::LimitExceeded("codec: encoded block too large"))?;
+        self.inner
+            .write_all(&(self.buffer.len() as u32).to_le_bytes())?;
+        self.inner.write_all(&encoded_len.to_le_bytes())?;
+        self.inner.write_all(&crc32(&self.buffer).to_le_bytes())?;
+        self.inner.write_all(&encoded)?;
+        self.buffer.clear();
+        Ok(())
+    }
+
+    // 写出缓冲中的数据与结束标记，flush 后返回底层 writer。
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_block()?;
+        self.inner.write_all(&[0u8; 12])?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for CompressWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.block_size - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..n]);
+        if self.buffer.len() == self.block_size {
+            self.write_block()?;
+        }
+        Ok(n)
+    }
+
+    // 把已缓冲的数据作为一个较短的块写出，并 flush 底层 writer。
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_block()?;
+        self.inner.flush()
+    }
+}
+
+// 分块解压读取器，读取 CompressWriter 写出的数据流。
+pub struct DecompressReader<R: Read> {
+    inner: R,
+    codec: Codec,
+    block: Vec<u8>,
+    pos: usize,
+    header_read: bool,
+    done: bool,
+}
+
+impl<R: Read> DecompressReader<R> {
+    // codec 须与写入时使用的编码器一致，否则第一次读取时返回错误。
+    pub fn new(inner: R, codec: Codec) -> Self {
+        DecompressReader {
+            inner,
+            codec,
+            block: Vec::new(),
+            pos: 0,
+            header_read: false,
+            done: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn read_exact_or(&mut self, buf: &mut [u8], what: &'static str) -> Result<(), Error> {
+        self.inner
+            .read_exact(buf)
+            .map_err(|e| Error::from_read(e, what))
+    }
+
+    fn read_header(&mut self) -> Result<(), Error> {
+        let mut magic = [0u8; 5];
+        self.read_exact_or(&mut magic, "codec: truncated stream header")?;
+        if &magic[..4] != STREAM_MAGIC {
+            return Err(Error::BadMagic("codec: bad stream magic"));
+        }
+        let mut name = vec![0u8; magic[4] as usize];
+        self.read_exact_or(&mut name, "codec: truncated stream header")?;
+        if name != self.codec.name().as_bytes() {
+            return Err(Error::InvalidData(
+                "codec: stream written with another codec",
+            ));
+        }
+        self.header_read = true;
+        Ok(())
+    }
+
+    // 读入并解码下一块；遇到结束标记时置 done。
+    fn next_block(&mut self) -> Result<(), Error> {
+        if !self.header_read {
+            self.read_header()?;
+        }
+        let mut head = [0u8; 12];
+        self.read_exact_or(&mut head, "codec: truncated block header")?;
+        let field = |i: usize| u32::from_le_bytes(head[i..i + 4].try_into().unwrap());
+        let (raw_len, encoded_len, crc) = (field(0), field(4), field(8));
+        if raw_len == 0 {
+            if encoded_len != 0 || crc != 0 {
+                return Err(Error::InvalidData("codec: bad end marker"));
+            }
+            self.done = true;
+            return Ok(());
+        }
+        if raw_len as usize > MAX_BLOCK_SIZE {
+            return Err(Error::InvalidData("codec: bad block length"));
+        }
+        // 编码数据按实际读到的字节增长，伪造的长度字段不会导致大块预分配。
+        let mut encoded = Vec::new();
+        (&mut self.inner)
+            .take(encoded_len as u64)
+            .read_to_end(&mut encoded)?;
+        if encoded.len() != encoded_len as usize {
+            return Err(Error::Truncated("codec: truncated block"));
+        }
+        let limits = DecodeLimits::new(raw_len as u64 + TABLE_ALLOWANCE);
+        let block = self.codec.decode_bytes(&encoded, &limits)?;
+        if block.len() != raw_len as usize {
+            return Err(Error::InvalidData("codec: block length mismatch"));
+        }
+        if crc32(&block) != crc {
+            return Err(Error::ChecksumMismatch("codec: block checksum mismatch"));
+        }
+        self.block = block;
+        self.pos = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecompressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.block.len() {
+            if self.done || buf.is_empty() {
+                return Ok(0);
+            }
+            self.next_block()?;
+        }
+        let n = buf.len().min(self.block.len() - self.pos);
+        buf[..n].copy_from_slice(&self.block[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_common::selftest::pseudo_random;
+    use encoding_common::test_util::assert_bytes_eq;
+
+    // 每次最多返回 chunk 个字节的 reader，模拟小缓冲区的来源。
+    struct Chunked<'a> {
+        data: &'a [u8],
+        chunk: usize,
+    }
+
+    impl Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.chunk).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    fn sample() -> Vec<u8> {
+        let mut data = b"adapters over every codec ".repeat(2000);
+        data.extend(vec![0u8; 30_000]);
+        data.extend(pseudo_random(20_000, 0xABCD));
+        data
+    }
+
+    #[test]
+    fn every_codec_roundtrips_through_adapters() {
+        let data = sample();
+        for codec in Codec::ALL {
+            for (block_size, chunk) in [(4096, 7), (DEFAULT_BLOCK_SIZE, 1 << 16)] {
+                let mut writer = CompressWriter::new(Vec::new(), codec).with_block_size(block_size);
+                io::copy(&mut Chunked { data: &data, chunk }, &mut writer).unwrap();
+                let encoded = writer.finish().unwrap();
+
+                let mut reader = DecompressReader::new(
+                    Chunked {
+                        data: &encoded,
+                        chunk,
+                    },
+                    codec,
+                );
+                let mut decoded = Vec::new();
+                io::copy(&mut reader, &mut decoded).unwrap();
+                assert_bytes_eq(&data, &decoded);
+            }
+        }
+
+        let empty = CompressWriter::new(Vec::new(), Codec::Range)
+            .finish()
+            .unwrap();
+        let mut decoded = Vec::new();
+        DecompressReader::new(&empty[..], Codec::Range)
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert!(decoded.is_empty());
+    }
+
+    #[test]
+    fn adapters_detect_corruption_and_codec_mismatch() {
+        let data = sample();
+        let mut writer = CompressWriter::new(Vec::new(), Codec::Huffman).with_block_size(10_000);
+        writer.write_all(&data).unwrap();
+        let encoded = writer.finish().unwrap();
+
+        let read_all = |bytes: &[u8], codec| {
+            let mut out = Vec::new();
+            DecompressReader::new(bytes, codec)
+                .read_to_end(&mut out)
+                .map(|_| out)
+        };
+        assert!(read_all(&encoded, Codec::Range).is_err());
+        assert!(read_all(&encoded[..encoded.len() - 12], Codec::Huffman).is_err());
+        let mut corrupt = encoded.clone();
+        let last = corrupt.len() - 20;
+        corrupt[last] ^= 0x40;
+        assert!(read_all(&corrupt, Codec::Huffman).is_err());
+        assert_bytes_eq(&data, &read_all(&encoded, Codec::Huffman).unwrap());
+    }
+}
@@ -0,0 +1,31 @@
+// 浏览器入口（wasm feature）：range 与 Huffman 的内存 API 的薄封装，错误以 JS 异常抛出。
+// 只调用按字节切片编解码的函数，不涉及文件系统与计时，可编译到 wasm32-unknown-unknown。
+
+use wasm_bindgen::prelude::*;
+
+use crate::Error;
+
+fn to_js(e: Error) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
+
+#[wasm_bindgen]
+pub fn wasm_range_encode(input: &[u8]) -> Vec<u8> {
+    // 不可取消的编码不会失败。
+    rangecoder::encode(input).expect("range: encode failed")
+}
+
+#[wasm_bindgen]
+pub fn wasm_range_decode(input: &[u8]) -> Result<Vec<u8>, JsValue> {
+    rangecoder::decode(input).map_err(to_js)
+}
+
+#[wasm_bindgen]
+pub fn wasm_huffman_encode(input: &[u8]) -> Vec<u8> {
+    huffman::huffman_encode(input)
+}
+
+#[wasm_bindgen]
+pub fn wasm_huffman_decode(input: &[u8]) -> Result<Vec<u8>, JsValue> {
+    huffman::huffman_decode(input).map_err(to_js)
+}
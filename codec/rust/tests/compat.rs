@@ -0,0 +1,54 @@
+// 跨语言兼容性测试：compat/fixtures 下保存其他语言实现产生的编码文件，
+// 文件名为 <编码器>-<产生该文件的语言>.<扩展名>，原文统一为 plain.bin。
+// 新增其他语言的夹具只需放入同一目录，无需修改本测试。
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../compat/fixtures")
+}
+
+fn decode_fixture(codec: &str, path: &Path) -> Vec<u8> {
+    let output = std::env::temp_dir().join(format!(
+        "compat_{}_{}",
+        process::id(),
+        path.file_name().unwrap().to_str().unwrap()
+    ));
+    let (input, out) = (path.to_str().unwrap(), output.to_str().unwrap());
+    let result = match codec {
+        "huffman" => huffman::huffman_decode_file(input, out),
+        "rle" => rle::rle_decode_file(input, out),
+        "range" => {
+            let decoded = rangecoder::decode(&fs::read(path).unwrap()).unwrap();
+            fs::write(out, decoded).map_err(Into::into)
+        }
+        _ => panic!("unknown codec in fixture name {}", path.display()),
+    };
+    result.unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+    let decoded = fs::read(&output).unwrap();
+    let _ = fs::remove_file(&output);
+    decoded
+}
+
+#[test]
+fn rust_decodes_fixtures_from_other_languages() {
+    let plain = fs::read(fixtures_dir().join("plain.bin")).unwrap();
+    let mut covered = Vec::new();
+    for entry in fs::read_dir(fixtures_dir()).unwrap() {
+        let path = entry.unwrap().path();
+        let stem = path.file_stem().unwrap().to_str().unwrap();
+        let Some((codec, lang)) = stem.split_once('-') else {
+            continue;
+        };
+        let decoded = decode_fixture(codec, &path);
+        assert!(
+            decoded == plain,
+            "{codec} stream from {lang} decoded differently"
+        );
+        covered.push(codec.to_string());
+    }
+    for codec in ["huffman", "rle", "range"] {
+        assert!(covered.iter().any(|c| c == codec), "no fixture for {codec}");
+    }
+}
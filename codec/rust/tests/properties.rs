@@ -0,0 +1,129 @@
+// 三个编码器的性质测试：对 proptest 生成的字节序列检查 decode(encode(x)) == x，
+// 并把生成的数据、编码结果的截断前缀直接交给解码器，只允许返回错误、不允许 panic。
+// 覆盖空输入、同一字节重复、高熵、长短游程混合与小字母表几种形态，长度集中在 0..4096；
+// 失败时 proptest 自动缩小用例并报告最小的反例。
+use proptest::prelude::*;
+
+// 短长度更常见，也会出现 255 / 256 / 4096 等边界附近的长度。
+fn lengths() -> impl Strategy<Value = usize> {
+    prop_oneof![0..8usize, 250..262usize, 4090..4102usize, 0..4096usize]
+}
+
+fn inputs() -> impl Strategy<Value = Vec<u8>> {
+    prop_oneof![
+        Just(Vec::new()),
+        (any::<u8>(), lengths()).prop_map(|(value, len)| vec![value; len.max(1)]),
+        lengths().prop_flat_map(|len| prop::collection::vec(any::<u8>(), len)),
+        prop::collection::vec((any::<u8>(), 1..300usize), 0..40).prop_map(|runs| {
+            let mut out = Vec::new();
+            for (value, run) in runs {
+                out.extend(std::iter::repeat_n(value, run));
+            }
+            out.truncate(4096);
+            out
+        }),
+        (2u8..5).prop_flat_map(|alphabet| {
+            prop::collection::vec((0..alphabet).prop_map(|b| b'a' + b), 0..4096)
+        }),
+    ]
+}
+
+fn expect_roundtrip(
+    name: &str,
+    data: &[u8],
+    decoded: Result<Vec<u8>, impl std::fmt::Display>,
+) -> Result<(), TestCaseError> {
+    match decoded {
+        Ok(out) => prop_assert!(out == data, "{name}: decoded {} bytes, mismatch", out.len()),
+        Err(e) => return Err(TestCaseError::fail(format!("{name}: {e}"))),
+    }
+    Ok(())
+}
+
+// 截断位置：空、只剩 1 字节、去掉最后 1 字节与中间。
+fn prefixes(encoded: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let len = encoded.len();
+    [0, 1, len / 2, len.saturating_sub(1)]
+        .into_iter()
+        .filter(move |&cut| cut < len)
+        .map(move |cut| &encoded[..cut])
+}
+
+// decode_any 识别的全部魔数；RCNC 之外的其余 RCN* 交给 rangecoder::decode。
+const MAGICS: [&[u8; 4]; 29] = [
+    b"RLE0", b"RLPB", b"RLEW", b"HFMN", b"HFM2", b"HFMC", b"HFMA", b"RCNC", b"RCN1", b"RCN2",
+    b"RCNA", b"RCNB", b"RCND", b"RCNF", b"RCNG", b"RCNH", b"RCNI", b"RCNK", b"RCNL", b"RCNM",
+    b"RCNP", b"RCNQ", b"RCNR", b"RCNS", b"RCNT", b"RCNV", b"RCNW", b"RCNX", b"RCNZ",
+];
+
+proptest! {
+    #[test]
+    fn range_roundtrips(data in inputs()) {
+        let encoded = rangecoder::encode(&data).map_err(|e| TestCaseError::fail(e.to_string()))?;
+        expect_roundtrip("decode", &data, rangecoder::decode(&encoded))?;
+        prop_assert!(rangecoder::verify(&data).map_err(|e| TestCaseError::fail(e.to_string()))?);
+        for prefix in prefixes(&encoded) {
+            let _ = rangecoder::decode_with_limit(prefix, 1 << 16);
+        }
+    }
+
+    #[test]
+    fn huffman_roundtrips(data in inputs()) {
+        let encoded = huffman::huffman_encode(&data);
+        expect_roundtrip("decode", &data, huffman::huffman_decode(&encoded))?;
+        let canonical = huffman::huffman_encode_canonical(&data);
+        expect_roundtrip(
+            "canonical",
+            &data,
+            huffman::huffman_decode_canonical(&canonical),
+        )?;
+        let adaptive = huffman::huffman_encode_adaptive(&data);
+        expect_roundtrip(
+            "adaptive",
+            &data,
+            huffman::huffman_decode_adaptive(&adaptive),
+        )?;
+        for prefix in prefixes(&encoded) {
+            let _ = huffman::huffman_decode_with_limit(prefix, 1 << 16);
+        }
+        for prefix in prefixes(&adaptive) {
+            let _ = huffman::huffman_decode_adaptive(prefix);
+        }
+    }
+
+    #[test]
+    fn rle_roundtrips(data in inputs()) {
+        expect_roundtrip("classic", &data, rle::rle_decode(&rle::rle_encode(&data)))?;
+        expect_roundtrip(
+            "varint",
+            &data,
+            rle::rle_decode_varint(&rle::rle_encode_varint(&data)),
+        )?;
+        expect_roundtrip(
+            "packbits",
+            &data,
+            rle::packbits_decode(&rle::packbits_encode(&data)),
+        )?;
+        let even = &data[..data.len() & !1];
+        let words = rle::rle_encode_u16_le(even).map_err(|e| TestCaseError::fail(e.to_string()))?;
+        expect_roundtrip("u16", even, rle::rle_decode_u16_le(&words))?;
+        for prefix in prefixes(&rle::rle_encode(&data)) {
+            let _ = rle::rle_decode_with_limit(prefix, 1 << 16);
+        }
+    }
+
+    // 任意字节作为编码数据交给带输出上限的各解码器：只允许返回错误或有限的输出。
+    // 没有上限的解码器会按损坏的 count 分配巨大的输出，不在这里测试。
+    #[test]
+    fn decoders_reject_arbitrary_input_without_panicking(data in inputs()) {
+        let _ = rangecoder::decode_with_limit(&data, 1 << 16);
+        let _ = huffman::huffman_decode_with_limit(&data, 1 << 16);
+        let _ = huffman::huffman_decode_adaptive(&data);
+        let _ = rle::rle_decode_with_limit(&data, 1 << 16);
+        // 加上各格式的魔数，让 decode_any 把任意字节交给对应的解码器。
+        let limits = codec::DecodeLimits::new(1 << 16);
+        for magic in MAGICS {
+            let _ = codec::decode_any_with_limits(&[&magic[..], &data].concat(), &limits);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+// 压缩率回归门禁：每个编码器对每个标准数据剖面的输出大小都不得超过下面记录的基线
+// （允许 TOLERANCE_PERCENT 的浮动），重构使压缩率变差时测试失败。
+//
+// 有意改进压缩率（或接受某项变差）后更新基线：运行
+//   cargo test -p codec --test ratio -- --nocapture
+// 测试会打印每一项的当前大小，按输出替换 BASELINES 中对应的数值并在提交说明中注明原因。
+use codec::Codec;
+use encoding_common::selftest::profiles;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+// 允许的相对浮动。
+const TOLERANCE_PERCENT: u64 = 1;
+
+// (剖面, 编码器, 输出字节数)。
+const BASELINES: &[(&str, &str, u64)] = &[
+    ("empty", "rle", 0),
+    ("empty", "huffman", 1037),
+    ("empty", "range", 1040),
+    ("empty", "lzrange", 10),
+    ("empty", "stored", 0),
+    ("single-byte", "rle", 5),
+    ("single-byte", "huffman", 1037),
+    ("single-byte", "range", 13),
+    ("single-byte", "lzrange", 10),
+    ("single-byte", "stored", 1),
+    ("text", "rle", 45000),
+    ("text", "huffman", 6137),
+    ("text", "range", 6037),
+    ("text", "lzrange", 93),
+    ("text", "stored", 9000),
+    ("long-runs", "rle", 30),
+    ("long-runs", "huffman", 5949),
+    ("long-runs", "range", 5800),
+    ("long-runs", "lzrange", 37),
+    ("long-runs", "stored", 16755),
+    ("skewed", "rle", 2555),
+    ("skewed", "huffman", 7543),
+    ("skewed", "range", 1585),
+    ("skewed", "lzrange", 812),
+    ("skewed", "stored", 50000),
+    ("random", "rle", 326345),
+    ("random", "huffman", 66599),
+    ("random", "range", 66554),
+    ("random", "lzrange", 66265),
+    ("random", "stored", 65536),
+];
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("codec_ratio_{}_{name}", process::id()))
+}
+
+fn encoded_size(codec: Codec, input: &Path, output: &Path) -> u64 {
+    codec
+        .encode_file(input.to_str().unwrap(), output.to_str().unwrap())
+        .unwrap();
+    fs::metadata(output).unwrap().len()
+}
+
+#[test]
+fn compression_ratio_does_not_regress() {
+    let input = temp_path("input");
+    let output = temp_path("output");
+    let mut failures = Vec::new();
+    for (profile, data) in profiles() {
+        fs::write(&input, &data).unwrap();
+        for codec in Codec::ALL {
+            let size = encoded_size(codec, &input, &output);
+            println!("    (\"{profile}\", \"{codec}\", {size}),");
+            let baseline = BASELINES
+                .iter()
+                .find(|&&(p, c, _)| p == profile && c == codec.name())
+                .map(|&(_, _, b)| b);
+            match baseline {
+                None => failures.push(format!("{profile}/{codec}: no baseline (now {size})")),
+                Some(b) if size * 100 > b * (100 + TOLERANCE_PERCENT) => failures.push(format!(
+                    "{profile}/{codec}: {size} bytes, baseline {b} bytes (+{:.2}%)",
+                    (size as f64 / b.max(1) as f64 - 1.0) * 100.0
+                )),
+                Some(_) => {}
+            }
+        }
+    }
+    let _ = fs::remove_file(input);
+    let _ = fs::remove_file(output);
+    assert!(
+        failures.is_empty(),
+        "compression ratio regressed beyond {TOLERANCE_PERCENT}%:\n{}\n\
+         if the change is intended, update BASELINES in codec/rust/tests/ratio.rs",
+        failures.join("\n")
+    );
+}
@@ -0,0 +1,18 @@
+// wasm feature 的导出函数在本机目标上同样可以编译与调用；错误路径需要 JS 运行时构造 JsValue，这里只走成功路径。
+// wasm32 上的编译由 cargo build -p codec --lib --features wasm --target wasm32-unknown-unknown 检查。
+#![cfg(feature = "wasm")]
+
+use codec::wasm::{wasm_huffman_decode, wasm_huffman_encode, wasm_range_decode, wasm_range_encode};
+
+#[test]
+fn wasm_wrappers_roundtrip() {
+    for data in [&b""[..], b"x", &b"wasm wrappers are thin. ".repeat(50)] {
+        assert_eq!(wasm_range_decode(&wasm_range_encode(data)).unwrap(), data);
+        assert_eq!(
+            wasm_huffman_decode(&wasm_huffman_encode(data)).unwrap(),
+            data
+        );
+        assert_eq!(wasm_range_encode(data), rangecoder::encode(data).unwrap());
+        assert_eq!(wasm_huffman_encode(data), huffman::huffman_encode(data));
+    }
+}
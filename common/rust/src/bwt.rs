@@ -0,0 +1,118 @@
+// Burrows-Wheeler 变换：把输入的所有循环移位按字典序排序，输出排序后每一行的最后一个字节，
+// 以及原输入（移位 0）所在的行号（主索引）。相同上下文之前的字节被聚到一起，
+// 之后接 mtf_encode 再交给 range / Huffman，即 bzip2 式的处理流程。
+//
+// 主索引不包含在输出字节中，用于文件格式时须与数据一起保存，解码时原样传给 bwt_decode。
+
+use crate::Error;
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+// 循环移位的倍增排序：第 k 轮按 (前 2^k 字节的名次, 其后 2^k 字节的名次) 排序，
+// 每轮一次 O(n log n) 的比较排序，至多 log n 轮，合计 O(n log² n)；
+// 连续相同的字节等重复数据不会退化。内存为 3 个 n 项的 usize 数组。
+fn sort_rotations(data: &[u8]) -> Vec<usize> {
+    let n = data.len();
+    let mut order: Vec<usize> = (0..n).collect();
+    let mut rank: Vec<usize> = data.iter().map(|&b| b as usize).collect();
+    let mut next_rank = vec![0usize; n];
+    let mut k = 1;
+    loop {
+        let key = |i: usize| (rank[i], rank[(i + k) % n]);
+        order.sort_unstable_by_key(|&i| key(i));
+        next_rank[order[0]] = 0;
+        for w in 1..n {
+            let step = (key(order[w - 1]) != key(order[w])) as usize;
+            next_rank[order[w]] = next_rank[order[w - 1]] + step;
+        }
+        core::mem::swap(&mut rank, &mut next_rank);
+        // 名次两两不同即排序完成；周期性输入的相同移位永远并列，比较长度达到 n 时停止。
+        if rank[order[n - 1]] == n - 1 || k >= n {
+            return order;
+        }
+        k *= 2;
+    }
+}
+
+// 返回 (变换后的字节, 主索引)。空输入返回 (空, 0)。
+pub fn bwt_encode(data: &[u8]) -> (Vec<u8>, usize) {
+    if data.is_empty() {
+        return (Vec::new(), 0);
+    }
+    let n = data.len();
+    let order = sort_rotations(data);
+    let last = order.iter().map(|&i| data[(i + n - 1) % n]).collect();
+    let index = order.iter().position(|&i| i == 0).unwrap();
+    (last, index)
+}
+
+// 由最后一列与主索引还原输入，O(n)。非空输入的主索引不小于长度时返回 InvalidInput。
+pub fn bwt_decode(data: &[u8], index: usize) -> Result<Vec<u8>, Error> {
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+    if index >= data.len() {
+        return Err(Error::InvalidInput("bwt: primary index out of range"));
+    }
+    // 第一列是最后一列排序后的结果；next[r] 为第 r 行左移一位后所在的行（同一字节的各行保持相对顺序）。
+    let mut start = [0usize; 256];
+    for &b in data {
+        start[b as usize] += 1;
+    }
+    let mut sum = 0;
+    for s in start.iter_mut() {
+        (*s, sum) = (sum, sum + *s);
+    }
+    let mut next = vec![0usize; data.len()];
+    for (i, &b) in data.iter().enumerate() {
+        next[start[b as usize]] = i;
+        start[b as usize] += 1;
+    }
+    let mut out = Vec::with_capacity(data.len());
+    let mut row = next[index];
+    for _ in 0..data.len() {
+        out.push(data[row]);
+        row = next[row];
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selftest::pseudo_random;
+
+    #[test]
+    fn bwt_roundtrip_restores_index() {
+        let (last, index) = bwt_encode(b"banana");
+        assert_eq!((&last[..], index), (&b"nnbaaa"[..], 3));
+        assert_eq!(bwt_decode(&last, index).unwrap(), b"banana");
+
+        let inputs = [
+            b"abracadabra".to_vec(),
+            b"mississippi".to_vec(),
+            b"abababab".to_vec(),
+            vec![0u8; 5000],
+            b"x".to_vec(),
+            pseudo_random(4096, 0xB3D7),
+        ];
+        for data in inputs {
+            let (last, index) = bwt_encode(&data);
+            assert_eq!(last.len(), data.len());
+            assert_eq!(bwt_decode(&last, index).unwrap(), data);
+        }
+        // 其他主索引还原出的是别的循环移位。
+        let data = pseudo_random(1000, 0x1D3A);
+        let (last, index) = bwt_encode(&data);
+        let shifted = bwt_decode(&last, (index + 1) % data.len()).unwrap();
+        assert_ne!(shifted, data);
+        assert!(data.repeat(2).windows(data.len()).any(|w| w == shifted));
+
+        assert_eq!(bwt_encode(&[]), (Vec::new(), 0));
+        assert!(bwt_decode(&[], 0).unwrap().is_empty());
+        assert!(matches!(
+            bwt_decode(b"nnbaaa", 6),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+}
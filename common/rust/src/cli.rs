@@ -0,0 +1,41 @@
+// 各编码器 CLI 共用的输出：成功后在 stderr 报告原始大小、压缩后大小与压缩率。
+
+use std::fs;
+
+// 压缩率为压缩后大小占原始大小的百分比；原始为空时不计算。
+pub fn size_report(original: u64, compressed: u64) -> String {
+    if original == 0 {
+        return format!("{original} → {compressed} 字节");
+    }
+    let percent = compressed as f64 * 100.0 / original as f64;
+    format!("{original} → {compressed} 字节 ({percent:.1}%)")
+}
+
+// 大小取自写完后的文件元数据。encode 时输入为原始数据，decode 时输出为原始数据；
+// 任一路径为 "-"（标准输入 / 输出）或无法读取元数据时不输出。
+pub fn report_sizes(mode: &str, input_path: &str, output_path: &str) {
+    let len = |path: &str| match path {
+        "-" => None,
+        _ => fs::metadata(path).ok().map(|m| m.len()),
+    };
+    let (Some(input), Some(output)) = (len(input_path), len(output_path)) else {
+        return;
+    };
+    let (original, compressed) = match mode {
+        "decode" => (output, input),
+        _ => (input, output),
+    };
+    eprintln!("{}", size_report(original, compressed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_report_formats_ratio() {
+        assert_eq!(size_report(12345, 4567), "12345 → 4567 字节 (37.0%)");
+        assert_eq!(size_report(100, 250), "100 → 250 字节 (250.0%)");
+        assert_eq!(size_report(0, 1037), "0 → 1037 字节");
+    }
+}
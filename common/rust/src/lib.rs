@@ -0,0 +1,165 @@
+// 关闭默认的 std feature 时为 no_std + alloc：错误类型不含 Io 变体，selftest 与 test_util 不可用。
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error;
+#[cfg(feature = "std")]
+use std::io;
+
+pub mod bwt;
+pub mod checksum;
+#[cfg(feature = "std")]
+pub mod cli;
+pub mod delta;
+pub mod mtf;
+pub mod rolling;
+#[cfg(feature = "std")]
+pub mod selftest;
+pub mod symbol;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+
+// 各编码器共用的错误类型。
+// 变体表示失败类别，便于调用方按类别分支；附带的字符串是带编码器前缀的英文说明
+// （如 "range: truncated header"），Display 原样输出。
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    // 魔数或格式标志不匹配。
+    BadMagic(&'static str),
+    // 输入在完整读出某个字段或数据流之前结束。
+    Truncated(&'static str),
+    // 头部中的频率表、码长或字典非法。
+    InvalidModel(&'static str),
+    // 数据体损坏（非法记录、无法解出的码字等）。
+    InvalidData(&'static str),
+    // 调用参数非法。
+    InvalidInput(&'static str),
+    ChecksumMismatch(&'static str),
+    // 输出超过调用方设定的上限。
+    LimitExceeded(&'static str),
+    Cancelled(&'static str),
+    #[cfg(feature = "std")]
+    Io(io::Error),
+}
+
+#[cfg(feature = "std")]
+impl Error {
+    // 将读取时的 io::Error 归类：UnexpectedEof 视为截断，其余保留为 Io。
+    pub fn from_read(e: io::Error, what: &'static str) -> Error {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            Error::Truncated(what)
+        } else {
+            Error::Io(e)
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::BadMagic(msg)
+            | Error::Truncated(msg)
+            | Error::InvalidModel(msg)
+            | Error::InvalidData(msg)
+            | Error::InvalidInput(msg)
+            | Error::ChecksumMismatch(msg)
+            | Error::LimitExceeded(msg)
+            | Error::Cancelled(msg) => write!(f, "{msg}"),
+            #[cfg(feature = "std")]
+            Error::Io(e) => write!(f, "I/O error: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+// 供 Read / Write 适配器返回：Io 还原为原来的 io::Error，其余归为 InvalidData。
+#[cfg(feature = "std")]
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Io(e) => e,
+            other => io::Error::new(io::ErrorKind::InvalidData, other),
+        }
+    }
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+// 解码的资源上限，用于处理不可信输入。max_memory 同时约束解码所需的表
+// （Huffman 树、区间编码的频率与累积表、读入内存的输入等）与输出大小，
+// 超出时各编码器返回 Error::LimitExceeded，而不是耗尽进程内存。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    max_memory: u64,
+}
+
+impl DecodeLimits {
+    pub fn new(max_memory: u64) -> DecodeLimits {
+        DecodeLimits { max_memory }
+    }
+
+    pub fn unlimited() -> DecodeLimits {
+        DecodeLimits::new(u64::MAX)
+    }
+
+    pub fn max_memory(&self) -> u64 {
+        self.max_memory
+    }
+
+    // 扣除 table_bytes 字节的表之后剩余的输出额度；表本身已超出上限时返回 None。
+    pub fn output_budget(&self, table_bytes: u64) -> Option<u64> {
+        self.max_memory.checked_sub(table_bytes)
+    }
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        DecodeLimits::unlimited()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn io_errors_chain_source() {
+        let err = Error::from(io::Error::new(io::ErrorKind::PermissionDenied, "denied"));
+        assert_eq!(err.to_string(), "I/O error: denied");
+        assert_eq!(err.source().unwrap().to_string(), "denied");
+        assert!(Error::BadMagic("range: bad magic").source().is_none());
+
+        let eof = io::Error::from(io::ErrorKind::UnexpectedEof);
+        assert!(matches!(
+            Error::from_read(eof, "x: truncated"),
+            Error::Truncated(_)
+        ));
+        let other = io::Error::from(io::ErrorKind::Other);
+        assert!(matches!(
+            Error::from_read(other, "x: truncated"),
+            Error::Io(_)
+        ));
+    }
+}
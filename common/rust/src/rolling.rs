@@ -0,0 +1,112 @@
+// 滑动窗口多项式哈希（Rabin-Karp），用于内容定义分块与 LZ 匹配查找。
+//
+// 窗口 w 内字节 b_0..b_{w-1}（b_0 最早进入）的哈希为
+//     H = Σ (b_i + 1) · BASE^(w-1-i)  mod 2^64
+// 每个字节加 1，使开头的 0 字节也会改变哈希。模 2^64 由 wrapping 运算自然得到，
+// BASE 取奇数以保证与 2^64 互素，使每个位置的贡献都不会被乘成 0。
+// 窗口滑动一格时 H' = (H - (out + 1) · BASE^(w-1)) · BASE + (in + 1)，为 O(1)。
+
+// 默认窗口大小（字节）。
+pub const DEFAULT_WINDOW: usize = 48;
+
+#[derive(Debug, Clone)]
+pub struct RollingHash {
+    hash: u64,
+    window: usize,
+    // BASE^(window-1)，滑出字节的权重。
+    out_weight: u64,
+}
+
+impl RollingHash {
+    // 64 位 FNV 素数。
+    pub const BASE: u64 = 0x0000_0100_0000_01B3;
+
+    // window 为 0 时按 1 处理。
+    pub fn new(window: usize) -> RollingHash {
+        let window = window.max(1);
+        let mut out_weight = 1u64;
+        for _ in 1..window {
+            out_weight = out_weight.wrapping_mul(Self::BASE);
+        }
+        RollingHash {
+            hash: 0,
+            window,
+            out_weight,
+        }
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn reset(&mut self) {
+        self.hash = 0;
+    }
+
+    // 窗口尚未填满时追加一个字节。
+    pub fn push(&mut self, in_byte: u8) {
+        self.hash = self
+            .hash
+            .wrapping_mul(Self::BASE)
+            .wrapping_add(in_byte as u64 + 1);
+    }
+
+    // 窗口已满时滑动一格：out_byte 为离开窗口的最早字节，in_byte 为新进入的字节。
+    pub fn roll(&mut self, out_byte: u8, in_byte: u8) {
+        let without = self
+            .hash
+            .wrapping_sub((out_byte as u64 + 1).wrapping_mul(self.out_weight));
+        self.hash = without
+            .wrapping_mul(Self::BASE)
+            .wrapping_add(in_byte as u64 + 1);
+    }
+
+    // 从头计算一个窗口的哈希，与 push 整个窗口的结果相同。
+    pub fn of(window: &[u8]) -> u64 {
+        let mut h = RollingHash::new(window.len());
+        for &b in window {
+            h.push(b);
+        }
+        h.hash()
+    }
+}
+
+impl Default for RollingHash {
+    fn default() -> Self {
+        RollingHash::new(DEFAULT_WINDOW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selftest::pseudo_random;
+
+    #[test]
+    fn rolling_matches_from_scratch() {
+        let mut data = pseudo_random(2000, 42);
+        data[500..600].fill(0);
+        for window in [1, 2, 16, DEFAULT_WINDOW] {
+            let mut h = RollingHash::new(window);
+            for &b in &data[..window] {
+                h.push(b);
+            }
+            assert_eq!(h.hash(), RollingHash::of(&data[..window]));
+            for i in window..data.len() {
+                h.roll(data[i - window], data[i]);
+                assert_eq!(
+                    h.hash(),
+                    RollingHash::of(&data[i + 1 - window..=i]),
+                    "window {window} pos {i}"
+                );
+            }
+        }
+        // 全 0 窗口的哈希不为 0，窗口内 0 字节的个数可以区分。
+        assert_ne!(RollingHash::of(&[0; 4]), RollingHash::of(&[0; 3]));
+        assert_ne!(RollingHash::of(&[0; 4]), 0);
+    }
+}
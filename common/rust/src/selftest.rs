@@ -0,0 +1,104 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use crate::Error;
+
+// 自检与测试共用的数据剖面生成器。数据由固定种子生成，各平台上逐字节一致。
+pub fn profiles() -> Vec<(&'static str, Vec<u8>)> {
+    let text = b"The quick brown fox jumps over the lazy dog. ".repeat(200);
+
+    let mut long_runs = Vec::new();
+    for (i, &b) in b"aAbB\x00\xFF".iter().enumerate() {
+        long_runs.extend(std::iter::repeat_n(b, 300 + i * 997));
+    }
+
+    // 所有 256 个字节值都出现，但绝大多数是 0。
+    let mut skewed = vec![0u8; 50_000];
+    for b in 0..=255u8 {
+        skewed[b as usize * 193] = b;
+    }
+
+    vec![
+        ("empty", Vec::new()),
+        ("single-byte", vec![0x5A]),
+        ("text", text),
+        ("long-runs", long_runs),
+        ("skewed", skewed),
+        ("random", pseudo_random(1 << 16, 0x9E37_79B9_7F4A_7C15)),
+    ]
+}
+
+// xorshift64* 伪随机字节，seed 不能为 0。
+pub fn pseudo_random(len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            (state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 56) as u8
+        })
+        .collect()
+}
+
+// 对每个剖面执行 roundtrip 并逐项打印 PASS/FAIL，全部通过时返回 true。
+pub fn run(codec: &str, roundtrip: impl Fn(&[u8]) -> Result<Vec<u8>, Error>) -> bool {
+    let mut ok = true;
+    for (name, data) in profiles() {
+        match roundtrip(&data) {
+            Ok(out) if out == data => println!("PASS {codec}/{name}"),
+            Ok(out) => {
+                println!(
+                    "FAIL {codec}/{name}: output differs ({} bytes in, {} bytes out)",
+                    data.len(),
+                    out.len()
+                );
+                ok = false;
+            }
+            Err(e) => {
+                println!("FAIL {codec}/{name}: {e}");
+                ok = false;
+            }
+        }
+    }
+    ok
+}
+
+// 通过临时文件执行一次 encode → decode，供只有文件接口的编码器使用。
+pub fn roundtrip_via_files(
+    data: &[u8],
+    encode: impl Fn(&str, &str) -> Result<(), Error>,
+    decode: impl Fn(&str, &str) -> Result<(), Error>,
+) -> Result<Vec<u8>, Error> {
+    let paths = ["in", "enc", "out"].map(temp_path);
+    let [input, encoded, decoded] = paths.each_ref().map(|p| p.to_str().unwrap());
+    let result = fs::write(input, data)
+        .map_err(Error::from)
+        .and_then(|_| encode(input, encoded))
+        .and_then(|_| decode(encoded, decoded))
+        .and_then(|_| fs::read(decoded).map_err(Error::from));
+    for p in &paths {
+        let _ = fs::remove_file(p);
+    }
+    result
+}
+
+fn temp_path(suffix: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("encoding-selftest-{}.{suffix}", process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_failures() {
+        assert!(run("identity", |data| Ok(data.to_vec())));
+        assert!(!run("truncate", |data| Ok(data[..data.len() / 2].to_vec())));
+        let broken = Error::InvalidData;
+        assert!(!run("error", |_| Err(broken("selftest: broken"))));
+        let copy = |i: &str, o: &str| fs::copy(i, o).map(|_| ()).map_err(Error::from);
+        assert!(run("files", |data| roundtrip_via_files(data, copy, copy)));
+    }
+}
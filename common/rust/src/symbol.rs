@@ -0,0 +1,81 @@
+// 各编码器共用的字母表约定与小端整数读写，改动这里即同时改动所有格式。
+//
+// 字母表为 256 个字节值加 1 个 EOF 符号：range 与 Huffman 的频率表都是 SYMBOL_LIMIT 项，
+// EOF 固定为最后一个符号。
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+pub const SYMBOL_LIMIT: usize = 257;
+pub const EOF_SYMBOL: usize = SYMBOL_LIMIT - 1;
+
+pub fn write_u32_le(out: &mut Vec<u8>, v: u32) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+// 从 input[*pos..] 读取一个 32 位小端整数并前移 pos；剩余不足 4 字节时返回 None 且不移动 pos。
+pub fn read_u32_le(input: &[u8], pos: &mut usize) -> Option<u32> {
+    let bytes = input.get(*pos..pos.checked_add(4)?)?;
+    *pos += 4;
+    Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+// 流式版本，供逐记录读写文件的编码器使用。
+#[cfg(feature = "std")]
+pub fn write_u32_le_to<W: Write>(w: &mut W, v: u32) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+// 从流中读取一个 32 位小端整数：Ok(None) 表示一个字节都没读到的正常 EOF，
+// 读到部分字节后结束返回 UnexpectedEof（可用 Error::from_read 归类为截断）。
+#[cfg(feature = "std")]
+pub fn read_u32_le_from<R: Read>(r: &mut R) -> io::Result<Option<u32>> {
+    let mut buf = [0u8; 4];
+    let mut read = 0usize;
+    while read < 4 {
+        match r.read(&mut buf[read..]) {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(Some(u32::from_le_bytes(buf)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn u32_le_helpers_roundtrip_and_stop_at_end() {
+        let mut out = Vec::new();
+        write_u32_le(&mut out, 0x0102_0304);
+        write_u32_le(&mut out, u32::MAX);
+        out.push(0xAA);
+        assert_eq!(&out[..4], [0x04, 0x03, 0x02, 0x01]);
+        let mut pos = 0;
+        assert_eq!(read_u32_le(&out, &mut pos), Some(0x0102_0304));
+        assert_eq!(read_u32_le(&out, &mut pos), Some(u32::MAX));
+        assert_eq!(read_u32_le(&out, &mut pos), None);
+        assert_eq!(pos, 8);
+        let mut far = usize::MAX;
+        assert_eq!(read_u32_le(&out, &mut far), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn streaming_u32_le_distinguishes_eof_from_truncation() {
+        let mut out = Vec::new();
+        write_u32_le_to(&mut out, 0x0102_0304).unwrap();
+        assert_eq!(out, [0x04, 0x03, 0x02, 0x01]);
+        let mut reader = &out[..];
+        assert_eq!(read_u32_le_from(&mut reader).unwrap(), Some(0x0102_0304));
+        assert_eq!(read_u32_le_from(&mut reader).unwrap(), None);
+        let err = read_u32_le_from(&mut &out[..3]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
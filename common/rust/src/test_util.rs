@@ -0,0 +1,84 @@
+// 测试辅助函数，通过 test-util feature 提供给各编码器的测试使用。
+
+// 不一致时在首个差异位置前后各显示的字节数。
+const CONTEXT: usize = 8;
+
+// 第一个不同的字节偏移；一方是另一方的前缀时为较短一方的长度，完全相同时为 None。
+pub fn first_difference(expected: &[u8], actual: &[u8]) -> Option<usize> {
+    let common = expected.len().min(actual.len());
+    match (0..common).find(|&i| expected[i] != actual[i]) {
+        Some(i) => Some(i),
+        None if expected.len() != actual.len() => Some(common),
+        None => None,
+    }
+}
+
+// 两段字节不一致时返回说明：首个差异偏移、双方长度以及差异附近的十六进制内容
+// （差异字节用方括号标出）。
+pub fn describe_difference(expected: &[u8], actual: &[u8]) -> Option<String> {
+    let offset = first_difference(expected, actual)?;
+    let start = offset.saturating_sub(CONTEXT);
+    Some(format!(
+        "bytes differ at offset {offset} (expected {} bytes, actual {} bytes)\n\
+         expected[{start}..]: {}\n  actual[{start}..]: {}",
+        expected.len(),
+        actual.len(),
+        hex_context(expected, start, offset),
+        hex_context(actual, start, offset),
+    ))
+}
+
+fn hex_context(data: &[u8], start: usize, offset: usize) -> String {
+    let end = data.len().min(offset + CONTEXT + 1);
+    let mut parts: Vec<String> = (start.min(end)..end)
+        .map(|i| match i == offset {
+            true => format!("[{:02x}]", data[i]),
+            false => format!("{:02x}", data[i]),
+        })
+        .collect();
+    if offset >= data.len() {
+        parts.push("[end]".to_string());
+    }
+    parts.join(" ")
+}
+
+// 替代 assert_eq! 比较两段字节：不一致时只报告首个差异附近的内容，而不是打印整个缓冲区。
+#[track_caller]
+pub fn assert_bytes_eq(expected: &[u8], actual: &[u8]) {
+    if let Some(message) = describe_difference(expected, actual) {
+        panic!("{message}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_first_difference() {
+        let expected: Vec<u8> = (0..64).collect();
+        let mut actual = expected.clone();
+        actual[37] = 0xFF;
+        actual[50] = 0xFF;
+        assert_eq!(first_difference(&expected, &actual), Some(37));
+        let message = describe_difference(&expected, &actual).unwrap();
+        assert!(
+            message.starts_with("bytes differ at offset 37 (expected 64 bytes, actual 64 bytes)")
+        );
+        assert!(message.contains("expected[29..]: 1d 1e 1f 20 21 22 23 24 [25] 26"));
+        assert!(message.contains("actual[29..]: 1d 1e 1f 20 21 22 23 24 [ff] 26"));
+
+        // 一方是另一方的前缀。
+        assert_eq!(first_difference(b"abc", b"abcd"), Some(3));
+        let message = describe_difference(b"abc", b"abcd").unwrap();
+        assert!(message.contains("61 62 63 [end]"), "{message}");
+        assert_eq!(describe_difference(b"same", b"same"), None);
+
+        assert_bytes_eq(&expected, &expected);
+        let panic = std::panic::catch_unwind(|| assert_bytes_eq(&expected, &actual)).unwrap_err();
+        assert!(panic
+            .downcast_ref::<String>()
+            .unwrap()
+            .contains("offset 37"));
+    }
+}
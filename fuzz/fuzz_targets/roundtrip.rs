@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// 任意输入都必须能编码并精确还原；同一份输入再作为编码数据交给解码器与按魔数分派的
+// codec::decode_any，只允许返回错误或有限的输出，不允许 panic。
+fuzz_target!(|data: &[u8]| {
+    let encoded = rangecoder::encode(data).expect("range: encode failed");
+    let decoded = rangecoder::decode(&encoded).expect("range: decode failed");
+    assert!(decoded == data, "range: roundtrip mismatch");
+    assert!(rangecoder::verify(data).expect("range: verify failed"));
+
+    let _ = rangecoder::decode_with_limit(data, 1 << 20);
+    let _ = codec::decode_any(data);
+});
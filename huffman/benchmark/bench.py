@@ -9,6 +9,8 @@ ROOT = Path(__file__).resolve().parent.parent
 CPP_DIR = ROOT / "cpp"
 GO_DIR = ROOT / "go"
 RUST_DIR = ROOT / "rust"
+# Rust 实现属于仓库根目录的 Cargo workspace，产物统一输出到根目录 target/
+CARGO_TARGET_DIR = ROOT.parent / "target"
 BENCH_DIR = ROOT / "benchmark"
 TMP_DIR = BENCH_DIR / "tmp"
 
@@ -40,7 +42,7 @@ def compile_all():
     times = {}
     times["cpp_build"] = run(["g++", "-std=c++17", "-O2", "main.cpp", "-o", "huffman_cpp"], CPP_DIR)
     times["go_build"] = run(["go", "build", "-o", "huffman_go", "."], GO_DIR)
-    times["rust_build"] = run(["rustc", "-O", "main.rs", "-o", "huffman_rust"], RUST_DIR)
+    times["rust_build"] = run(["cargo", "build", "--release", "--bin", "huffman_rust"], RUST_DIR)
     return times
 
 
@@ -78,7 +80,7 @@ def main():
 
     cpp_exe = CPP_DIR / "huffman_cpp"
     go_exe = GO_DIR / "huffman_go"
-    rust_exe = RUST_DIR / "huffman_rust"
+    rust_exe = CARGO_TARGET_DIR / "release" / "huffman_rust"
 
     cpp_enc = TMP_DIR / "cpp.huf"
     cpp_dec = TMP_DIR / "cpp.out"
@@ -0,0 +1,2550 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+pub use encoding_common::{DecodeLimits, Error};
+
+use encoding_common::symbol::SYMBOL_LIMIT;
+
+// 树节点中的符号为 u32，EOF 与 range 编码器共用 encoding_common::symbol 中的定义。
+const EOF_SYMBOL: u32 = encoding_common::symbol::EOF_SYMBOL as u32;
+const _: () = assert!(EOF_SYMBOL as usize + 1 == SYMBOL_LIMIT);
+
+struct Node {
+    symbol: u32,
+    freq: u64,
+    left: Option<Box<Node>>,
+    right: Option<Box<Node>>,
+}
+
+fn is_leaf(node: &Node) -> bool {
+    node.left.is_none() && node.right.is_none()
+}
+
+struct HeapItem {
+    freq: u64,
+    symbol: u32,
+    node: Box<Node>,
+}
+
+impl Eq for HeapItem {}
+
+impl PartialEq for HeapItem {
+    fn eq(&self, other: &Self) -> bool {
+        self.freq == other.freq && self.symbol == other.symbol
+    }
+}
+
+impl Ord for HeapItem {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.freq.cmp(&self.freq).then_with(|| other.symbol.cmp(&self.symbol))
+    }
+}
+
+impl PartialOrd for HeapItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn build_tree(freq: &[u32]) -> Box<Node> {
+    let mut heap = BinaryHeap::<HeapItem>::new();
+    for (s, &f) in freq.iter().enumerate() {
+        if f == 0 {
+            continue;
+        }
+        let node = Box::new(Node {
+            symbol: s as u32,
+            freq: f as u64,
+            left: None,
+            right: None,
+        });
+        heap.push(HeapItem {
+            freq: node.freq,
+            symbol: node.symbol,
+            node,
+        });
+    }
+    if heap.is_empty() {
+        return Box::new(Node {
+            symbol: EOF_SYMBOL,
+            freq: 1,
+            left: None,
+            right: None,
+        });
+    }
+    if heap.len() == 1 {
+        let item = heap.pop().unwrap();
+        let only = item.node;
+        let parent = Box::new(Node {
+            symbol: 0,
+            freq: only.freq,
+            left: Some(only),
+            right: None,
+        });
+        heap.push(HeapItem {
+            freq: parent.freq,
+            symbol: parent.symbol,
+            node: parent,
+        });
+    }
+    while heap.len() > 1 {
+        let a = heap.pop().unwrap().node;
+        let b = heap.pop().unwrap().node;
+        let freq_sum = a.freq + b.freq;
+        let parent = Box::new(Node {
+            symbol: 0,
+            freq: freq_sum,
+            left: Some(a),
+            right: Some(b),
+        });
+        let symbol = parent.symbol;
+        heap.push(HeapItem {
+            freq: parent.freq,
+            symbol,
+            node: parent,
+        });
+    }
+    heap.pop().unwrap().node
+}
+
+struct BitWriter<W: Write> {
+    writer: W,
+    // 尚未凑满一个字节的比特，右对齐。
+    buffer: u64,
+    bits_in_buffer: u8,
+}
+
+impl<W: Write> BitWriter<W> {
+    fn new(writer: W) -> Self {
+        BitWriter {
+            writer,
+            buffer: 0,
+            bits_in_buffer: 0,
+        }
+    }
+
+    // 高位在前写出 value 的低 count 位（count <= 64），凑满的整字节立即交给 writer。
+    fn write_bits(&mut self, value: u64, count: u8) -> io::Result<()> {
+        debug_assert!(count <= 64, "huffman: too many bits");
+        // 缓冲区最多留有 7 位，一次最多并入 56 位，更长的先写高位部分。
+        if count > 56 {
+            self.write_bits(value >> 32, count - 32)?;
+            return self.write_bits(value & 0xFFFF_FFFF, 32);
+        }
+        let mask = (1u64 << count) - 1;
+        self.buffer = (self.buffer << count) | (value & mask);
+        self.bits_in_buffer += count;
+        if self.bits_in_buffer < 8 {
+            return Ok(());
+        }
+        let mut bytes = [0u8; 8];
+        let mut n = 0;
+        while self.bits_in_buffer >= 8 {
+            self.bits_in_buffer -= 8;
+            bytes[n] = (self.buffer >> self.bits_in_buffer) as u8;
+            n += 1;
+        }
+        self.buffer &= (1u64 << self.bits_in_buffer) - 1;
+        self.writer.write_all(&bytes[..n])
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.bits_in_buffer > 0 {
+            let byte = (self.buffer << (8 - self.bits_in_buffer)) as u8;
+            self.writer.write_all(&[byte])?;
+            self.bits_in_buffer = 0;
+            self.buffer = 0;
+        }
+        self.writer.flush()
+    }
+}
+
+struct BitReader<R: Read> {
+    reader: R,
+    // 已读入但尚未消耗的比特，右对齐；只有 peek_bits 会一次预读多个字节。
+    buffer: u64,
+    bits_in_buffer: u8,
+    // 已消耗的输入比特数，不含输入结束后补的 0。
+    consumed: u64,
+    reached_eof: bool,
+}
+
+impl<R: Read> BitReader<R> {
+    fn new(reader: R) -> Self {
+        BitReader {
+            reader,
+            buffer: 0,
+            bits_in_buffer: 0,
+            consumed: 0,
+            reached_eof: false,
+        }
+    }
+
+    // 读入一个字节；输入已结束（或读取出错）时返回 false。
+    fn fill_byte(&mut self) -> bool {
+        let mut buf = [0u8; 1];
+        match self.reader.read(&mut buf) {
+            Ok(0) | Err(_) => false,
+            Ok(_) => {
+                self.buffer = (self.buffer << 8) | buf[0] as u64;
+                self.bits_in_buffer += 8;
+                true
+            }
+        }
+    }
+
+    // 输入结束后返回 0 并置 eof 标志。
+    fn read_bit(&mut self) -> u8 {
+        if self.bits_in_buffer == 0 && !self.fill_byte() {
+            self.reached_eof = true;
+            return 0;
+        }
+        self.bits_in_buffer -= 1;
+        self.consumed += 1;
+        ((self.buffer >> self.bits_in_buffer) & 1) as u8
+    }
+
+    // 查看接下来的 count 位（count <= 32）而不消耗，输入结束后的部分补 0。
+    fn peek_bits(&mut self, count: u8) -> u32 {
+        while self.bits_in_buffer < count && self.fill_byte() {}
+        let mask = (1u64 << count) - 1;
+        let bits = if self.bits_in_buffer >= count {
+            self.buffer >> (self.bits_in_buffer - count)
+        } else {
+            self.buffer << (count - self.bits_in_buffer)
+        };
+        (bits & mask) as u32
+    }
+
+    // 消耗 peek_bits 看到的前 count 位；超出输入的部分与 read_bit 一样置 eof 标志。
+    fn consume(&mut self, count: u8) {
+        if count <= self.bits_in_buffer {
+            self.bits_in_buffer -= count;
+            self.consumed += count as u64;
+        } else {
+            self.consumed += self.bits_in_buffer as u64;
+            self.bits_in_buffer = 0;
+            self.reached_eof = true;
+        }
+    }
+
+    // 精确的比特位置：解码器在 EOF 符号之后停止时，其后不足一个字节的比特都是补位。
+    fn bits_consumed(&self) -> u64 {
+        self.consumed
+    }
+
+    fn read_bits(&mut self, count: u8) -> u64 {
+        let mut value = 0u64;
+        for _ in 0..count {
+            value = (value << 1) | self.read_bit() as u64;
+        }
+        value
+    }
+
+    fn eof(&self) -> bool {
+        self.reached_eof
+    }
+}
+
+fn build_frequencies_from_file(path: &str) -> Vec<u32> {
+    let mut freq = vec![0u32; SYMBOL_LIMIT];
+    if let Ok(file) = File::open(path) {
+        let mut reader = BufReader::new(file);
+        let mut buf = [0u8; 4096];
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    for &b in &buf[..n] {
+                        freq[b as usize] += 1;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    }
+    freq[EOF_SYMBOL as usize] = 1;
+    freq
+}
+
+fn write_frequencies<W: Write>(writer: &mut W, freq: &[u32]) -> io::Result<()> {
+    let count = freq.len() as u32;
+    writer.write_all(&count.to_le_bytes())?;
+    for &v in freq {
+        writer.write_all(&v.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn read_frequencies<R: Read>(reader: &mut R) -> Result<Vec<u32>, Error> {
+    let mut count_bytes = [0u8; 4];
+    reader
+        .read_exact(&mut count_bytes)
+        .map_err(|e| Error::from_read(e, "huffman: truncated frequency table"))?;
+
+    let count = u32::from_le_bytes(count_bytes) as usize;
+    if count != SYMBOL_LIMIT {
+        return Err(Error::InvalidModel("huffman: bad symbol count"));
+    }
+
+    let mut freq = vec![0u32; count];
+    for f in freq.iter_mut() {
+        let mut arr = [0u8; 4];
+        reader
+            .read_exact(&mut arr)
+            .map_err(|e| Error::from_read(e, "huffman: truncated frequency table"))?;
+        *f = u32::from_le_bytes(arr);
+    }
+
+    Ok(freq)
+}
+
+// 每个符号的 (码字, 码长)，码字右对齐、高位先输出；码长为 0 表示模型中没有该符号。
+type CodeTable = [(u64, u8); SYMBOL_LIMIT];
+
+// 左分支为 0、右分支为 1；只有一个叶子时其码字为单个 0 比特。
+// 编码数据时 EOF 总在模型中，只含一种字节的输入也有两个叶子，各占 1 位码字；
+// 单叶子只出现在空输入（仅 EOF）的模型中。
+fn build_codes(node: &Node, codes: &mut CodeTable, code: u64, len: u8) {
+    if is_leaf(node) {
+        // 257 个符号、32 位频率在实践中不会产生超过 64 位的码字。
+        debug_assert!(len <= 64, "huffman: code longer than 64 bits");
+        codes[node.symbol as usize] = (code, len.max(1));
+        return;
+    }
+    if let Some(ref left) = node.left {
+        build_codes(left, codes, code << 1, len + 1);
+    }
+    if let Some(ref right) = node.right {
+        build_codes(right, codes, (code << 1) | 1, len + 1);
+    }
+}
+
+fn build_code_table(root: &Node) -> CodeTable {
+    let mut codes = [(0u64, 0u8); SYMBOL_LIMIT];
+    build_codes(root, &mut codes, 0, 0);
+    codes
+}
+
+// 由哈夫曼树计算每个符号的码长，未出现的符号码长为 0。
+fn collect_lengths(node: &Node, depth: u8, lengths: &mut [u8]) {
+    if is_leaf(node) {
+        lengths[node.symbol as usize] = depth.max(1);
+        return;
+    }
+    if let Some(ref left) = node.left {
+        collect_lengths(left, depth + 1, lengths);
+    }
+    if let Some(ref right) = node.right {
+        collect_lengths(right, depth + 1, lengths);
+    }
+}
+
+// 根据频率表构建哈夫曼树并返回每个符号的码长（长度 SYMBOL_LIMIT）。
+pub fn build_code_lengths(freq: &[u32]) -> Vec<u8> {
+    let root = build_tree(freq);
+    let mut lengths = vec![0u8; freq.len().max(SYMBOL_LIMIT)];
+    collect_lengths(&root, 0, &mut lengths);
+    lengths
+}
+
+// 由码长分配 canonical 哈夫曼码：按 (码长, 符号) 升序依次递增分配。
+// 返回每个符号的 (码字, 码长)，码字右对齐、高位先输出；码长为 0 表示该符号无码字。
+fn build_canonical_codes(lengths: &[u8]) -> Vec<(u64, u8)> {
+    let mut order: Vec<usize> = (0..lengths.len()).filter(|&s| lengths[s] > 0).collect();
+    order.sort_by_key(|&s| (lengths[s], s));
+
+    let mut codes = vec![(0u64, 0u8); lengths.len()];
+    let mut code: u64 = 0;
+    let mut prev_len: u8 = 0;
+    for (i, &s) in order.iter().enumerate() {
+        let len = lengths[s];
+        if i > 0 {
+            code += 1;
+        }
+        code <<= len - prev_len;
+        prev_len = len;
+        codes[s] = (code, len);
+    }
+    codes
+}
+
+// 解码表中的一项，见 DecodeTable。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DecodeEntry {
+    pub symbol: u16,
+    // 码长；0 表示没有长度不超过 root_bits 的码字以该索引为前缀。
+    pub length: u8,
+}
+
+// canonical 哈夫曼解码表。
+//
+// 布局：entries 共 2^root_bits 项，索引为接下来 root_bits 个输入比特（高位在前）。
+// 对每个长度 len <= root_bits 的码字 c，所有以 c 为前缀的索引
+// [c << (root_bits - len), (c + 1) << (root_bits - len)) 都指向 (symbol, len)，
+// 因此一次查表即可得到符号并只消耗 len 个比特。
+// 长度超过 root_bits 的码字对应项的 length 为 0，此时按 canonical 顺序逐长度比较
+// （first_code / counts / symbols），见 decode。
+#[derive(Debug, Clone)]
+pub struct DecodeTable {
+    root_bits: u8,
+    max_len: u8,
+    entries: Vec<DecodeEntry>,
+    // 按码长索引：该长度的第一个码字、码字个数以及其在 symbols 中的起始位置。
+    first_code: Vec<u64>,
+    counts: Vec<u32>,
+    first_index: Vec<u32>,
+    // 按 (码长, 符号) 排序的符号列表。
+    symbols: Vec<u16>,
+}
+
+impl DecodeTable {
+    pub fn root_bits(&self) -> u8 {
+        self.root_bits
+    }
+
+    // 最长码长，调用方每次至少需要准备 min(max_len, 64) 个比特才能保证解出一个符号。
+    pub fn max_len(&self) -> u8 {
+        self.max_len
+    }
+
+    pub fn entries(&self) -> &[DecodeEntry] {
+        &self.entries
+    }
+
+    // bits 为接下来的 available 个输入比特（高位在前、右对齐，available <= 64）。
+    // 返回 (符号, 码长)；比特不足以确定符号或码字非法时返回 None。
+    pub fn decode(&self, bits: u64, available: u8) -> Option<(u16, u8)> {
+        let root = self.root_bits;
+        let index = if available >= root {
+            bits >> (available - root)
+        } else {
+            bits << (root - available)
+        };
+        let entry = self.entries[index as usize & ((1usize << root) - 1)];
+        if entry.length > 0 {
+            return if entry.length <= available {
+                Some((entry.symbol, entry.length))
+            } else {
+                None
+            };
+        }
+        for len in (root + 1)..=self.max_len.min(available) {
+            let code = bits >> (available - len);
+            let l = len as usize;
+            if self.counts[l] > 0 && code >= self.first_code[l] {
+                let offset = code - self.first_code[l];
+                if offset < self.counts[l] as u64 {
+                    let idx = self.first_index[l] as usize + offset as usize;
+                    return Some((self.symbols[idx], len));
+                }
+            }
+        }
+        None
+    }
+}
+
+// 根据码长数组（例如 build_code_lengths 的结果）构建 canonical 解码表。
+// root_bits 为一级表的索引位数，取值 1..=24，否则返回 InvalidInput。码长可以来自调用方自己的数据流：
+// 码长超过 MAX_CODE_LEN（63）、不满足 Kraft 不等式或符号多于 65536 个时返回 InvalidModel。
+pub fn build_decode_table(lengths: &[u8], root_bits: u8) -> Result<DecodeTable, Error> {
+    if !(1..=24).contains(&root_bits) {
+        return Err(Error::InvalidInput("huffman: bad root_bits"));
+    }
+    if lengths.len() > 1 << 16 || lengths.iter().any(|&len| len > MAX_CODE_LEN) {
+        return Err(Error::InvalidModel("huffman: bad code lengths"));
+    }
+    // 各码长对应的码字空间之和不得超过 2^64，否则码字重叠，填表时越界。
+    let kraft: u128 = lengths
+        .iter()
+        .filter(|&&len| len > 0)
+        .map(|&len| 1u128 << (64 - len))
+        .sum();
+    if kraft > 1u128 << 64 {
+        return Err(Error::InvalidModel("huffman: bad code lengths"));
+    }
+    let max_len = lengths.iter().copied().max().unwrap_or(0);
+    let codes = build_canonical_codes(lengths);
+
+    let mut entries = vec![DecodeEntry::default(); 1usize << root_bits];
+    let mut counts = vec![0u32; max_len as usize + 1];
+    for (symbol, &(code, len)) in codes.iter().enumerate() {
+        if len == 0 {
+            continue;
+        }
+        counts[len as usize] += 1;
+        if len <= root_bits {
+            let shift = root_bits - len;
+            let start = (code << shift) as usize;
+            let end = ((code + 1) << shift) as usize;
+            for e in &mut entries[start..end] {
+                *e = DecodeEntry {
+                    symbol: symbol as u16,
+                    length: len,
+                };
+            }
+        }
+    }
+
+    let mut symbols: Vec<u16> = (0..lengths.len())
+        .filter(|&s| lengths[s] > 0)
+        .map(|s| s as u16)
+        .collect();
+    symbols.sort_by_key(|&s| (lengths[s as usize], s));
+
+    let mut first_code = vec![0u64; max_len as usize + 1];
+    let mut first_index = vec![0u32; max_len as usize + 1];
+    let mut index = 0u32;
+    for len in 1..=max_len as usize {
+        if counts[len] > 0 {
+            let s = symbols[index as usize] as usize;
+            first_code[len] = codes[s].0;
+        }
+        first_index[len] = index;
+        index += counts[len];
+    }
+
+    Ok(DecodeTable {
+        root_bits,
+        max_len,
+        entries,
+        first_code,
+        counts,
+        first_index,
+        symbols,
+    })
+}
+
+// 外部模型格式：魔数之后仅写 1 字节标志，频率表保存在单独的模型文件中。
+const MODEL_EXTERNAL: u8 = 0xE0;
+const MODEL_MAGIC: &[u8; 4] = b"HFMD";
+
+// 文件接口的路径为 "-" 时，输入读自标准输入、输出写到标准输出，便于在管道中使用。
+// 两者都按原始字节读写，不做任何换行或编码转换。
+const STDIO_PATH: &str = "-";
+
+fn create_output(output_path: &str) -> io::Result<BufWriter<Box<dyn Write>>> {
+    let output: Box<dyn Write> = if output_path == STDIO_PATH {
+        Box::new(io::stdout().lock())
+    } else {
+        Box::new(File::create(output_path)?)
+    };
+    Ok(BufWriter::new(output))
+}
+
+fn open_input(input_path: &str) -> io::Result<BufReader<Box<dyn Read>>> {
+    let input: Box<dyn Read> = if input_path == STDIO_PATH {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(File::open(input_path)?)
+    };
+    Ok(BufReader::new(input))
+}
+
+fn read_input(input_path: &str) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    open_input(input_path)?.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn write_output(output_path: &str, data: &[u8]) -> io::Result<()> {
+    let mut writer = create_output(output_path)?;
+    writer.write_all(data)?;
+    writer.flush()
+}
+
+fn read_magic<R: Read>(reader: &mut R, expected: &[u8; 4]) -> Result<(), Error> {
+    let mut magic = [0u8; 4];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|e| Error::from_read(e, "huffman: truncated header"))?;
+    if &magic != expected {
+        return Err(Error::BadMagic("huffman: bad magic"));
+    }
+    Ok(())
+}
+
+// 按给定频率表构建编码，并将 reader 中的全部字节及 EOF 编码写入 writer。
+fn encode_stream<R: Read, W: Write>(reader: &mut R, writer: W, freq: &[u32]) -> Result<(), Error> {
+    let codes = build_code_table(&build_tree(freq));
+
+    let mut bit_writer = BitWriter::new(writer);
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        for &b in &buf[..n] {
+            write_code(&mut bit_writer, codes[b as usize])?;
+        }
+    }
+    write_code(&mut bit_writer, codes[EOF_SYMBOL as usize])?;
+    bit_writer.flush()?;
+    Ok(())
+}
+
+// 写出一个码字；码长为 0 表示模型中没有该符号。
+fn write_code<W: Write>(bit_writer: &mut BitWriter<W>, code: (u64, u8)) -> Result<(), Error> {
+    if code.1 == 0 {
+        return Err(Error::InvalidInput("huffman: symbol missing from model"));
+    }
+    bit_writer.write_bits(code.0, code.1)?;
+    Ok(())
+}
+
+// 增量哈夫曼编码器：按调用方给定的频率表编码，由调用方逐字节 push。
+// 输出格式与 huffman_encode_file 相同（魔数 HFMN + 频率表 + 比特流），
+// 频率表与输入的实际直方图一致时输出逐字节相同。
+// 已完成的整字节先缓存在内部；flush_partial（或 with_flush_interval 设定的周期）
+// 把它们推送给底层 writer，尚未凑满一个字节的比特留在编码器中，不影响最终输出。
+pub struct HuffmanEncoder<W: Write> {
+    bit_writer: BitWriter<BufWriter<W>>,
+    codes: Box<CodeTable>,
+    flush_interval: usize,
+    since_flush: usize,
+}
+
+impl<W: Write> HuffmanEncoder<W> {
+    // freq 须为 257 项且 EOF 频率非零；立即写出头部（暂存在内部缓冲区中）。
+    pub fn new(writer: W, freq: &[u32]) -> Result<Self, Error> {
+        if freq.len() != SYMBOL_LIMIT || freq[EOF_SYMBOL as usize] == 0 {
+            return Err(Error::InvalidModel("huffman: bad frequency table"));
+        }
+        let mut writer = BufWriter::new(writer);
+        writer.write_all(b"HFMN")?;
+        write_frequencies(&mut writer, freq)?;
+
+        let codes = Box::new(build_code_table(&build_tree(freq)));
+        Ok(HuffmanEncoder {
+            bit_writer: BitWriter::new(writer),
+            codes,
+            flush_interval: 0,
+            since_flush: 0,
+        })
+    }
+
+    // 每 push interval 个字节自动调用一次 flush_partial；0 表示不自动推送。
+    pub fn with_flush_interval(mut self, interval: usize) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    pub fn push(&mut self, b: u8) -> Result<(), Error> {
+        write_code(&mut self.bit_writer, self.codes[b as usize])?;
+        if self.flush_interval > 0 {
+            self.since_flush += 1;
+            if self.since_flush == self.flush_interval {
+                self.flush_partial()?;
+            }
+        }
+        Ok(())
+    }
+
+    // 把已完成的整字节推送给底层 writer 并 flush 它；不足一个字节的比特不会写出。
+    pub fn flush_partial(&mut self) -> Result<(), Error> {
+        self.since_flush = 0;
+        self.bit_writer.writer.flush()?;
+        Ok(())
+    }
+
+    // 写出 EOF 码字与末尾补齐，flush 后返回底层 writer。
+    pub fn finish(mut self) -> Result<W, Error> {
+        write_code(&mut self.bit_writer, self.codes[EOF_SYMBOL as usize])?;
+        self.bit_writer.flush()?;
+        self.bit_writer
+            .writer
+            .into_inner()
+            .map_err(|e| Error::Io(e.into_error()))
+    }
+}
+
+// 快速解码表的索引位数：码长不超过 FAST_BITS 的码字一次查表解出，更长的码字退回逐比特遍历。
+const FAST_BITS: u8 = 12;
+
+// 以接下来 FAST_BITS 位为下标的 (符号, 码长)；码长为 0 表示码字更长或不是合法前缀。
+fn build_fast_table(codes: &CodeTable) -> Vec<(u16, u8)> {
+    let mut table = vec![(0u16, 0u8); 1 << FAST_BITS];
+    for (symbol, &(code, len)) in codes.iter().enumerate() {
+        if len == 0 || len > FAST_BITS {
+            continue;
+        }
+        let shift = FAST_BITS - len;
+        let start = (code << shift) as usize;
+        let end = ((code + 1) << shift) as usize;
+        table[start..end].fill((symbol as u16, len));
+    }
+    table
+}
+
+// 按给定频率表重建哈夫曼树，从 reader 解码直到 EOF 符号。
+// 解码出的字节数超过 max_output 时返回 LimitExceeded。
+fn decode_stream<R: Read, W: Write>(
+    reader: R,
+    writer: &mut W,
+    freq: &[u32],
+    max_output: u64,
+) -> Result<(), Error> {
+    decode_bits(&mut BitReader::new(reader), writer, freq, max_output).map(|_| ())
+}
+
+// 解到 EOF 符号的最后一个比特即停止，之后的补位与尾随数据都不会被读取为符号；
+// 只有码字用到输入结束之后的比特时才视为截断。返回包括 EOF 码字在内消耗的比特数。
+// 短码字查 build_fast_table 的表，其余从树根逐比特遍历；截断、补位与出错时的行为与逐比特解码相同。
+fn decode_bits<R: Read, W: Write>(
+    bit_reader: &mut BitReader<R>,
+    writer: &mut W,
+    freq: &[u32],
+    max_output: u64,
+) -> Result<u64, Error> {
+    let root = build_tree(freq);
+    // 全零频率表得到单个叶子的树，没有任何合法码字。
+    if is_leaf(&root) {
+        return Err(Error::InvalidData("huffman: invalid code"));
+    }
+    let fast = build_fast_table(&build_code_table(&root));
+    let mut written: u64 = 0;
+
+    loop {
+        let (symbol, len) = fast[bit_reader.peek_bits(FAST_BITS) as usize];
+        let symbol = if len > 0 {
+            bit_reader.consume(len);
+            symbol as u32
+        } else {
+            let mut node_ref: &Node = &root;
+            while !is_leaf(node_ref) {
+                let child = match bit_reader.read_bit() {
+                    0 => &node_ref.left,
+                    _ => &node_ref.right,
+                };
+                node_ref = child
+                    .as_deref()
+                    .ok_or(Error::InvalidData("huffman: invalid code"))?;
+            }
+            node_ref.symbol
+        };
+        if symbol == EOF_SYMBOL {
+            break;
+        }
+        if written == max_output {
+            return Err(Error::LimitExceeded("huffman: output limit exceeded"));
+        }
+        writer.write_all(&[symbol as u8])?;
+        written += 1;
+        // 码字用到了输入之后的补位比特：数据流在 EOF 之前被截断。
+        if bit_reader.eof() {
+            return Err(Error::Truncated("huffman: missing EOF symbol"));
+        }
+    }
+    writer.flush()?;
+    Ok(bit_reader.bits_consumed())
+}
+
+// 逐比特遍历哈夫曼树的参考实现，decode_stream 的结果（包括出错时已写出的字节）须与之完全一致。
+#[cfg(test)]
+fn decode_stream_bitwise<R: Read, W: Write>(
+    reader: R,
+    writer: &mut W,
+    freq: &[u32],
+    max_output: u64,
+) -> Result<(), Error> {
+    let root = build_tree(freq);
+    let mut written: u64 = 0;
+
+    let mut bit_reader = BitReader::new(reader);
+    let mut node_ref: &Node = &root;
+    let mut saw_eof = false;
+    loop {
+        let bit = bit_reader.read_bit();
+        if bit == 0 {
+            match node_ref.left {
+                Some(ref left) => {
+                    node_ref = left;
+                }
+                None => {
+                    return Err(Error::InvalidData("huffman: invalid code"));
+                }
+            }
+        } else {
+            match node_ref.right {
+                Some(ref right) => {
+                    node_ref = right;
+                }
+                None => {
+                    return Err(Error::InvalidData("huffman: invalid code"));
+                }
+            }
+        }
+        if is_leaf(node_ref) {
+            if node_ref.symbol == EOF_SYMBOL {
+                saw_eof = true;
+                break;
+            }
+            if written == max_output {
+                return Err(Error::LimitExceeded("huffman: output limit exceeded"));
+            }
+            writer.write_all(&[node_ref.symbol as u8])?;
+            written += 1;
+            node_ref = &root;
+        }
+        if bit_reader.eof() && std::ptr::eq(node_ref, &*root) {
+            break;
+        }
+    }
+
+    if !saw_eof {
+        return Err(Error::Truncated("huffman: missing EOF symbol"));
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn build_frequencies(data: &[u8]) -> Vec<u32> {
+    let mut freq = vec![0u32; SYMBOL_LIMIT];
+    for &b in data {
+        freq[b as usize] += 1;
+    }
+    freq[EOF_SYMBOL as usize] = 1;
+    freq
+}
+
+// 在内存中编码，输出格式与 huffman_encode_file 相同（魔数 HFMN + 频率表 + 比特流）。
+pub fn huffman_encode(input: &[u8]) -> Vec<u8> {
+    let freq = build_frequencies(input);
+    let mut out = Vec::with_capacity(8 + 4 * SYMBOL_LIMIT + input.len() / 2);
+    out.extend_from_slice(b"HFMN");
+    // 频率表由输入统计而来，每个字节都有码字；写入 Vec 也不会出现 I/O 错误。
+    write_frequencies(&mut out, &freq).expect("huffman: writing to Vec failed");
+    encode_stream(&mut &input[..], &mut out, &freq).expect("huffman: in-memory encode failed");
+    out
+}
+
+// 解码 huffman_encode / huffman_encode_file 的输出。
+pub fn huffman_decode(input: &[u8]) -> Result<Vec<u8>, Error> {
+    huffman_decode_with_limit(input, usize::MAX)
+}
+
+// 与 huffman_decode 相同，但输出将超过 max_output 字节时立即返回
+// "huffman: output limit exceeded"，用于解码不可信的输入。
+pub fn huffman_decode_with_limit(input: &[u8], max_output: usize) -> Result<Vec<u8>, Error> {
+    let mut reader = input;
+    read_magic(&mut reader, b"HFMN")?;
+    let freq = read_frequencies(&mut reader)?;
+    let mut out = Vec::with_capacity(input.len().saturating_mul(2).min(max_output));
+    decode_stream(reader, &mut out, &freq, max_output as u64)?;
+    Ok(out)
+}
+
+// 把写入的字节与 expected 逐段比较，不保存输出；第一次不一致时让写入失败，使解码提前停止。
+struct VerifyWriter<'a> {
+    expected: &'a [u8],
+    mismatch: bool,
+}
+
+impl Write for VerifyWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.expected.strip_prefix(buf) {
+            Some(rest) => {
+                self.expected = rest;
+                Ok(buf.len())
+            }
+            None => {
+                self.mismatch = true;
+                Err(io::Error::other("huffman: verify mismatch"))
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// huffman_encode 之后立即解码并与 input 逐段比较，第一个不一致处即返回 Ok(false)，
+// 不分配完整的输出；用于模糊测试。只有数据流本身无法解码时才返回 Err。
+pub fn huffman_verify(input: &[u8]) -> Result<bool, Error> {
+    let encoded = huffman_encode(input);
+    verify_encoded(&encoded, input)
+}
+
+fn verify_encoded(encoded: &[u8], input: &[u8]) -> Result<bool, Error> {
+    let mut reader = encoded;
+    read_magic(&mut reader, b"HFMN")?;
+    let freq = read_frequencies(&mut reader)?;
+    let mut writer = VerifyWriter {
+        expected: input,
+        mismatch: false,
+    };
+    let result = decode_stream(reader, &mut writer, &freq, input.len() as u64 + 1);
+    if writer.mismatch {
+        return Ok(false);
+    }
+    match result {
+        Ok(()) => Ok(writer.expected.is_empty()),
+        // 输出比 input 长。
+        Err(Error::LimitExceeded(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+// 带完整性校验的 HFMN 变体：魔数 HFMC + 频率表 + 4 字节小端原始数据 CRC32 + 比特流。
+// 截断或比特翻转的数据流可能被解码成内容错误但形式合法的输出，CRC 让解码端能发现这类损坏。
+// HFMN 仍是文件接口与其他语言实现共用的默认格式。
+const CHECKED_MAGIC: &[u8; 4] = b"HFMC";
+
+pub fn huffman_encode_checked(input: &[u8]) -> Vec<u8> {
+    let freq = build_frequencies(input);
+    let mut out = Vec::with_capacity(12 + 4 * SYMBOL_LIMIT + input.len() / 2);
+    out.extend_from_slice(CHECKED_MAGIC);
+    // 同 huffman_encode：写入 Vec 不会失败，每个字节都有码字。
+    write_frequencies(&mut out, &freq).expect("huffman: writing to Vec failed");
+    out.extend_from_slice(&encoding_common::checksum::crc32(input).to_le_bytes());
+    encode_stream(&mut &input[..], &mut out, &freq).expect("huffman: in-memory encode failed");
+    out
+}
+
+// 解码 huffman_encode_checked 的输出；输出的 CRC32 与头部不符时返回 ChecksumMismatch。
+pub fn huffman_decode_checked(input: &[u8]) -> Result<Vec<u8>, Error> {
+    huffman_decode_checked_with_limit(input, usize::MAX)
+}
+
+// 与 huffman_decode_checked 相同，输出超过 max_output 字节时返回 LimitExceeded。
+pub fn huffman_decode_checked_with_limit(
+    input: &[u8],
+    max_output: usize,
+) -> Result<Vec<u8>, Error> {
+    let mut reader = input;
+    read_magic(&mut reader, CHECKED_MAGIC)?;
+    let freq = read_frequencies(&mut reader)?;
+    let mut crc = [0u8; 4];
+    reader
+        .read_exact(&mut crc)
+        .map_err(|e| Error::from_read(e, "huffman: truncated header"))?;
+    let mut out = Vec::with_capacity(input.len().saturating_mul(2).min(max_output));
+    decode_stream(reader, &mut out, &freq, max_output as u64)?;
+    if encoding_common::checksum::crc32(&out) != u32::from_le_bytes(crc) {
+        return Err(Error::ChecksumMismatch("huffman: checksum mismatch"));
+    }
+    Ok(out)
+}
+
+fn compress_file(input_path: &str, output_path: &str) -> Result<(), Error> {
+    let data = read_input(input_path)?;
+    write_output(output_path, &huffman_encode(&data))?;
+    Ok(())
+}
+
+// 与 compress_file 输出相同，但通过内存映射读取输入：频率统计与编码都直接遍历映射的切片，
+// 省去两次缓冲读取。无法映射时（空文件、特殊文件或平台不支持）回退到 compress_file。
+#[cfg(feature = "mmap")]
+pub fn compress_file_mmap(input_path: &str, output_path: &str) -> Result<(), Error> {
+    let file = File::open(input_path)?;
+    // SAFETY: 映射只读使用；若其他进程在压缩期间修改该文件，结果未定义，调用方需保证输入文件不被并发修改。
+    let map = match unsafe { memmap2::Mmap::map(&file) } {
+        Ok(map) => map,
+        Err(_) => return compress_file(input_path, output_path),
+    };
+    let data: &[u8] = &map;
+    let freq = build_frequencies(data);
+
+    let mut writer = create_output(output_path)?;
+    writer.write_all(b"HFMN")?;
+    write_frequencies(&mut writer, &freq)?;
+    encode_stream(&mut &data[..], writer, &freq)
+}
+
+fn decompress_file(
+    input_path: &str,
+    output_path: &str,
+    limits: &DecodeLimits,
+) -> Result<(), Error> {
+    let reader = open_input(input_path)?;
+    let mut writer = create_output(output_path)?;
+    huffman_decode_reader(reader, &mut writer, limits)
+}
+
+// 从任意 reader 解码 huffman_encode_file 格式的数据流并写入 writer，limits 的含义同
+// huffman_decode_file_with_limits；用于内存缓冲区、网络流等不经过文件的场景。
+pub fn huffman_decode_reader<R: Read, W: Write>(
+    mut reader: R,
+    writer: &mut W,
+    limits: &DecodeLimits,
+) -> Result<(), Error> {
+    read_magic(&mut reader, b"HFMN")?;
+    let freq = read_frequencies(&mut reader)?;
+    let max_output = limits
+        .output_budget(tree_bytes(&freq))
+        .ok_or(Error::LimitExceeded("huffman: memory limit exceeded"))?;
+    decode_stream(reader, writer, &freq, max_output)
+}
+
+// 解码树占用的字节数（频率表与 n 个叶子、n - 1 个内部节点），计入 DecodeLimits。
+fn tree_bytes(freq: &[u32]) -> u64 {
+    let leaves = freq.iter().filter(|&&f| f != 0).count().max(1);
+    (std::mem::size_of_val(freq) + 2 * leaves * std::mem::size_of::<Node>()) as u64
+}
+
+// 扫描语料文件生成可复用的模型文件（魔数 HFMD + 频率表）。
+// 语料中未出现的符号频率记为 1，保证任意输入都能用该模型编码。
+pub fn build_model_file(corpus_path: &str, model_path: &str) -> Result<(), Error> {
+    let mut freq = build_frequencies_from_file(corpus_path);
+    for f in freq.iter_mut() {
+        if *f == 0 {
+            *f = 1;
+        }
+    }
+    let mut writer = create_output(model_path)?;
+    writer.write_all(MODEL_MAGIC)?;
+    write_frequencies(&mut writer, &freq)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn read_model_file(model_path: &str) -> Result<Vec<u32>, Error> {
+    let mut reader = open_input(model_path)?;
+    read_magic(&mut reader, MODEL_MAGIC)?;
+    read_frequencies(&mut reader)
+}
+
+// 使用外部模型文件压缩：输出为魔数 HFMN + 1 字节外部模型标志 + 编码比特流，不内嵌频率表。
+pub fn compress_file_with_model_file(
+    input_path: &str,
+    output_path: &str,
+    model_path: &str,
+) -> Result<(), Error> {
+    let freq = read_model_file(model_path)?;
+    let mut reader = open_input(input_path)?;
+    let mut writer = create_output(output_path)?;
+
+    writer.write_all(b"HFMN")?;
+    writer.write_all(&[MODEL_EXTERNAL])?;
+    encode_stream(&mut reader, writer, &freq)
+}
+
+// 解压 compress_file_with_model_file 的输出，需使用与压缩时相同的模型文件。
+pub fn decompress_file_with_model_file(
+    input_path: &str,
+    output_path: &str,
+    model_path: &str,
+) -> Result<(), Error> {
+    decompress_file_with_model_file_with_limits(
+        input_path,
+        output_path,
+        model_path,
+        &DecodeLimits::unlimited(),
+    )
+}
+
+// 在 limits 约束下解压：解码树与输出合计超过上限时返回 LimitExceeded。
+pub fn decompress_file_with_model_file_with_limits(
+    input_path: &str,
+    output_path: &str,
+    model_path: &str,
+    limits: &DecodeLimits,
+) -> Result<(), Error> {
+    let freq = read_model_file(model_path)?;
+    let max_output = limits
+        .output_budget(tree_bytes(&freq))
+        .ok_or(Error::LimitExceeded("huffman: memory limit exceeded"))?;
+    let mut reader = open_input(input_path)?;
+    read_magic(&mut reader, b"HFMN")?;
+    let mut flag = [0u8; 1];
+    reader
+        .read_exact(&mut flag)
+        .map_err(|e| Error::from_read(e, "huffman: truncated header"))?;
+    if flag[0] != MODEL_EXTERNAL {
+        return Err(Error::BadMagic("huffman: not an external-model stream"));
+    }
+
+    let mut writer = create_output(output_path)?;
+    decode_stream(reader, &mut writer, &freq, max_output)
+}
+
+// 周期重建格式：魔数 HFMP + 4 字节小端 rebuild_interval + 比特流。
+// 比特流由若干块组成，每块先写一份码长表（见 write_code_lengths），
+// 随后是按这些码长分配的 canonical 码字编码的最多 rebuild_interval 个字节；
+// 不足 rebuild_interval 字节的块是最后一块，以 EOF 符号结束。
+const PERIODIC_MAGIC: &[u8; 4] = b"HFMP";
+const LENGTH_BITS: u8 = 6;
+const SYMBOL_BITS: u8 = 9;
+
+// 码长表以 1 位选择符开头：
+// 0 = 稠密，SYMBOL_LIMIT 个 6 位码长（0 表示该符号不出现）；
+// 1 = 稀疏，9 位出现符号数 n，随后 n 个 (9 位符号, 6 位码长)，符号严格递增。
+// 两种表示取较小者，字母表很小时稀疏表示远短于稠密表示。
+fn write_code_lengths<W: Write>(bit_writer: &mut BitWriter<W>, lengths: &[u8]) -> io::Result<()> {
+    let present = lengths.iter().filter(|&&len| len > 0).count();
+    let dense_bits = SYMBOL_LIMIT * LENGTH_BITS as usize;
+    let sparse_bits = SYMBOL_BITS as usize + present * (SYMBOL_BITS + LENGTH_BITS) as usize;
+    if sparse_bits < dense_bits {
+        bit_writer.write_bits(1, 1)?;
+        bit_writer.write_bits(present as u64, SYMBOL_BITS)?;
+        for (symbol, &len) in lengths.iter().enumerate().filter(|(_, &len)| len > 0) {
+            bit_writer.write_bits(symbol as u64, SYMBOL_BITS)?;
+            bit_writer.write_bits(len as u64, LENGTH_BITS)?;
+        }
+    } else {
+        bit_writer.write_bits(0, 1)?;
+        for &len in lengths {
+            bit_writer.write_bits(len as u64, LENGTH_BITS)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_code_lengths<R: Read>(bit_reader: &mut BitReader<R>) -> Result<Vec<u8>, Error> {
+    let mut lengths = vec![0u8; SYMBOL_LIMIT];
+    if bit_reader.read_bits(1) == 0 {
+        for len in lengths.iter_mut() {
+            *len = bit_reader.read_bits(LENGTH_BITS) as u8;
+        }
+    } else {
+        let present = bit_reader.read_bits(SYMBOL_BITS) as usize;
+        let mut next = 0;
+        for _ in 0..present {
+            let symbol = bit_reader.read_bits(SYMBOL_BITS) as usize;
+            if symbol < next || symbol >= SYMBOL_LIMIT {
+                return Err(Error::InvalidModel("huffman: bad code lengths"));
+            }
+            lengths[symbol] = bit_reader.read_bits(LENGTH_BITS) as u8;
+            next = symbol + 1;
+        }
+    }
+    if bit_reader.eof() {
+        return Err(Error::Truncated("huffman: truncated code lengths"));
+    }
+    Ok(lengths)
+}
+
+// 介于静态与完全自适应之间：每 rebuild_interval 个字节按该块的统计重建一次哈夫曼码，
+// 并把新的码长写入流中，解码端读到码长后同步重建，无需逐符号更新树。
+pub fn compress_file_periodic(
+    input_path: &str,
+    output_path: &str,
+    rebuild_interval: u32,
+) -> Result<(), Error> {
+    if rebuild_interval == 0 {
+        return Err(Error::InvalidInput(
+            "huffman: rebuild_interval must be positive",
+        ));
+    }
+    let mut reader = open_input(input_path)?;
+    let mut writer = create_output(output_path)?;
+    writer.write_all(PERIODIC_MAGIC)?;
+    writer.write_all(&rebuild_interval.to_le_bytes())?;
+
+    let mut bit_writer = BitWriter::new(writer);
+    let mut block = Vec::new();
+    loop {
+        block.clear();
+        (&mut reader)
+            .take(rebuild_interval as u64)
+            .read_to_end(&mut block)?;
+        let last = block.len() < rebuild_interval as usize;
+
+        let mut freq = vec![0u32; SYMBOL_LIMIT];
+        for &b in &block {
+            freq[b as usize] += 1;
+        }
+        freq[EOF_SYMBOL as usize] = 1;
+        let lengths = build_code_lengths(&freq);
+        write_code_lengths(&mut bit_writer, &lengths)?;
+
+        let codes = build_canonical_codes(&lengths);
+        for &b in &block {
+            let (code, len) = codes[b as usize];
+            bit_writer.write_bits(code, len)?;
+        }
+        if last {
+            let (code, len) = codes[EOF_SYMBOL as usize];
+            bit_writer.write_bits(code, len)?;
+            break;
+        }
+    }
+    bit_writer.flush()?;
+    Ok(())
+}
+
+// 读取一块的码长并构建解码表。
+fn read_block_table<R: Read>(bit_reader: &mut BitReader<R>) -> Result<DecodeTable, Error> {
+    let lengths = read_code_lengths(bit_reader)?;
+    checked_decode_table(&lengths)
+}
+
+// canonical 码字在 u64 中逐长度左移分配，首个码长为 64 时移位溢出，码长上限为 63。
+// 编码端由 32 位频率构建的树远达不到这个深度。
+const MAX_CODE_LEN: u8 = 63;
+
+// 由数据流中读出的码长构建解码表；除 build_decode_table 的检查外还必须包含 EOF。
+fn checked_decode_table(lengths: &[u8]) -> Result<DecodeTable, Error> {
+    if lengths.get(EOF_SYMBOL as usize).is_none_or(|&len| len == 0) {
+        return Err(Error::InvalidModel("huffman: bad code lengths"));
+    }
+    build_decode_table(lengths, 10)
+}
+
+// 解码表占用的字节数，计入 DecodeLimits。
+fn decode_table_bytes(table: &DecodeTable) -> u64 {
+    (std::mem::size_of_val(table.entries.as_slice())
+        + std::mem::size_of_val(table.first_code.as_slice())
+        + std::mem::size_of_val(table.counts.as_slice())
+        + std::mem::size_of_val(table.first_index.as_slice())
+        + std::mem::size_of_val(table.symbols.as_slice())) as u64
+}
+
+pub fn decompress_file_periodic(input_path: &str, output_path: &str) -> Result<(), Error> {
+    decompress_file_periodic_with_limits(input_path, output_path, &DecodeLimits::unlimited())
+}
+
+// 在 limits 约束下解压：当前块的解码表与已写出的输出合计超过上限时返回 LimitExceeded。
+pub fn decompress_file_periodic_with_limits(
+    input_path: &str,
+    output_path: &str,
+    limits: &DecodeLimits,
+) -> Result<(), Error> {
+    let mut reader = open_input(input_path)?;
+    read_magic(&mut reader, PERIODIC_MAGIC)?;
+    let mut interval = [0u8; 4];
+    reader
+        .read_exact(&mut interval)
+        .map_err(|e| Error::from_read(e, "huffman: truncated header"))?;
+    let interval = u32::from_le_bytes(interval);
+    if interval == 0 {
+        return Err(Error::InvalidModel("huffman: bad rebuild_interval"));
+    }
+
+    let mut writer = create_output(output_path)?;
+    let mut bit_reader = BitReader::new(reader);
+    let mut written: u64 = 0;
+    'blocks: loop {
+        let table = read_block_table(&mut bit_reader)?;
+        let max_output = limits
+            .output_budget(decode_table_bytes(&table))
+            .ok_or(Error::LimitExceeded("huffman: memory limit exceeded"))?;
+        for _ in 0..interval {
+            let symbol = read_canonical_symbol(&mut bit_reader, &table)?;
+            if symbol as u32 == EOF_SYMBOL {
+                break 'blocks;
+            }
+            if written >= max_output {
+                return Err(Error::LimitExceeded("huffman: output limit exceeded"));
+            }
+            writer.write_all(&[symbol as u8])?;
+            written += 1;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// 逐比特读取一个 canonical 码字。
+fn read_canonical_symbol<R: Read>(
+    bit_reader: &mut BitReader<R>,
+    table: &DecodeTable,
+) -> Result<u16, Error> {
+    let mut code = 0u64;
+    let mut n = 0u8;
+    loop {
+        code = (code << 1) | bit_reader.read_bit() as u64;
+        n += 1;
+        if bit_reader.eof() {
+            return Err(Error::Truncated("huffman: truncated bitstream"));
+        }
+        if let Some((symbol, _)) = table.decode(code, n) {
+            return Ok(symbol);
+        }
+        if n >= table.max_len() {
+            return Err(Error::InvalidData("huffman: invalid code"));
+        }
+    }
+}
+
+// 码长表经区间编码压缩的格式：魔数 HFMR + 4 字节小端压缩码长表长度 + 压缩码长表 + 比特流。
+// 码长表是 SYMBOL_LIMIT 个取值很小的整数，用 rangecoder 的自适应 order-0 模型（RCNA）编码，
+// 通常只需几十到一百多字节，而 HFMN 的频率表固定为 1032 字节。数据按 canonical 码字编码，以 EOF 结束。
+const PACKED_MAGIC: &[u8; 4] = b"HFMR";
+// 压缩码长表的长度上限，远大于 SYMBOL_LIMIT 个码长的最坏情况。
+const MAX_PACKED_LENGTHS: u32 = 4096;
+
+fn pack_code_lengths(lengths: &[u8]) -> Result<Vec<u8>, Error> {
+    rangecoder::encode_adaptive(lengths, rangecoder::AdaptiveModel::new())
+}
+
+fn unpack_code_lengths(packed: &[u8]) -> Result<Vec<u8>, Error> {
+    let lengths = rangecoder::decode_adaptive(packed, rangecoder::AdaptiveModel::new())
+        .map_err(|_| Error::InvalidModel("huffman: bad packed code lengths"))?;
+    if lengths.len() != SYMBOL_LIMIT {
+        return Err(Error::InvalidModel("huffman: bad packed code lengths"));
+    }
+    Ok(lengths)
+}
+
+// 与 huffman_encode_file 相同的哈夫曼码长，但头部只保存区间编码后的码长表，中等大小的文件上头部明显更小。
+pub fn compress_file_packed(input_path: &str, output_path: &str) -> Result<(), Error> {
+    let freq = build_frequencies_from_file(input_path);
+    let lengths = build_code_lengths(&freq);
+    let packed = pack_code_lengths(&lengths)?;
+
+    let mut reader = open_input(input_path)?;
+    let mut writer = create_output(output_path)?;
+    writer.write_all(PACKED_MAGIC)?;
+    writer.write_all(&(packed.len() as u32).to_le_bytes())?;
+    writer.write_all(&packed)?;
+
+    encode_canonical_body(&mut reader, writer, &lengths)
+}
+
+pub fn decompress_file_packed(input_path: &str, output_path: &str) -> Result<(), Error> {
+    decompress_file_packed_with_limits(input_path, output_path, &DecodeLimits::unlimited())
+}
+
+// 在 limits 约束下解压：解码表与输出合计超过上限时返回 LimitExceeded。
+pub fn decompress_file_packed_with_limits(
+    input_path: &str,
+    output_path: &str,
+    limits: &DecodeLimits,
+) -> Result<(), Error> {
+    let mut reader = open_input(input_path)?;
+    read_magic(&mut reader, PACKED_MAGIC)?;
+    let mut len = [0u8; 4];
+    reader
+        .read_exact(&mut len)
+        .map_err(|e| Error::from_read(e, "huffman: truncated header"))?;
+    let len = u32::from_le_bytes(len);
+    if len > MAX_PACKED_LENGTHS {
+        return Err(Error::InvalidModel("huffman: bad packed code lengths"));
+    }
+    let mut packed = vec![0u8; len as usize];
+    reader
+        .read_exact(&mut packed)
+        .map_err(|e| Error::from_read(e, "huffman: truncated header"))?;
+    let lengths = unpack_code_lengths(&packed)?;
+    let table = checked_decode_table(&lengths)?;
+    let max_output = limits
+        .output_budget(decode_table_bytes(&table))
+        .ok_or(Error::LimitExceeded("huffman: memory limit exceeded"))?;
+    decode_canonical_body(reader, &mut create_output(output_path)?, &table, max_output)
+}
+
+// 按 lengths 对应的 canonical 码字编码 reader 中的全部字节及 EOF。
+fn encode_canonical_body<R: Read, W: Write>(
+    reader: &mut R,
+    writer: W,
+    lengths: &[u8],
+) -> Result<(), Error> {
+    let codes = build_canonical_codes(lengths);
+    let mut bit_writer = BitWriter::new(writer);
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        for &b in &buf[..n] {
+            let (code, len) = codes[b as usize];
+            bit_writer.write_bits(code, len)?;
+        }
+    }
+    let (code, len) = codes[EOF_SYMBOL as usize];
+    bit_writer.write_bits(code, len)?;
+    bit_writer.flush()?;
+    Ok(())
+}
+
+// 逐个解码 canonical 码字直到 EOF；解码出的字节数超过 max_output 时返回 LimitExceeded。
+fn decode_canonical_body<R: Read, W: Write>(
+    reader: R,
+    writer: &mut W,
+    table: &DecodeTable,
+    max_output: u64,
+) -> Result<(), Error> {
+    let mut bit_reader = BitReader::new(reader);
+    let mut written: u64 = 0;
+    loop {
+        let symbol = read_canonical_symbol(&mut bit_reader, table)?;
+        if symbol as u32 == EOF_SYMBOL {
+            break;
+        }
+        if written == max_output {
+            return Err(Error::LimitExceeded("huffman: output limit exceeded"));
+        }
+        writer.write_all(&[symbol as u8])?;
+        written += 1;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// canonical 码长头部格式：魔数 HFM2 + SYMBOL_LIMIT 个 1 字节码长（0 表示未出现）+ 比特流。
+// 码字只由码长决定（按 (码长, 符号) 升序分配，见 build_canonical_codes），
+// 头部固定 261 字节，而 HFMN 的频率表为 1032 字节。HFMN 数据流交给 HFM2 解码器时返回 BadMagic。
+const CANONICAL_MAGIC: &[u8; 4] = b"HFM2";
+
+// 与 huffman_encode 使用相同的码长，但头部只保存码长表。
+pub fn huffman_encode_canonical(input: &[u8]) -> Vec<u8> {
+    let lengths = build_code_lengths(&build_frequencies(input));
+    let mut out = Vec::with_capacity(4 + SYMBOL_LIMIT + input.len() / 2);
+    out.extend_from_slice(CANONICAL_MAGIC);
+    out.extend_from_slice(&lengths[..SYMBOL_LIMIT]);
+    // 写入 Vec 不会失败。
+    encode_canonical_body(&mut &input[..], &mut out, &lengths)
+        .expect("huffman: in-memory encode failed");
+    out
+}
+
+pub fn huffman_decode_canonical(input: &[u8]) -> Result<Vec<u8>, Error> {
+    huffman_decode_canonical_with_limit(input, usize::MAX)
+}
+
+// 与 huffman_decode_canonical 相同，输出超过 max_output 字节时返回 LimitExceeded。
+pub fn huffman_decode_canonical_with_limit(
+    input: &[u8],
+    max_output: usize,
+) -> Result<Vec<u8>, Error> {
+    let mut reader = input;
+    read_magic(&mut reader, CANONICAL_MAGIC)?;
+    let mut lengths = vec![0u8; SYMBOL_LIMIT];
+    reader
+        .read_exact(&mut lengths)
+        .map_err(|e| Error::from_read(e, "huffman: truncated code lengths"))?;
+    let table = checked_decode_table(&lengths)?;
+    let mut out = Vec::with_capacity(input.len().saturating_mul(2).min(max_output));
+    decode_canonical_body(reader, &mut out, &table, max_output as u64)?;
+    Ok(out)
+}
+
+// 自适应 Huffman（FGK）：单遍编码，不需要预先统计频率，也不存储频率表。
+// 格式：魔数 HFMA + 比特流。编解码两端从只有一个 NYT（尚未出现）结点的树开始，
+// 每个符号之后按相同规则更新树；首次出现的符号写成 NYT 的码字 + 9 位原始符号值，
+// 以同样方式写出的 EOF 符号结束数据流。适合一次只能看到一部分数据的流式场景。
+const ADAPTIVE_MAGIC: &[u8; 4] = b"HFMA";
+const NO_NODE: usize = usize::MAX;
+
+struct AdaptiveNode {
+    weight: u64,
+    parent: usize,
+    // 叶结点的 left/right 为 NO_NODE。
+    left: usize,
+    right: usize,
+    symbol: u32,
+}
+
+// 结点按编号从大到小存放：下标 0 为根，下标越大编号越小，权重随下标单调不增（兄弟性质），
+// 同一权重块的块首是块内下标最小的结点。NYT 总是最后一个结点。
+struct AdaptiveTree {
+    nodes: Vec<AdaptiveNode>,
+    leaf_of: Vec<usize>,
+    nyt: usize,
+}
+
+impl AdaptiveTree {
+    fn new() -> Self {
+        AdaptiveTree {
+            nodes: vec![AdaptiveNode {
+                weight: 0,
+                parent: NO_NODE,
+                left: NO_NODE,
+                right: NO_NODE,
+                symbol: 0,
+            }],
+            leaf_of: vec![NO_NODE; SYMBOL_LIMIT],
+            nyt: 0,
+        }
+    }
+
+    // 从根到 node 的码字，逐位写出（树深可能超过 64）。
+    fn write_path<W: Write>(&self, bit_writer: &mut BitWriter<W>, node: usize) -> io::Result<()> {
+        let mut bits = Vec::new();
+        let mut current = node;
+        while self.nodes[current].parent != NO_NODE {
+            let parent = self.nodes[current].parent;
+            bits.push((self.nodes[parent].right == current) as u64);
+            current = parent;
+        }
+        for &bit in bits.iter().rev() {
+            bit_writer.write_bits(bit, 1)?;
+        }
+        Ok(())
+    }
+
+    fn encode<W: Write>(&mut self, bit_writer: &mut BitWriter<W>, symbol: u32) -> io::Result<()> {
+        match self.leaf_of[symbol as usize] {
+            NO_NODE => {
+                self.write_path(bit_writer, self.nyt)?;
+                bit_writer.write_bits(symbol as u64, SYMBOL_BITS)?;
+            }
+            leaf => self.write_path(bit_writer, leaf)?,
+        }
+        if symbol != EOF_SYMBOL {
+            self.update(symbol);
+        }
+        Ok(())
+    }
+
+    // 读出一个符号并更新树；EOF 不更新。
+    fn decode<R: Read>(&mut self, bit_reader: &mut BitReader<R>) -> Result<u32, Error> {
+        let mut node = 0;
+        while self.nodes[node].left != NO_NODE {
+            node = if bit_reader.read_bit() == 0 {
+                self.nodes[node].left
+            } else {
+                self.nodes[node].right
+            };
+        }
+        let symbol = if node == self.nyt {
+            let symbol = bit_reader.read_bits(SYMBOL_BITS) as u32;
+            if symbol > EOF_SYMBOL || self.leaf_of[symbol as usize] != NO_NODE {
+                return Err(Error::InvalidData("huffman: invalid symbol"));
+            }
+            symbol
+        } else {
+            self.nodes[node].symbol
+        };
+        if bit_reader.eof() {
+            return Err(Error::Truncated("huffman: truncated bitstream"));
+        }
+        if symbol != EOF_SYMBOL {
+            self.update(symbol);
+        }
+        Ok(symbol)
+    }
+
+    fn update(&mut self, symbol: u32) {
+        let mut node = self.leaf_of[symbol as usize];
+        if node == NO_NODE {
+            // NYT 分裂为内部结点，右孩子为新符号的叶，左孩子为新的 NYT；两者编号最小，
+            // 新叶与原 NYT 权重加 1 后兄弟性质仍成立，从原 NYT 的父结点继续更新。
+            let old = self.nyt;
+            let (leaf, nyt) = (self.nodes.len(), self.nodes.len() + 1);
+            for (symbol, weight) in [(symbol, 1), (0, 0)] {
+                self.nodes.push(AdaptiveNode {
+                    weight,
+                    parent: old,
+                    left: NO_NODE,
+                    right: NO_NODE,
+                    symbol,
+                });
+            }
+            self.nodes[old].left = nyt;
+            self.nodes[old].right = leaf;
+            self.nodes[old].weight += 1;
+            self.leaf_of[symbol as usize] = leaf;
+            self.nyt = nyt;
+            node = self.nodes[old].parent;
+        }
+        while node != NO_NODE {
+            let weight = self.nodes[node].weight;
+            let mut leader = node;
+            while leader > 0 && self.nodes[leader - 1].weight == weight {
+                leader -= 1;
+            }
+            if leader != node && leader != self.nodes[node].parent {
+                self.swap(node, leader);
+                node = leader;
+            }
+            self.nodes[node].weight += 1;
+            node = self.nodes[node].parent;
+        }
+    }
+
+    // 交换两个位置上的子树；parent 属于位置本身，保持不变。
+    fn swap(&mut self, a: usize, b: usize) {
+        let (na, nb) = (&self.nodes[a], &self.nodes[b]);
+        let (ea, eb) = (
+            (na.weight, na.left, na.right, na.symbol),
+            (nb.weight, nb.left, nb.right, nb.symbol),
+        );
+        for (position, (weight, left, right, symbol)) in [(a, eb), (b, ea)] {
+            let n = &mut self.nodes[position];
+            (n.weight, n.left, n.right, n.symbol) = (weight, left, right, symbol);
+            if left == NO_NODE {
+                self.leaf_of[symbol as usize] = position;
+            } else {
+                self.nodes[left].parent = position;
+                self.nodes[right].parent = position;
+            }
+        }
+    }
+}
+
+pub fn huffman_encode_adaptive(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(8 + input.len());
+    out.extend_from_slice(ADAPTIVE_MAGIC);
+    let mut tree = AdaptiveTree::new();
+    let mut bit_writer = BitWriter::new(&mut out);
+    // 写入 Vec 不会失败。
+    for &b in input.iter() {
+        tree.encode(&mut bit_writer, b as u32)
+            .expect("huffman: writing to Vec failed");
+    }
+    tree.encode(&mut bit_writer, EOF_SYMBOL)
+        .and_then(|_| bit_writer.flush())
+        .expect("huffman: writing to Vec failed");
+    out
+}
+
+pub fn huffman_decode_adaptive(input: &[u8]) -> Result<Vec<u8>, Error> {
+    huffman_decode_adaptive_with_limit(input, usize::MAX)
+}
+
+// 与 huffman_decode_adaptive 相同，输出超过 max_output 字节时返回 LimitExceeded。
+pub fn huffman_decode_adaptive_with_limit(
+    input: &[u8],
+    max_output: usize,
+) -> Result<Vec<u8>, Error> {
+    let mut reader = input;
+    read_magic(&mut reader, ADAPTIVE_MAGIC)?;
+    let mut tree = AdaptiveTree::new();
+    let mut bit_reader = BitReader::new(reader);
+    let mut out = Vec::with_capacity(input.len().saturating_mul(2).min(max_output));
+    loop {
+        match tree.decode(&mut bit_reader)? {
+            EOF_SYMBOL => return Ok(out),
+            _ if out.len() == max_output => {
+                return Err(Error::LimitExceeded("huffman: output limit exceeded"))
+            }
+            symbol => out.push(symbol as u8),
+        }
+    }
+}
+
+pub fn huffman_encode_file(input_path: &str, output_path: &str) -> Result<(), Error> {
+    compress_file(input_path, output_path)
+}
+
+pub fn huffman_decode_file(input_path: &str, output_path: &str) -> Result<(), Error> {
+    let data = read_input(input_path)?;
+    write_output(output_path, &huffman_decode(&data)?)?;
+    Ok(())
+}
+
+// 在 limits 约束下解码：解码树与输出合计超过上限时返回 LimitExceeded。
+pub fn huffman_decode_file_with_limits(
+    input_path: &str,
+    output_path: &str,
+    limits: &DecodeLimits,
+) -> Result<(), Error> {
+    decompress_file(input_path, output_path, limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_common::test_util::assert_bytes_eq;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("huffman_test_{}_{name}", process::id()))
+    }
+
+    fn path_str(p: &Path) -> &str {
+        p.to_str().unwrap()
+    }
+
+    #[test]
+    fn roundtrip_file() {
+        let input = temp_path("rt.in");
+        let encoded = temp_path("rt.huf");
+        let decoded = temp_path("rt.out");
+        let data: Vec<u8> = (0..5000u32).map(|i| (i * 31 + 7) as u8).collect();
+        fs::write(&input, &data).unwrap();
+        huffman_encode_file(path_str(&input), path_str(&encoded)).unwrap();
+        huffman_decode_file(path_str(&encoded), path_str(&decoded)).unwrap();
+        assert_bytes_eq(&data, &fs::read(&decoded).unwrap());
+        for p in [input, encoded, decoded] {
+            let _ = fs::remove_file(p);
+        }
+    }
+
+    #[test]
+    fn packed_code_lengths_shrink_header() {
+        let data = b"a typical text distribution, with spaces and punctuation. ".repeat(300);
+        let mut freq = vec![0u32; SYMBOL_LIMIT];
+        for &b in &data {
+            freq[b as usize] += 1;
+        }
+        freq[EOF_SYMBOL as usize] = 1;
+        let lengths = build_code_lengths(&freq);
+        let packed = pack_code_lengths(&lengths).unwrap();
+        assert_eq!(unpack_code_lengths(&packed).unwrap(), lengths);
+        assert!(packed.len() < lengths.len(), "{}", packed.len());
+
+        let input = temp_path("packed.in");
+        let encoded = temp_path("packed.huf");
+        let classic = temp_path("packed.classic");
+        let decoded = temp_path("packed.out");
+        for data in [data, Vec::new(), vec![0x42]] {
+            fs::write(&input, &data).unwrap();
+            compress_file_packed(path_str(&input), path_str(&encoded)).unwrap();
+            huffman_encode_file(path_str(&input), path_str(&classic)).unwrap();
+            let packed_len = fs::metadata(&encoded).unwrap().len();
+            let classic_len = fs::metadata(&classic).unwrap().len();
+            assert!(packed_len < classic_len, "{packed_len} vs {classic_len}");
+            decompress_file_packed(path_str(&encoded), path_str(&decoded)).unwrap();
+            assert_bytes_eq(&data, &fs::read(&decoded).unwrap());
+        }
+        for p in [input, encoded, classic, decoded] {
+            let _ = fs::remove_file(p);
+        }
+    }
+
+    #[test]
+    fn file_variants_enforce_limits() {
+        let data = b"limits apply to every file decoder. ".repeat(100);
+        let table = checked_decode_table(&build_code_lengths(&build_frequencies(&data))).unwrap();
+        let exact = DecodeLimits::new(decode_table_bytes(&table) + data.len() as u64);
+        let short = DecodeLimits::new(decode_table_bytes(&table) + data.len() as u64 - 1);
+        let tiny = DecodeLimits::new(100);
+
+        let input = temp_path("limits.in");
+        let encoded = temp_path("limits.huf");
+        let decoded = temp_path("limits.out");
+        let model = temp_path("limits.hfmd");
+        fs::write(&input, &data).unwrap();
+        let (input, encoded, decoded, model_path) = (
+            path_str(&input),
+            path_str(&encoded),
+            path_str(&decoded),
+            path_str(&model),
+        );
+
+        compress_file_packed(input, encoded).unwrap();
+        decompress_file_packed_with_limits(encoded, decoded, &exact).unwrap();
+        assert_bytes_eq(&data, &fs::read(decoded).unwrap());
+        for limits in [&short, &tiny] {
+            let err = decompress_file_packed_with_limits(encoded, decoded, limits).unwrap_err();
+            assert!(matches!(err, Error::LimitExceeded(_)), "{err}");
+        }
+
+        // 间隔大于输入时只有一块，码长与 HFMR 相同。
+        compress_file_periodic(input, encoded, 1 << 20).unwrap();
+        decompress_file_periodic_with_limits(encoded, decoded, &exact).unwrap();
+        assert_bytes_eq(&data, &fs::read(decoded).unwrap());
+        for limits in [&short, &tiny] {
+            let err = decompress_file_periodic_with_limits(encoded, decoded, limits).unwrap_err();
+            assert!(matches!(err, Error::LimitExceeded(_)), "{err}");
+        }
+
+        build_model_file(input, model_path).unwrap();
+        compress_file_with_model_file(input, encoded, model_path).unwrap();
+        let freq = read_model_file(model_path).unwrap();
+        let exact = DecodeLimits::new(tree_bytes(&freq) + data.len() as u64);
+        let short = DecodeLimits::new(tree_bytes(&freq) + data.len() as u64 - 1);
+        decompress_file_with_model_file_with_limits(encoded, decoded, model_path, &exact).unwrap();
+        assert_bytes_eq(&data, &fs::read(decoded).unwrap());
+        for limits in [&short, &tiny] {
+            let err =
+                decompress_file_with_model_file_with_limits(encoded, decoded, model_path, limits)
+                    .unwrap_err();
+            assert!(matches!(err, Error::LimitExceeded(_)), "{err}");
+        }
+
+        for p in [input, encoded, decoded, model_path] {
+            let _ = fs::remove_file(p);
+        }
+    }
+
+    #[test]
+    fn code_lengths_over_63_are_rejected() {
+        // 满足 Kraft 不等式的两个 64 位码字：分配码字时首个左移量为 64。
+        let mut lengths = vec![0u8; SYMBOL_LIMIT];
+        lengths[b'a' as usize] = 64;
+        lengths[EOF_SYMBOL as usize] = 64;
+        assert!(matches!(
+            checked_decode_table(&lengths),
+            Err(Error::InvalidModel(_))
+        ));
+        lengths[b'a' as usize] = 63;
+        lengths[EOF_SYMBOL as usize] = 63;
+        assert_eq!(checked_decode_table(&lengths).unwrap().max_len(), 63);
+
+        lengths[b'a' as usize] = 64;
+        lengths[EOF_SYMBOL as usize] = 64;
+        let packed = pack_code_lengths(&lengths).unwrap();
+        let mut crafted = PACKED_MAGIC.to_vec();
+        crafted.extend_from_slice(&(packed.len() as u32).to_le_bytes());
+        crafted.extend_from_slice(&packed);
+        crafted.extend_from_slice(&[0; 16]);
+        let encoded = temp_path("len64.huf");
+        let decoded = temp_path("len64.out");
+        fs::write(&encoded, &crafted).unwrap();
+        assert!(matches!(
+            decompress_file_packed(path_str(&encoded), path_str(&decoded)),
+            Err(Error::InvalidModel(_))
+        ));
+        for p in [encoded, decoded] {
+            let _ = fs::remove_file(p);
+        }
+    }
+
+    #[test]
+    fn incremental_encoder_flushes_before_finish() {
+        let data: Vec<u8> = b"streaming huffman output over a pipe ".repeat(400);
+        let input = temp_path("stream.in");
+        let encoded = temp_path("stream.huf");
+        fs::write(&input, &data).unwrap();
+        huffman_encode_file(path_str(&input), path_str(&encoded)).unwrap();
+        let expected = fs::read(&encoded).unwrap();
+        let freq = build_frequencies_from_file(path_str(&input));
+        for p in [input, encoded] {
+            let _ = fs::remove_file(p);
+        }
+
+        let (mut pipe_reader, pipe_writer) = io::pipe().unwrap();
+        let mut encoder = HuffmanEncoder::new(pipe_writer, &freq)
+            .unwrap()
+            .with_flush_interval(1024);
+        for &b in &data[..4096] {
+            encoder.push(b).unwrap();
+        }
+        // 尚未 finish，但已推送的整字节已经可以从管道读到。
+        let mut early = vec![0u8; 1 << 16];
+        let n = pipe_reader.read(&mut early).unwrap();
+        assert!(n > 4 + 4 * SYMBOL_LIMIT, "{n}");
+        early.truncate(n);
+
+        for &b in &data[4096..] {
+            encoder.push(b).unwrap();
+        }
+        drop(encoder.finish().unwrap());
+        pipe_reader.read_to_end(&mut early).unwrap();
+        assert_bytes_eq(&expected, &early);
+
+        let mut missing = HuffmanEncoder::new(Vec::new(), &freq).unwrap();
+        assert!(matches!(missing.push(b'#'), Err(Error::InvalidInput(_))));
+        assert!(matches!(
+            HuffmanEncoder::new(Vec::new(), &[1; 3]),
+            Err(Error::InvalidModel(_))
+        ));
+    }
+
+    #[test]
+    fn periodic_rebuild_adapts_to_shifting_statistics() {
+        let input = temp_path("periodic.in");
+        let encoded = temp_path("periodic.huf");
+        let decoded = temp_path("periodic.out");
+
+        // 前半段只含 4 个符号，后半段换成另外 4 个：单棵静态树需要 3 比特/符号，分块重建只需 2 比特。
+        let mut data: Vec<u8> = (0..32768u32)
+            .map(|i| b"abcd"[(i * 7 % 4) as usize])
+            .collect();
+        data.extend((0..32768u32).map(|i| 200 + (i * 5 % 4) as u8));
+        fs::write(&input, &data).unwrap();
+        huffman_encode_file(path_str(&input), path_str(&encoded)).unwrap();
+        let static_len = fs::metadata(&encoded).unwrap().len();
+        compress_file_periodic(path_str(&input), path_str(&encoded), 8192).unwrap();
+        let periodic_len = fs::metadata(&encoded).unwrap().len();
+        assert!(periodic_len < static_len, "{periodic_len} >= {static_len}");
+        decompress_file_periodic(path_str(&encoded), path_str(&decoded)).unwrap();
+        assert_bytes_eq(&data, &fs::read(&decoded).unwrap());
+
+        // 长度恰为块大小整数倍、空输入以及跨越多个块边界的情形。
+        for len in [0usize, 1, 100, 300, 305] {
+            let data: Vec<u8> = (0..len).map(|i| (i * i % 251) as u8).collect();
+            fs::write(&input, &data).unwrap();
+            compress_file_periodic(path_str(&input), path_str(&encoded), 100).unwrap();
+            decompress_file_periodic(path_str(&encoded), path_str(&decoded)).unwrap();
+            assert_eq!(fs::read(&decoded).unwrap(), data, "len {len}");
+        }
+        for p in [input, encoded, decoded] {
+            let _ = fs::remove_file(p);
+        }
+    }
+
+    #[test]
+    fn sparse_code_lengths_smaller_for_small_alphabet() {
+        let data = b"abacabadabacabae".repeat(8);
+        let mut freq = vec![0u32; SYMBOL_LIMIT];
+        for &b in &data {
+            freq[b as usize] += 1;
+        }
+        freq[EOF_SYMBOL as usize] = 1;
+        let lengths = build_code_lengths(&freq);
+
+        let mut sparse = Vec::new();
+        {
+            let mut bw = BitWriter::new(&mut sparse);
+            write_code_lengths(&mut bw, &lengths).unwrap();
+            bw.flush().unwrap();
+        }
+        let dense_bytes = (1 + SYMBOL_LIMIT * LENGTH_BITS as usize).div_ceil(8);
+        assert!(
+            sparse.len() < dense_bytes,
+            "{} >= {dense_bytes}",
+            sparse.len()
+        );
+        let mut br = BitReader::new(sparse.as_slice());
+        assert_eq!(read_code_lengths(&mut br).unwrap(), lengths);
+
+        // 出现符号很多时退回稠密表示，同样能读回。
+        let full = vec![9u8; SYMBOL_LIMIT];
+        let mut dense = Vec::new();
+        {
+            let mut bw = BitWriter::new(&mut dense);
+            write_code_lengths(&mut bw, &full).unwrap();
+            bw.flush().unwrap();
+        }
+        assert_eq!(dense.len(), dense_bytes);
+        let mut br = BitReader::new(dense.as_slice());
+        assert_eq!(read_code_lengths(&mut br).unwrap(), full);
+
+        let input = temp_path("sparse.in");
+        let encoded = temp_path("sparse.huf");
+        let decoded = temp_path("sparse.out");
+        fs::write(&input, &data).unwrap();
+        compress_file_periodic(path_str(&input), path_str(&encoded), 32).unwrap();
+        decompress_file_periodic(path_str(&encoded), path_str(&decoded)).unwrap();
+        assert_bytes_eq(&data, &fs::read(&decoded).unwrap());
+        for p in [input, encoded, decoded] {
+            let _ = fs::remove_file(p);
+        }
+    }
+
+    #[test]
+    fn decode_limits_enforced() {
+        let input = temp_path("limits.in");
+        let encoded = temp_path("limits.huf");
+        let decoded = temp_path("limits.out");
+        let data = b"limited output ".repeat(100);
+        fs::write(&input, &data).unwrap();
+        huffman_encode_file(path_str(&input), path_str(&encoded)).unwrap();
+
+        let mut freq = vec![0u32; SYMBOL_LIMIT];
+        for &b in &data {
+            freq[b as usize] += 1;
+        }
+        freq[EOF_SYMBOL as usize] = 1;
+        let table = tree_bytes(&freq);
+        let decode = |max_memory: u64| {
+            huffman_decode_file_with_limits(
+                path_str(&encoded),
+                path_str(&decoded),
+                &DecodeLimits::new(max_memory),
+            )
+        };
+        decode(table + data.len() as u64).unwrap();
+        assert_bytes_eq(&data, &fs::read(&decoded).unwrap());
+        assert!(matches!(
+            decode(table + data.len() as u64 - 1),
+            Err(Error::LimitExceeded(_))
+        ));
+        assert!(matches!(decode(table - 1), Err(Error::LimitExceeded(_))));
+        for p in [input, encoded, decoded] {
+            let _ = fs::remove_file(p);
+        }
+    }
+
+    #[test]
+    fn error_variants_for_malformed_input() {
+        use std::error::Error as _;
+        let input = temp_path("err.in");
+        let encoded = temp_path("err.huf");
+        let decoded = temp_path("err.out");
+        fs::write(&input, b"error variants").unwrap();
+        huffman_encode_file(path_str(&input), path_str(&encoded)).unwrap();
+        let enc = fs::read(&encoded).unwrap();
+
+        let decode_bytes = |data: &[u8]| {
+            fs::write(&encoded, data).unwrap();
+            huffman_decode_file(path_str(&encoded), path_str(&decoded)).unwrap_err()
+        };
+        assert!(matches!(decode_bytes(b"HF"), Error::Truncated(_)));
+        assert!(matches!(decode_bytes(b"XXXXabcd"), Error::BadMagic(_)));
+        assert!(matches!(decode_bytes(&enc[..100]), Error::Truncated(_)));
+        assert!(matches!(
+            decode_bytes(&enc[..enc.len() - 2]),
+            Error::Truncated(_)
+        ));
+        let mut bad_count = enc.clone();
+        bad_count[4..8].copy_from_slice(&3u32.to_le_bytes());
+        assert!(matches!(decode_bytes(&bad_count), Error::InvalidModel(_)));
+
+        let err = compress_file_periodic(path_str(&input), path_str(&encoded), 0).unwrap_err();
+        assert!(matches!(err, Error::InvalidInput(_)));
+        let err =
+            huffman_encode_file(path_str(&temp_path("missing")), path_str(&encoded)).unwrap_err();
+        assert!(matches!(err, Error::Io(_)), "{err}");
+        assert!(err.source().is_some());
+        for p in [input, encoded, decoded] {
+            let _ = fs::remove_file(p);
+        }
+    }
+
+    // 以 canonical 码字把 data（及 EOF）写成比特流。
+    fn encode_canonical(data: &[u8], codes: &[(u64, u8)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        {
+            let mut bw = BitWriter::new(&mut out);
+            for sym in data
+                .iter()
+                .map(|&b| b as usize)
+                .chain([EOF_SYMBOL as usize])
+            {
+                let (code, len) = codes[sym];
+                bw.write_bits(code, len).unwrap();
+            }
+            bw.flush().unwrap();
+        }
+        out
+    }
+
+    // 由 canonical 码字构建一棵树，逐比特遍历解码。
+    fn decode_tree_walk(stream: &[u8], codes: &[(u64, u8)]) -> Vec<u8> {
+        let mut root = Node {
+            symbol: 0,
+            freq: 0,
+            left: None,
+            right: None,
+        };
+        for (symbol, &(code, len)) in codes.iter().enumerate() {
+            let mut node = &mut root;
+            for i in (0..len).rev() {
+                let child = if (code >> i) & 1 == 0 {
+                    &mut node.left
+                } else {
+                    &mut node.right
+                };
+                node = child.get_or_insert_with(|| {
+                    Box::new(Node {
+                        symbol: 0,
+                        freq: 0,
+                        left: None,
+                        right: None,
+                    })
+                });
+            }
+            node.symbol = symbol as u32;
+        }
+        let mut out = Vec::new();
+        let mut br = BitReader::new(stream);
+        let mut node = &root;
+        loop {
+            node = if br.read_bit() == 0 {
+                node.left.as_ref().unwrap()
+            } else {
+                node.right.as_ref().unwrap()
+            };
+            if is_leaf(node) {
+                if node.symbol == EOF_SYMBOL {
+                    return out;
+                }
+                out.push(node.symbol as u8);
+                node = &root;
+            }
+        }
+    }
+
+    fn decode_with_table(stream: &[u8], table: &DecodeTable) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut bits: u64 = 0;
+        let mut available: u8 = 0;
+        let mut pos = 0;
+        loop {
+            while available <= 56 {
+                let b = stream.get(pos).copied().unwrap_or(0);
+                pos += 1;
+                bits = (bits << 8) | b as u64;
+                available += 8;
+            }
+            let (symbol, len) = table.decode(bits, available).unwrap();
+            available -= len;
+            bits &= (1u64 << available) - 1;
+            if symbol as u32 == EOF_SYMBOL {
+                return out;
+            }
+            out.push(symbol as u8);
+        }
+    }
+
+    #[test]
+    fn decode_table_matches_tree_walk() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+        for seed in 0..20u64 {
+            // 几何分布的偏斜数据，使部分码长超过 root_bits。
+            let data: Vec<u8> = (0..4000u64)
+                .map(|i| {
+                    let mut h = DefaultHasher::new();
+                    (seed, i).hash(&mut h);
+                    (h.finish() | 1 << 40).trailing_zeros() as u8 * 5
+                })
+                .collect();
+            let mut freq = vec![0u32; SYMBOL_LIMIT];
+            for &b in &data {
+                freq[b as usize] += 1;
+            }
+            freq[EOF_SYMBOL as usize] = 1;
+            let lengths = build_code_lengths(&freq);
+            let codes = build_canonical_codes(&lengths);
+            let stream = encode_canonical(&data, &codes);
+
+            let table = build_decode_table(&lengths, 4).unwrap();
+            assert!(table.max_len() > table.root_bits());
+            assert_bytes_eq(&data, &decode_with_table(&stream, &table));
+            assert_bytes_eq(&data, &decode_tree_walk(&stream, &codes));
+        }
+    }
+
+    #[test]
+    fn decode_table_layout() {
+        // 码长 a=1, b=2, c=3, d=3 -> 0, 10, 110, 111
+        let lengths = [1u8, 2, 3, 3];
+        let table = build_decode_table(&lengths, 2).unwrap();
+        let e = table.entries();
+        assert_eq!(
+            e[0b00],
+            DecodeEntry {
+                symbol: 0,
+                length: 1
+            }
+        );
+        assert_eq!(
+            e[0b01],
+            DecodeEntry {
+                symbol: 0,
+                length: 1
+            }
+        );
+        assert_eq!(
+            e[0b10],
+            DecodeEntry {
+                symbol: 1,
+                length: 2
+            }
+        );
+        assert_eq!(e[0b11].length, 0);
+        assert_eq!(table.decode(0b110, 3), Some((2, 3)));
+        assert_eq!(table.decode(0b111, 3), Some((3, 3)));
+        assert_eq!(table.decode(0b11, 2), None);
+
+        // 码长上限 63：最长的两个码字为 62 个 1 后接 0 / 1。
+        let mut lengths: Vec<u8> = (1..=MAX_CODE_LEN).collect();
+        lengths.push(MAX_CODE_LEN);
+        let table = build_decode_table(&lengths, 8).unwrap();
+        assert_eq!(table.max_len(), 63);
+        assert_eq!(table.decode(u64::MAX >> 1, 63), Some((63, 63)));
+        assert_eq!(table.decode((u64::MAX >> 1) - 1, 63), Some((62, 63)));
+    }
+
+    #[test]
+    fn decode_table_rejects_bad_lengths() {
+        // 三个 1 位码字超出码字空间：返回错误而不是在填表时越界。
+        let mut lengths = vec![0u8; SYMBOL_LIMIT];
+        lengths[..3].fill(1);
+        assert!(matches!(
+            build_decode_table(&lengths, 9),
+            Err(Error::InvalidModel(_))
+        ));
+        lengths[2] = 0;
+        assert!(build_decode_table(&lengths, 9).is_ok());
+        lengths[1] = 64;
+        assert!(matches!(
+            build_decode_table(&lengths, 9),
+            Err(Error::InvalidModel(_))
+        ));
+        let mut many = vec![0u8; (1 << 16) + 1];
+        many[1 << 16] = 1;
+        assert!(matches!(
+            build_decode_table(&many, 9),
+            Err(Error::InvalidModel(_))
+        ));
+        for root_bits in [0, 25] {
+            assert!(matches!(
+                build_decode_table(&[1, 1], root_bits),
+                Err(Error::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap_compress_matches_buffered() {
+        let input = temp_path("mmap.in");
+        let buffered = temp_path("mmap.buffered");
+        let mapped = temp_path("mmap.mapped");
+        for data in [Vec::new(), b"mmap input ".repeat(1000)] {
+            fs::write(&input, &data).unwrap();
+            huffman_encode_file(path_str(&input), path_str(&buffered)).unwrap();
+            compress_file_mmap(path_str(&input), path_str(&mapped)).unwrap();
+            assert_eq!(fs::read(&mapped).unwrap(), fs::read(&buffered).unwrap());
+        }
+        for p in [input, buffered, mapped] {
+            let _ = fs::remove_file(p);
+        }
+    }
+
+    #[test]
+    fn external_model_roundtrip() {
+        let corpus = temp_path("model.corpus");
+        let model = temp_path("model.hfmd");
+        fs::write(
+            &corpus,
+            b"the quick brown fox jumps over the lazy dog ".repeat(50),
+        )
+        .unwrap();
+        build_model_file(path_str(&corpus), path_str(&model)).unwrap();
+
+        let inputs: [&[u8]; 2] = [b"the lazy fox", &[0u8, 1, 2, 255, b'q', b'q']];
+        for (i, data) in inputs.iter().enumerate() {
+            let input = temp_path(&format!("model{i}.in"));
+            let encoded = temp_path(&format!("model{i}.huf"));
+            let decoded = temp_path(&format!("model{i}.out"));
+            fs::write(&input, data).unwrap();
+            compress_file_with_model_file(path_str(&input), path_str(&encoded), path_str(&model))
+                .unwrap();
+            let enc = fs::read(&encoded).unwrap();
+            assert_eq!(&enc[..5], &[b'H', b'F', b'M', b'N', MODEL_EXTERNAL]);
+            assert!(enc.len() < 5 + 4 + SYMBOL_LIMIT * 4);
+            decompress_file_with_model_file(
+                path_str(&encoded),
+                path_str(&decoded),
+                path_str(&model),
+            )
+            .unwrap();
+            assert_bytes_eq(data, &fs::read(&decoded).unwrap());
+
+            let err = huffman_decode_file(path_str(&encoded), path_str(&decoded)).unwrap_err();
+            assert!(matches!(err, Error::InvalidModel(_)), "{err}");
+            for p in [input, encoded, decoded] {
+                let _ = fs::remove_file(p);
+            }
+        }
+        let _ = fs::remove_file(corpus);
+        let _ = fs::remove_file(model);
+    }
+
+    #[test]
+    fn slice_api_roundtrip_and_matches_file_api() {
+        let input = temp_path("slice.in");
+        let encoded = temp_path("slice.huf");
+        for data in [&b""[..], b"x", b"\0", &b"slice api ".repeat(400)] {
+            let bytes = huffman_encode(data);
+            assert_bytes_eq(data, &huffman_decode(&bytes).unwrap());
+
+            fs::write(&input, data).unwrap();
+            huffman_encode_file(path_str(&input), path_str(&encoded)).unwrap();
+            assert_bytes_eq(&fs::read(&encoded).unwrap(), &bytes);
+        }
+        let bytes = huffman_encode(b"truncated stream");
+        assert!(huffman_decode(&bytes[..bytes.len() - 3]).is_err());
+        assert!(matches!(huffman_decode(b"HFM"), Err(Error::Truncated(_))));
+        let _ = fs::remove_file(input);
+        let _ = fs::remove_file(encoded);
+    }
+
+    #[test]
+    fn decode_with_limit_stops_long_stream() {
+        // 8000 个 'a' 的码字只有 1 比特，约 1 KiB 的数据体即可展开为 8000 字节。
+        let encoded = huffman_encode(&[b'a'; 8000]);
+        let err = huffman_decode_with_limit(&encoded, 100).unwrap_err();
+        assert_eq!(err.to_string(), "huffman: output limit exceeded");
+        assert_eq!(
+            huffman_decode_with_limit(&encoded, 8000).unwrap().len(),
+            8000
+        );
+    }
+
+    #[test]
+    fn write_bits_handles_every_width() {
+        // 逐位展开作为参照，覆盖跨字节与超过 56 位的写入。
+        let mut expected_bits = Vec::new();
+        let mut bw = BitWriter::new(Vec::new());
+        for (i, count) in (0..=64u8).chain([3, 64, 1, 57]).enumerate() {
+            let value = 0x9E37_79B9_7F4A_7C15u64.rotate_left(i as u32);
+            bw.write_bits(value, count).unwrap();
+            expected_bits.extend((0..count).rev().map(|b| (value >> b) & 1));
+        }
+        bw.flush().unwrap();
+        let mut expected = Vec::new();
+        for chunk in expected_bits.chunks(8) {
+            let byte = chunk.iter().fold(0u8, |acc, &b| (acc << 1) | b as u8);
+            expected.push(byte << (8 - chunk.len()));
+        }
+        assert_bytes_eq(&expected, &bw.writer);
+    }
+
+    #[test]
+    fn table_decoder_matches_bitwise_decoder() {
+        // 码长跨越 FAST_BITS 的模型：斐波那契式的频率让最长码字远超 12 位。
+        let mut skewed = Vec::new();
+        let (mut a, mut b) = (1usize, 1usize);
+        for symbol in 0..24u8 {
+            skewed.extend(std::iter::repeat_n(symbol, a));
+            (a, b) = (b, a + b);
+        }
+        let mut reader = &huffman_encode(&skewed)[4..];
+        let freq = read_frequencies(&mut reader).unwrap();
+        assert!(build_code_table(&build_tree(&freq))
+            .iter()
+            .any(|c| c.1 > FAST_BITS));
+        let random = encoding_common::selftest::pseudo_random(20_000, 0x7AB1E);
+        let decode_both = |body: &[u8], freq: &[u32], max: u64| {
+            let mut fast = Vec::new();
+            let fast_result = decode_stream(body, &mut fast, freq, max).map_err(|e| e.to_string());
+            let mut slow = Vec::new();
+            let slow_result =
+                decode_stream_bitwise(body, &mut slow, freq, max).map_err(|e| e.to_string());
+            assert_eq!(fast_result, slow_result);
+            assert_bytes_eq(&slow, &fast);
+            fast_result.map(|_| fast)
+        };
+        for data in [&b""[..], b"a", b"abracadabra", &skewed, &random] {
+            let encoded = huffman_encode(data);
+            let mut reader = &encoded[4..];
+            let freq = read_frequencies(&mut reader).unwrap();
+            assert_bytes_eq(data, &decode_both(reader, &freq, u64::MAX).unwrap());
+            // 截断、翻转比特与输出上限下两者的结果与已写出的字节也一致。
+            for cut in [0, 1, reader.len() / 2, reader.len().saturating_sub(1)] {
+                let _ = decode_both(&reader[..cut], &freq, u64::MAX);
+            }
+            for flip in (0..reader.len()).step_by(reader.len() / 7 + 1) {
+                let mut corrupt = reader.to_vec();
+                corrupt[flip] ^= 0x24;
+                let _ = decode_both(&corrupt, &freq, u64::MAX);
+            }
+            let _ = decode_both(reader, &freq, data.len() as u64 / 2);
+        }
+        let _ = decode_both(&[0xFF], &[0; SYMBOL_LIMIT], u64::MAX);
+    }
+
+    #[test]
+    fn decoder_stops_exactly_after_eof_symbol() {
+        // 找出编码后比特数恰为 8 的倍数与不是 8 的倍数的输入各一个。
+        let text = b"exact bit positions after the final symbol";
+        let bits_of = |data: &[u8]| {
+            let codes = build_code_table(&build_tree(&build_frequencies(data)));
+            data.iter()
+                .map(|&b| codes[b as usize].1 as u64)
+                .sum::<u64>()
+                + codes[EOF_SYMBOL as usize].1 as u64
+        };
+        let aligned = (1..text.len())
+            .find(|&n| bits_of(&text[..n]) % 8 == 0)
+            .unwrap();
+        let unaligned = (1..text.len())
+            .find(|&n| bits_of(&text[..n]) % 8 != 0)
+            .unwrap();
+        for data in [&text[..aligned], &text[..unaligned]] {
+            let encoded = huffman_encode(data);
+            let mut reader = &encoded[4..];
+            let freq = read_frequencies(&mut reader).unwrap();
+            assert_eq!(reader.len() as u64, bits_of(data).div_ceil(8));
+
+            // 尾随的 0 字节与其他数据不会被读成多余的符号。
+            for trailer in [&[][..], &[0x00], &[0x00, 0xFF, 0x00]] {
+                let mut body = reader.to_vec();
+                body.extend_from_slice(trailer);
+                let mut bit_reader = BitReader::new(&body[..]);
+                let mut out = Vec::new();
+                let consumed = decode_bits(&mut bit_reader, &mut out, &freq, u64::MAX).unwrap();
+                assert_bytes_eq(data, &out);
+                assert_eq!(consumed, bits_of(data));
+                assert!(!bit_reader.eof());
+            }
+            // 少一个字节就是截断。
+            let mut out = Vec::new();
+            let truncated = decode_stream(&reader[..reader.len() - 1], &mut out, &freq, u64::MAX);
+            assert!(
+                matches!(truncated, Err(Error::Truncated(_))),
+                "{truncated:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn checked_format_detects_corruption() {
+        let data = b"integrity check for the huffman stream ".repeat(300);
+        let encoded = huffman_encode_checked(&data);
+        assert_eq!(&encoded[..4], b"HFMC");
+        assert_eq!(encoded.len(), huffman_encode(&data).len() + 4);
+        assert_bytes_eq(&data, &huffman_decode_checked(&encoded).unwrap());
+        assert_bytes_eq(
+            b"",
+            &huffman_decode_checked(&huffman_encode_checked(b"")).unwrap(),
+        );
+
+        // 翻转比特流中的一个字节：要么无法解出，要么校验和不符，绝不返回错误的数据。
+        let body = 8 + 4 * SYMBOL_LIMIT;
+        for offset in [body, body + 100, encoded.len() - 40] {
+            let mut corrupt = encoded.clone();
+            corrupt[offset] ^= 0x10;
+            assert!(huffman_decode_checked(&corrupt).is_err(), "offset {offset}");
+        }
+        // 改动 CRC 字段本身。
+        let mut corrupt = encoded.clone();
+        corrupt[body - 1] ^= 0x01;
+        assert!(matches!(
+            huffman_decode_checked(&corrupt),
+            Err(Error::ChecksumMismatch(_))
+        ));
+        assert!(matches!(
+            huffman_decode_checked(&huffman_encode(&data)),
+            Err(Error::BadMagic(_))
+        ));
+
+        assert_bytes_eq(
+            &data,
+            &huffman_decode_checked_with_limit(&encoded, data.len()).unwrap(),
+        );
+        assert!(matches!(
+            huffman_decode_checked_with_limit(&encoded, data.len() - 1),
+            Err(Error::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn verify_compares_without_output() {
+        let random = encoding_common::selftest::pseudo_random(20_000, 0x7E21);
+        for data in [&random[..], b"verify", b"", &[3u8; 4000][..]] {
+            assert!(huffman_verify(data).unwrap());
+        }
+        let encoded = huffman_encode(b"verify me");
+        assert!(!verify_encoded(&encoded, b"verify mE").unwrap());
+        assert!(!verify_encoded(&encoded, b"verify").unwrap());
+        assert!(!verify_encoded(&encoded, b"verify me!").unwrap());
+        assert!(verify_encoded(&encoded, b"verify me").unwrap());
+    }
+
+    #[test]
+    fn single_distinct_byte_costs_one_bit_each() {
+        // 字节与 EOF 两个叶子，码字各 1 位：100 万字节的数据体为 1000001 位，加上 1036 字节头部。
+        let data = vec![0x41u8; 1_000_000];
+        let encoded = huffman_encode(&data);
+        assert_eq!(encoded.len(), 1036 + 125_001);
+        assert_bytes_eq(&data, &huffman_decode(&encoded).unwrap());
+
+        let (input, output, decoded) = (
+            temp_path("single_in"),
+            temp_path("single_enc"),
+            temp_path("single_dec"),
+        );
+        fs::write(&input, &data).unwrap();
+        huffman_encode_file(path_str(&input), path_str(&output)).unwrap();
+        assert_eq!(fs::read(&output).unwrap(), encoded);
+        huffman_decode_file(path_str(&output), path_str(&decoded)).unwrap();
+        assert_bytes_eq(&data, &fs::read(&decoded).unwrap());
+        for p in [input, output, decoded] {
+            let _ = fs::remove_file(p);
+        }
+
+        // 只有 EOF 的单叶子树：码字为 0，另一分支不存在，遇到 1 比特时报告非法码字。
+        let codes = build_code_table(&build_tree(&build_frequencies(b"")));
+        assert_eq!(codes[EOF_SYMBOL as usize], (0, 1));
+        let empty = huffman_encode(b"");
+        assert_eq!(empty.len(), 1036 + 1);
+        assert!(huffman_decode(&empty).unwrap().is_empty());
+        let mut flipped = empty.clone();
+        *flipped.last_mut().unwrap() = 0x80;
+        assert!(matches!(
+            huffman_decode(&flipped),
+            Err(Error::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn canonical_header_roundtrip() {
+        for data in [&b""[..], b"q", &b"canonical header ".repeat(300)] {
+            let encoded = huffman_encode_canonical(data);
+            assert_eq!(&encoded[..4], b"HFM2");
+            assert_bytes_eq(data, &huffman_decode_canonical(&encoded).unwrap());
+        }
+        let text = b"canonical header ".repeat(300);
+        // 码长相同，比特流等长；头部从 1036 字节降到 261 字节。
+        assert_eq!(
+            huffman_encode_canonical(&text).len() + 775,
+            huffman_encode(&text).len()
+        );
+        assert!(matches!(
+            huffman_decode_canonical(&huffman_encode(&text)),
+            Err(Error::BadMagic(_))
+        ));
+        let encoded = huffman_encode_canonical(&text);
+        assert_bytes_eq(
+            &text,
+            &huffman_decode_canonical_with_limit(&encoded, text.len()).unwrap(),
+        );
+        assert!(matches!(
+            huffman_decode_canonical_with_limit(&encoded, text.len() - 1),
+            Err(Error::LimitExceeded(_))
+        ));
+
+        // 头部中的码长 64 按格式错误拒绝，不在分配码字时移位溢出。
+        let mut crafted = huffman_encode_canonical(b"q");
+        crafted[4 + b'q' as usize] = 64;
+        crafted[4 + EOF_SYMBOL as usize] = 64;
+        assert!(matches!(
+            huffman_decode_canonical(&crafted),
+            Err(Error::InvalidModel(_))
+        ));
+    }
+
+    #[test]
+    fn canonical_codes_depend_only_on_lengths() {
+        // 前两个输入的码长表完全相同，第三个的码长分布相同但落在其他符号上。
+        let inputs = [&b"aaaab"[..], b"abaaa", b"xxxxy"];
+        let encoded: Vec<Vec<u8>> = inputs.iter().map(|d| huffman_encode_canonical(d)).collect();
+        assert_eq!(
+            encoded[0][..4 + SYMBOL_LIMIT],
+            encoded[1][..4 + SYMBOL_LIMIT]
+        );
+        let dist = |e: &[u8]| {
+            let mut lens = e[4..4 + SYMBOL_LIMIT].to_vec();
+            lens.sort_unstable();
+            lens
+        };
+        assert_eq!(dist(&encoded[0]), dist(&encoded[2]));
+        for (data, enc) in inputs.iter().zip(&encoded) {
+            assert_bytes_eq(data, &huffman_decode_canonical(enc).unwrap());
+        }
+    }
+
+    #[test]
+    fn adaptive_roundtrip_without_header() {
+        for seed in [1u64, 0xADA7, 0xF6C5_1234] {
+            let random = encoding_common::selftest::pseudo_random(30_000, seed);
+            // 偏斜分布：字节取平方后取高位，前面的值远比后面的常见。
+            let skewed: Vec<u8> = random
+                .iter()
+                .map(|&b| ((b as u32 * b as u32) >> 8) as u8)
+                .collect();
+            for data in [&random[..], &skewed[..], &random[..1]] {
+                let encoded = huffman_encode_adaptive(data);
+                assert_bytes_eq(data, &huffman_decode_adaptive(&encoded).unwrap());
+            }
+        }
+        for data in [&b""[..], b"a", &[0xFFu8; 5000][..]] {
+            let encoded = huffman_encode_adaptive(data);
+            assert_eq!(&encoded[..4], b"HFMA");
+            assert_bytes_eq(data, &huffman_decode_adaptive(&encoded).unwrap());
+        }
+
+        // 没有频率表：短文本远小于 HFMN，长文本与静态码相差无几。
+        let short = b"adaptive";
+        assert!(huffman_encode_adaptive(short).len() < 20);
+        let text = b"the tree follows the data as it arrives. ".repeat(500);
+        let adaptive = huffman_encode_adaptive(&text).len();
+        assert!(adaptive < huffman_encode(&text).len(), "{adaptive} bytes");
+
+        let encoded = huffman_encode_adaptive(&text);
+        assert!(matches!(
+            huffman_decode_adaptive(&encoded[..encoded.len() / 2]),
+            Err(Error::Truncated(_))
+        ));
+        assert_bytes_eq(
+            &text,
+            &huffman_decode_adaptive_with_limit(&encoded, text.len()).unwrap(),
+        );
+        assert!(matches!(
+            huffman_decode_adaptive_with_limit(&encoded, text.len() - 1),
+            Err(Error::LimitExceeded(_))
+        ));
+        assert!(matches!(
+            huffman_decode_adaptive(&huffman_encode(short)),
+            Err(Error::BadMagic(_))
+        ));
+    }
+}
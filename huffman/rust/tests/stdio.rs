@@ -0,0 +1,30 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// 以 "-" 作为输入与输出路径运行 CLI，stdin 写入 input，返回 stdout。
+fn pipe(mode: &str, input: &[u8]) -> Vec<u8> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_huffman_rust"))
+        .args([mode, "-", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_vec();
+    // 另起线程写入，避免输出填满管道时双方互相等待。
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap().unwrap();
+    assert!(output.status.success(), "huffman_rust {mode} - - failed");
+    output.stdout
+}
+
+#[test]
+fn dash_paths_stream_through_stdin_and_stdout() {
+    // 含 \r\n 与 0x1A 等字节，输出到 stdout 时不得做任何转换。
+    let mut data = b"line one\r\nline two\n\x1a\x00\xff".repeat(500);
+    data.extend((0..20_000u32).map(|i| (i * 31 + 7) as u8));
+    let encoded = pipe("encode", &data);
+    assert_eq!(encoded, huffman::huffman_encode(&data));
+    assert_eq!(pipe("decode", &encoded), data);
+}
@@ -12,6 +12,9 @@ static const uint32_t SYMBOL_LIMIT = 257;
 static const uint32_t EOF_SYMBOL = SYMBOL_LIMIT - 1;
 static const uint32_t MAX_TOTAL = 1u << 24;
 static const uint32_t RENORM_THRESHOLD = 1u << 24;
+// RCNS 数据流允许的最大重复次数（1 GiB）。RCNS 只用 13 字节声明输出长度，
+// 超过上限时在分配之前抛出异常，伪造的头部不会让解码器耗尽内存。
+static const uint64_t MAX_SINGLE_RUN = 1ull << 30;
 
 static void scale_frequencies(std::vector<uint32_t>& freq) {
     uint64_t total = 0;
@@ -133,31 +136,50 @@ public:
         : out_(out), low_(0), high_(0xFFFFFFFFu) {}
 
     void encode_symbol(uint32_t symbol, const std::vector<uint32_t>& cumulative) {
-        uint64_t range = static_cast<uint64_t>(high_) - low_ + 1;
         uint64_t total = cumulative.back();
+        fit(total);
+        uint64_t range = static_cast<uint64_t>(high_) - low_ + 1;
         uint64_t symLow = cumulative[symbol];
         uint64_t symHigh = cumulative[symbol + 1];
 
         high_ = low_ + static_cast<uint32_t>((range * symHigh) / total - 1);
         low_ = low_ + static_cast<uint32_t>((range * symLow) / total);
+        renormalize();
+    }
 
-        while ((low_ ^ high_) < RENORM_THRESHOLD) {
+    void finish() {
+        for (int i = 0; i < 4; ++i) {
             uint8_t byte = static_cast<uint8_t>(low_ >> 24);
             out_.push_back(byte);
             low_ <<= 8;
-            high_ = (high_ << 8) | 0xFFu;
         }
     }
 
-    void finish() {
-        for (int i = 0; i < 4; ++i) {
+private:
+    void renormalize() {
+        while ((low_ ^ high_) < RENORM_THRESHOLD) {
             uint8_t byte = static_cast<uint8_t>(low_ >> 24);
             out_.push_back(byte);
             low_ <<= 8;
+            high_ = (high_ << 8) | 0xFFu;
+        }
+    }
+
+    // 区间跨越最高字节边界时宽度可能小于模型总和，此时频率为 1 的符号会分不到宽度，
+    // 区间随之倒置。编码前若宽度不足，保留边界两侧中较宽的一侧并移出字节，
+    // 直到宽度不小于 total。解码端在同一位置做相同的调整。
+    void fit(uint64_t total) {
+        while (static_cast<uint64_t>(high_ - low_) + 1 < total) {
+            uint32_t boundary = high_ & ~(RENORM_THRESHOLD - 1);
+            if (boundary - low_ >= high_ - boundary) {
+                high_ = boundary - 1;
+            } else {
+                low_ = boundary;
+            }
+            renormalize();
         }
     }
 
-private:
     std::vector<uint8_t>& out_;
     uint32_t low_;
     uint32_t high_;
@@ -173,8 +195,9 @@ public:
     }
 
     uint32_t decode_symbol(const std::vector<uint32_t>& cumulative) {
-        uint64_t range = static_cast<uint64_t>(high_) - low_ + 1;
         uint64_t total = cumulative.back();
+        fit(total);
+        uint64_t range = static_cast<uint64_t>(high_) - low_ + 1;
         uint64_t offset = code_ - low_;
         uint64_t value = ((offset + 1) * total - 1) / range;
 
@@ -195,17 +218,33 @@ public:
 
         high_ = low_ + static_cast<uint32_t>((range * symHigh) / total - 1);
         low_ = low_ + static_cast<uint32_t>((range * symLow) / total);
+        renormalize();
 
+        return symbol;
+    }
+
+private:
+    void renormalize() {
         while ((low_ ^ high_) < RENORM_THRESHOLD) {
             low_ <<= 8;
             high_ = (high_ << 8) | 0xFFu;
             code_ = (code_ << 8) | read_byte();
         }
+    }
 
-        return symbol;
+    // 与 RangeEncoder::fit 对应。
+    void fit(uint64_t total) {
+        while (static_cast<uint64_t>(high_ - low_) + 1 < total) {
+            uint32_t boundary = high_ & ~(RENORM_THRESHOLD - 1);
+            if (boundary - low_ >= high_ - boundary) {
+                high_ = boundary - 1;
+            } else {
+                low_ = boundary;
+            }
+            renormalize();
+        }
     }
 
-private:
     const uint8_t* data_;
     size_t size_;
     size_t pos_;
@@ -238,7 +277,29 @@ std::vector<uint8_t> encode(const std::vector<uint8_t>& data) {
     return out;
 }
 
+// RCNS 格式：魔数 + 1 字节取值 + 8 字节小端重复次数。
+static std::vector<uint8_t> decode_single(const std::vector<uint8_t>& encoded) {
+    if (encoded.size() < 13) {
+        throw std::runtime_error("Truncated single-byte stream");
+    }
+    uint64_t count = 0;
+    for (size_t i = 12; i >= 5; i--) {
+        count = (count << 8) | encoded[i];
+    }
+    if (count == 0) {
+        throw std::runtime_error("Invalid single-byte stream");
+    }
+    if (count > MAX_SINGLE_RUN) {
+        throw std::runtime_error("Single-byte stream exceeds output limit");
+    }
+    return std::vector<uint8_t>(static_cast<size_t>(count), encoded[4]);
+}
+
 std::vector<uint8_t> decode(const std::vector<uint8_t>& encoded) {
+    if (encoded.size() >= 4 && encoded[0] == 'R' && encoded[1] == 'C' && encoded[2] == 'N' &&
+        encoded[3] == 'S') {
+        return decode_single(encoded);
+    }
     size_t pos = 0;
     std::vector<uint32_t> freq;
     if (!read_header(encoded, pos, freq)) {
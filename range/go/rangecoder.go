@@ -1,6 +1,9 @@
 package rangecoder
 
-import "errors"
+import (
+	"bytes"
+	"errors"
+)
 
 const (
 	symbolLimit     = 257
@@ -9,6 +12,10 @@ const (
 	renormThreshold  = uint32(1) << 24
 )
 
+// MaxSingleRun 是 RCNS 数据流允许的最大重复次数（1 GiB）。RCNS 只用 13 字节声明输出长度，
+// 超过上限时 Decode 在分配之前返回错误，伪造的头部不会让解码器耗尽内存。
+const MaxSingleRun = 1 << 30
+
 func scaleFrequencies(freq []uint32) {
 	var total uint64
 	for _, f := range freq {
@@ -132,14 +139,18 @@ func newEncoder(out *[]byte) *encoder {
 }
 
 func (e *encoder) encodeSymbol(symbol uint32, cumulative []uint32) {
-	rangeVal := uint64(e.high) - uint64(e.low) + 1
 	total := uint64(cumulative[len(cumulative)-1])
+	e.fit(total)
+	rangeVal := uint64(e.high) - uint64(e.low) + 1
 	symLow := uint64(cumulative[symbol])
 	symHigh := uint64(cumulative[symbol+1])
 
 	e.high = e.low + uint32((rangeVal*symHigh)/total-1)
 	e.low = e.low + uint32((rangeVal*symLow)/total)
+	e.renormalize()
+}
 
+func (e *encoder) renormalize() {
 	for (e.low ^ e.high) < renormThreshold {
 		b := byte(e.low >> 24)
 		*e.out = append(*e.out, b)
@@ -148,6 +159,21 @@ func (e *encoder) encodeSymbol(symbol uint32, cumulative []uint32) {
 	}
 }
 
+// 区间跨越最高字节边界时宽度可能小于模型总和，此时频率为 1 的符号会分不到宽度，
+// 区间随之倒置。编码前若宽度不足，保留边界两侧中较宽的一侧并移出字节，
+// 直到宽度不小于 total。解码端在同一位置做相同的调整。
+func (e *encoder) fit(total uint64) {
+	for uint64(e.high-e.low)+1 < total {
+		boundary := e.high &^ (renormThreshold - 1)
+		if boundary-e.low >= e.high-boundary {
+			e.high = boundary - 1
+		} else {
+			e.low = boundary
+		}
+		e.renormalize()
+	}
+}
+
 func (e *encoder) finish() {
 	for i := 0; i < 4; i++ {
 		b := byte(e.low >> 24)
@@ -183,8 +209,9 @@ func (d *decoder) readByte() byte {
 }
 
 func (d *decoder) decodeSymbol(cumulative []uint32) uint32 {
-	rangeVal := uint64(d.high) - uint64(d.low) + 1
 	total := uint64(cumulative[len(cumulative)-1])
+	d.fit(total)
+	rangeVal := uint64(d.high) - uint64(d.low) + 1
 	offset := uint64(d.code - d.low)
 	value := ((offset+1)*total - 1) / rangeVal
 
@@ -205,14 +232,30 @@ func (d *decoder) decodeSymbol(cumulative []uint32) uint32 {
 
 	d.high = d.low + uint32((rangeVal*symHigh)/total-1)
 	d.low = d.low + uint32((rangeVal*symLow)/total)
+	d.renormalize()
+
+	return symbol
+}
 
+func (d *decoder) renormalize() {
 	for (d.low ^ d.high) < renormThreshold {
 		d.low <<= 8
 		d.high = (d.high << 8) | 0xFF
 		d.code = (d.code << 8) | uint32(d.readByte())
 	}
+}
 
-	return symbol
+// 与 encoder.fit 对应。
+func (d *decoder) fit(total uint64) {
+	for uint64(d.high-d.low)+1 < total {
+		boundary := d.high &^ (renormThreshold - 1)
+		if boundary-d.low >= d.high-boundary {
+			d.high = boundary - 1
+		} else {
+			d.low = boundary
+		}
+		d.renormalize()
+	}
 }
 
 func Encode(input []byte) ([]byte, error) {
@@ -232,7 +275,28 @@ func Encode(input []byte) ([]byte, error) {
 	return out, nil
 }
 
+// decodeSingle 解码 RCNS 格式：魔数 + 1 字节取值 + 8 字节小端重复次数。
+func decodeSingle(encoded []byte) ([]byte, error) {
+	if len(encoded) < 13 {
+		return nil, errors.New("range: truncated header")
+	}
+	var count uint64
+	for i := 12; i >= 5; i-- {
+		count = count<<8 | uint64(encoded[i])
+	}
+	if count == 0 {
+		return nil, errors.New("range: bad header")
+	}
+	if count > MaxSingleRun {
+		return nil, errors.New("range: output limit exceeded")
+	}
+	return bytes.Repeat(encoded[4:5], int(count)), nil
+}
+
 func Decode(encoded []byte) ([]byte, error) {
+	if len(encoded) >= 4 && string(encoded[:4]) == "RCNS" {
+		return decodeSingle(encoded)
+	}
 	pos := 0
 	freq, err := readHeader(encoded, &pos)
 	if err != nil {
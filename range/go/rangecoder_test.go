@@ -82,6 +82,29 @@ func BenchmarkEncodeDecode4MiB(b *testing.B) {
 	}
 }
 
+func TestSingleRunBomb(t *testing.T) {
+	single := func(count uint64) []byte {
+		b := []byte{'R', 'C', 'N', 'S', 0x42, 0, 0, 0, 0, 0, 0, 0, 0}
+		for i := 0; i < 8; i++ {
+			b[5+i] = byte(count >> (8 * i))
+		}
+		return b
+	}
+	dec, err := Decode(single(1000))
+	if err != nil || !bytes.Equal(dec, bytes.Repeat([]byte{0x42}, 1000)) {
+		t.Fatalf("decode single run: %v", err)
+	}
+	// 13 字节的头部声明远超上限的长度：返回错误，不尝试分配。
+	for _, count := range []uint64{MaxSingleRun + 1, 1 << 62, ^uint64(0)} {
+		if _, err := Decode(single(count)); err == nil {
+			t.Fatalf("count %d: expected error", count)
+		}
+	}
+	if _, err := Decode(single(0)); err == nil {
+		t.Fatalf("count 0: expected error")
+	}
+}
+
 func TestDeterministic(t *testing.T) {
 	data := makeTestData(1 << 16)
 	enc1, err := Encode(data)
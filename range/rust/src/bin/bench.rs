@@ -1,4 +1,8 @@
-use rangecoder::{decode, encode};
+use encoding_common::selftest;
+use rangecoder::{
+    decode, decode_with_model_file, encode, encode_with_model_file, train_model_file,
+};
+use std::process;
 use std::time::Instant;
 
 fn make_test_data(size: usize) -> Vec<u8> {
@@ -10,6 +14,39 @@ fn make_test_data(size: usize) -> Vec<u8> {
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("selftest") {
+        let ok = selftest::run("range", |data| decode(&encode(data)?));
+        process::exit(if ok { 0 } else { 1 });
+    }
+    // 模型文件子命令：由语料训练模型，再用同一模型编码/解码其他文件。
+    if let Some(mode @ ("train" | "encode-model" | "decode-model")) =
+        args.get(1).map(String::as_str)
+    {
+        let result = match (mode, &args[2..]) {
+            ("train", [corpus, model]) => train_model_file(corpus, model),
+            ("encode-model", [input, output, model]) => {
+                encode_with_model_file(input, output, model)
+            }
+            ("decode-model", [input, output, model]) => {
+                decode_with_model_file(input, output, model)
+            }
+            _ => {
+                eprintln!("用法: {} train corpus model_out", args[0]);
+                eprintln!(
+                    "      {} encode-model|decode-model input output model",
+                    args[0]
+                );
+                process::exit(1);
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("运行失败: {e}");
+            process::exit(1);
+        }
+        return;
+    }
+
     let size: usize = 1 << 20; // 1 MiB
     let iterations: usize = 20;
 
@@ -0,0 +1,370 @@
+// 可断点续解的流式格式：编码过程中可随时调用 checkpoint，把区间编码器刷新到字节边界
+// 并开始新的一段；每段开头保存恢复解码所需的状态，解码可从任意一段的起点开始。
+//
+// 格式：魔数 RCNT + 1 字节模式（0 静态、1 自适应）+ [静态：257 个 u32 频率]，之后为若干段：
+//   标记 CKPT + [自适应：段起点的 257 个 u32 模型计数] + u32 数据长度 + 以 EOF 结尾的编码数据。
+// 静态模式的模型在整个流中不变，段内无需保存状态。所有整数均为小端。
+
+use crate::{
+    build_cumulative, read_u32_le, scale_frequencies, write_u32_le, AdaptiveModel, DecodeLimits,
+    DecodeOptions, Error, Model, RangeDecoder, RangeEncoder, EOF_SYMBOL, MODEL_TABLE_BYTES,
+    SYMBOL_LIMIT,
+};
+use std::io::Write;
+
+const CHECKPOINT_MAGIC: &[u8; 4] = b"RCNT";
+const SEGMENT_MARKER: &[u8; 4] = b"CKPT";
+const MODE_STATIC: u8 = 0;
+const MODE_ADAPTIVE: u8 = 1;
+
+enum SegmentModel {
+    Static(Vec<u32>),
+    Adaptive(AdaptiveModel),
+}
+
+impl SegmentModel {
+    fn cumulative(&self) -> &[u32] {
+        match self {
+            SegmentModel::Static(cumulative) => cumulative,
+            SegmentModel::Adaptive(model) => model.cumulative(),
+        }
+    }
+}
+
+// 带检查点的流式编码器，由调用方逐段 write。
+// 当前段的编码数据缓存在内部，checkpoint 或 finish 时连同长度一起写给底层 writer。
+pub struct CheckpointEncoder<W: Write> {
+    writer: W,
+    // 已写给 writer 的字节数，即下一段的起始偏移。
+    written: u64,
+    model: SegmentModel,
+    body: Vec<u8>,
+    low: u32,
+    high: u32,
+}
+
+impl<W: Write> CheckpointEncoder<W> {
+    // 静态模型：freq 须为 257 项且 EOF 频率非零，总和过大时按 normalize 缩放。
+    pub fn new_static(writer: W, freq: &[u32]) -> Result<Self, Error> {
+        if freq.len() != SYMBOL_LIMIT || freq[EOF_SYMBOL] == 0 {
+            return Err(Error::InvalidModel("range: bad frequency table"));
+        }
+        let mut freq = freq.to_vec();
+        scale_frequencies(&mut freq);
+        let mut header = CHECKPOINT_MAGIC.to_vec();
+        header.push(MODE_STATIC);
+        for &f in &freq {
+            write_u32_le(&mut header, f);
+        }
+        let model = SegmentModel::Static(build_cumulative(&freq));
+        CheckpointEncoder::start(writer, header, model)
+    }
+
+    // 自适应模型：从 AdaptiveModel::new() 出发，每段开头保存当时的计数。
+    pub fn new_adaptive(writer: W) -> Result<Self, Error> {
+        let mut header = CHECKPOINT_MAGIC.to_vec();
+        header.push(MODE_ADAPTIVE);
+        let model = SegmentModel::Adaptive(AdaptiveModel::new());
+        CheckpointEncoder::start(writer, header, model)
+    }
+
+    fn start(writer: W, header: Vec<u8>, model: SegmentModel) -> Result<Self, Error> {
+        let mut enc = CheckpointEncoder {
+            writer,
+            written: 0,
+            model,
+            body: Vec::new(),
+            low: 0,
+            high: 0xFFFF_FFFF,
+        };
+        enc.emit(&header)?;
+        enc.begin_segment()?;
+        Ok(enc)
+    }
+
+    fn emit(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.writer.write_all(bytes)?;
+        self.written += bytes.len() as u64;
+        Ok(())
+    }
+
+    // 写出段标记与段起点的模型状态。
+    fn begin_segment(&mut self) -> Result<(), Error> {
+        let mut head = SEGMENT_MARKER.to_vec();
+        if let SegmentModel::Adaptive(model) = &self.model {
+            for &c in model.counts() {
+                write_u32_le(&mut head, c);
+            }
+        }
+        self.emit(&head)
+    }
+
+    // 静态模型中频率为 0 的字节无法编码，此时整段 data 都不写入并返回 InvalidInput。
+    pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        if let SegmentModel::Static(cumulative) = &self.model {
+            if data
+                .iter()
+                .any(|&b| cumulative[b as usize] == cumulative[b as usize + 1])
+            {
+                return Err(Error::InvalidInput("range: symbol not in model"));
+            }
+        }
+        let mut enc = RangeEncoder::resume(&mut self.body, self.low, self.high);
+        for &b in data {
+            enc.encode_symbol(b as u32, self.model.cumulative());
+            if let SegmentModel::Adaptive(model) = &mut self.model {
+                model.update(b as usize);
+            }
+        }
+        (self.low, self.high) = (enc.low, enc.high);
+        Ok(())
+    }
+
+    // 以 EOF 结束当前段，连同长度写给 writer，并重置区间编码器。
+    fn end_segment(&mut self) -> Result<(), Error> {
+        let mut body = std::mem::take(&mut self.body);
+        let mut enc = RangeEncoder::resume(&mut body, self.low, self.high);
+        enc.encode_symbol(EOF_SYMBOL as u32, self.model.cumulative());
+        enc.finish();
+        let len = u32::try_from(body.len())
+            .map_err(|_| Error::LimitExceeded("range: checkpoint segment too large"))?;
+        let mut head = Vec::with_capacity(4);
+        write_u32_le(&mut head, len);
+        self.emit(&head)?;
+        self.emit(&body)?;
+        body.clear();
+        self.body = body;
+        (self.low, self.high) = (0, 0xFFFF_FFFF);
+        Ok(())
+    }
+
+    // 结束当前段并开始新的一段，返回新段在输出中的偏移，可交给 decode_from_checkpoint。
+    pub fn checkpoint(&mut self) -> Result<u64, Error> {
+        self.end_segment()?;
+        let offset = self.written;
+        self.begin_segment()?;
+        Ok(offset)
+    }
+
+    // 结束最后一段，flush 后返回底层 writer。
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.end_segment()?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+struct StreamHeader {
+    adaptive: bool,
+    cumulative: Vec<u32>,
+    // 第一段的偏移。
+    first: usize,
+}
+
+fn read_stream_header(encoded: &[u8]) -> Result<StreamHeader, Error> {
+    if encoded.len() < 5 {
+        return Err(Error::Truncated("range: input too short"));
+    }
+    if &encoded[0..4] != CHECKPOINT_MAGIC {
+        return Err(Error::BadMagic("range: bad magic"));
+    }
+    match encoded[4] {
+        MODE_ADAPTIVE => Ok(StreamHeader {
+            adaptive: true,
+            cumulative: Vec::new(),
+            first: 5,
+        }),
+        MODE_STATIC => {
+            let mut pos = 5;
+            let freq = read_counts(encoded, &mut pos)?;
+            crate::validate_model(&freq)?;
+            Ok(StreamHeader {
+                adaptive: false,
+                cumulative: build_cumulative(&freq),
+                first: pos,
+            })
+        }
+        _ => Err(Error::InvalidData("range: bad checkpoint mode")),
+    }
+}
+
+fn read_counts(encoded: &[u8], pos: &mut usize) -> Result<Vec<u32>, Error> {
+    (0..SYMBOL_LIMIT)
+        .map(|_| read_u32_le(encoded, pos).ok_or(Error::Truncated("range: truncated model")))
+        .collect()
+}
+
+// 解码整个带检查点的流。
+pub fn decode_checkpointed(encoded: &[u8]) -> Result<Vec<u8>, Error> {
+    decode_checkpointed_with_limits(encoded, &DecodeLimits::unlimited())
+}
+
+// 在 limits 约束下解码整个流：模型表与输出合计超过上限时返回 LimitExceeded。
+pub fn decode_checkpointed_with_limits(
+    encoded: &[u8],
+    limits: &DecodeLimits,
+) -> Result<Vec<u8>, Error> {
+    let max_output = limits
+        .output_budget(MODEL_TABLE_BYTES)
+        .ok_or(Error::LimitExceeded("range: memory limit exceeded"))?;
+    let header = read_stream_header(encoded)?;
+    decode_segments(encoded, &header, header.first, max_output)
+}
+
+// 从 offset 处的检查点（checkpoint 的返回值）开始解码到流末尾，
+// 结果等于原文中该检查点之后的部分。offset 处不是段标记时返回 InvalidInput。
+pub fn decode_from_checkpoint(encoded: &[u8], offset: u64) -> Result<Vec<u8>, Error> {
+    let header = read_stream_header(encoded)?;
+    let offset = usize::try_from(offset)
+        .ok()
+        .filter(|&o| o >= header.first && encoded.get(o..o + 4) == Some(&SEGMENT_MARKER[..]))
+        .ok_or(Error::InvalidInput("range: not a checkpoint offset"))?;
+    decode_segments(encoded, &header, offset, u64::MAX)
+}
+
+fn decode_segments(
+    encoded: &[u8],
+    header: &StreamHeader,
+    mut pos: usize,
+    max_output: u64,
+) -> Result<Vec<u8>, Error> {
+    let max_output = usize::try_from(max_output).unwrap_or(usize::MAX);
+    let mut out = Vec::new();
+    while pos < encoded.len() {
+        if encoded.get(pos..pos + 4) != Some(&SEGMENT_MARKER[..]) {
+            return Err(Error::InvalidData("range: bad checkpoint marker"));
+        }
+        pos += 4;
+        let model = match header.adaptive {
+            true => {
+                let counts = read_counts(encoded, &mut pos)?;
+                Some(
+                    AdaptiveModel::from_counts(&counts)
+                        .ok_or(Error::InvalidModel("range: bad checkpoint model"))?,
+                )
+            }
+            false => None,
+        };
+        let len = read_u32_le(encoded, &mut pos)
+            .ok_or(Error::Truncated("range: truncated checkpoint"))? as usize;
+        let body = encoded
+            .get(pos..pos.saturating_add(len))
+            .ok_or(Error::Truncated("range: truncated checkpoint"))?;
+        pos += len;
+        // 本段可用的输出额度。
+        let remaining = max_output - out.len();
+        match model {
+            Some(model) => decode_adaptive_segment(body, model, remaining, &mut out)?,
+            None => {
+                let opts = DecodeOptions::new().with_max_output(remaining);
+                crate::decode_body_into(body, &header.cumulative, &opts, &mut out)?
+            }
+        }
+    }
+    Ok(out)
+}
+
+// 段内数据越过末尾时返回 Truncated：完整的段解码到 EOF 不会读到补零。
+fn decode_adaptive_segment(
+    body: &[u8],
+    mut model: AdaptiveModel,
+    max_output: usize,
+    out: &mut Vec<u8>,
+) -> Result<(), Error> {
+    let max_output = max_output.saturating_add(out.len());
+    let mut dec = RangeDecoder::new(body);
+    loop {
+        let sym = dec.decode_symbol(model.cumulative()) as usize;
+        if dec.overrun() {
+            return Err(Error::Truncated("range: truncated checkpoint"));
+        }
+        if sym == EOF_SYMBOL {
+            return Ok(());
+        }
+        if out.len() >= max_output {
+            return Err(Error::LimitExceeded("range: output limit exceeded"));
+        }
+        out.push(sym as u8);
+        model.update(sym);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_common::selftest::pseudo_random;
+    use encoding_common::test_util::assert_bytes_eq;
+
+    #[test]
+    fn resume_from_second_checkpoint() {
+        let data: Vec<u8> = pseudo_random(12_000, 0x9E37_79B9)
+            .iter()
+            .map(|&r| b"abcdefgh"[(r % 8) as usize])
+            .collect();
+        let chunks: Vec<&[u8]> = data.chunks(3000).collect();
+        let mut freq = vec![0u32; SYMBOL_LIMIT];
+        for &b in &data {
+            freq[b as usize] += 1;
+        }
+        freq[EOF_SYMBOL] = 1;
+
+        let encoders = [
+            CheckpointEncoder::new_adaptive(Vec::new()).unwrap(),
+            CheckpointEncoder::new_static(Vec::new(), &freq).unwrap(),
+        ];
+        for mut enc in encoders {
+            let mut offsets = Vec::new();
+            for chunk in &chunks {
+                enc.write(chunk).unwrap();
+                offsets.push(enc.checkpoint().unwrap());
+            }
+            let encoded = enc.finish().unwrap();
+
+            assert_bytes_eq(&data, &decode_checkpointed(&encoded).unwrap());
+            assert_bytes_eq(
+                &data[6000..],
+                &decode_from_checkpoint(&encoded, offsets[1]).unwrap(),
+            );
+            // 最后一个检查点之后没有数据。
+            let last = *offsets.last().unwrap();
+            assert!(decode_from_checkpoint(&encoded, last).unwrap().is_empty());
+            assert!(matches!(
+                decode_from_checkpoint(&encoded, offsets[1] + 1),
+                Err(Error::InvalidInput(_))
+            ));
+        }
+
+        let mut enc = CheckpointEncoder::new_static(Vec::new(), &freq).unwrap();
+        assert!(matches!(enc.write(b"z"), Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn checkpointed_decode_honors_limits() {
+        let data = pseudo_random(8000, 0xC4EC);
+        let mut freq = vec![1u32; SYMBOL_LIMIT];
+        for &b in &data {
+            freq[b as usize] += 1;
+        }
+        let encoders = [
+            CheckpointEncoder::new_adaptive(Vec::new()).unwrap(),
+            CheckpointEncoder::new_static(Vec::new(), &freq).unwrap(),
+        ];
+        for mut enc in encoders {
+            for chunk in data.chunks(3000) {
+                enc.write(chunk).unwrap();
+                enc.checkpoint().unwrap();
+            }
+            let encoded = enc.finish().unwrap();
+            let limits = DecodeLimits::new(MODEL_TABLE_BYTES + data.len() as u64);
+            assert_bytes_eq(
+                &data,
+                &decode_checkpointed_with_limits(&encoded, &limits).unwrap(),
+            );
+            let limits = DecodeLimits::new(MODEL_TABLE_BYTES + data.len() as u64 - 1);
+            assert!(matches!(
+                decode_checkpointed_with_limits(&encoded, &limits),
+                Err(Error::LimitExceeded(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,63 @@
+// CRC-32 的实现位于 encoding_common，这里仅引入供容器格式使用。
+pub use encoding_common::checksum::crc32;
+
+// Adler-32（RFC 1950），比 CRC32 快，但对短数据与部分错误模式的检出能力较弱。
+const ADLER_MOD: u32 = 65521;
+// 不取模时 b 不会溢出 u32 的最大字节数（zlib 的 NMAX）。
+const ADLER_NMAX: usize = 5552;
+
+// 增量计算：依次 update 各段数据，与一次计算整段数据的结果相同。
+#[derive(Debug, Clone, Copy)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    pub fn new() -> Adler32 {
+        Adler32 { a: 1, b: 0 }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for chunk in data.chunks(ADLER_NMAX) {
+            for &byte in chunk {
+                self.a += byte as u32;
+                self.b += self.a;
+            }
+            self.a %= ADLER_MOD;
+            self.b %= ADLER_MOD;
+        }
+    }
+
+    pub fn finish(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+}
+
+pub fn adler32(data: &[u8]) -> u32 {
+    let mut adler = Adler32::new();
+    adler.update(data);
+    adler.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adler32_known_vectors() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"a"), 0x0062_0062);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(adler32(b"123456789"), 0x091E_01DE);
+        // 全 0xFF 的长输入检验分段取模不会溢出。
+        assert_eq!(adler32(&[0xFF; 100_000]), 0x149A_302C);
+
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i * 7) as u8).collect();
+        let mut adler = Adler32::new();
+        for part in data.chunks(999) {
+            adler.update(part);
+        }
+        assert_eq!(adler.finish(), adler32(&data));
+    }
+}
@@ -0,0 +1,263 @@
+// 简单的逐比特上下文混合（context mixing）压缩器，建立在二元区间编码之上。
+//
+// 每个字节按高位在前拆成 8 个比特，每个比特由两个预测器给出"该位为 1"的概率：
+//   - order-k 比特上下文：此前最近 k 个比特（可跨字节）与当前比特在字节内的位置；
+//   - order-0 字节上下文：当前字节中已编码的高位前缀。
+// 两个概率由按比特位置区分的自适应线性混合器加权合成，再交给区间编码器。
+// 概率与权重都是整数，编解码两端按相同顺序更新，结果逐位一致。
+//
+// 格式：魔数 RCNK + 1 字节上下文阶数 k + 8 字节小端原始长度 + 编码数据。
+
+use crate::{DecodeLimits, Error, RangeDecoder, RangeEncoder, MAX_BITS_PER_BODY_BYTE};
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+const CM_MAGIC: &[u8; 4] = b"RCNK";
+const HEADER_LEN: usize = 13;
+
+// 默认 16 个比特（约两个字节）的上下文。
+pub const DEFAULT_CM_ORDER: u32 = 16;
+// 上下文表为 8 << k 项，k = 20 时约 16 MiB。
+pub const MAX_CM_ORDER: u32 = 20;
+
+// 概率精度 12 位，同时作为区间编码的频率总和。
+const PROB_BITS: u32 = 12;
+const PROB_ONE: i32 = 1 << PROB_BITS;
+// 预测器的学习速率：每次向目标移动 1/16。
+const PROB_RATE: u32 = 4;
+// 混合器权重为 16 位定点数，1.0 = 1 << 16。
+const WEIGHT_ONE: i32 = 1 << 16;
+const WEIGHT_RATE: u32 = 14;
+
+struct Predictor {
+    // order-k 比特上下文的概率表，下标为 (比特位置 << k) | 最近 k 个比特。
+    order_k: Vec<u16>,
+    // order-0 字节上下文，下标为带前导 1 的已编码前缀（1..=255）。
+    order_0: [u16; 256],
+    // 每个比特位置一组混合权重。
+    weights: [[i32; 2]; 8],
+    order: u32,
+    history: usize,
+    partial: usize,
+    bit_pos: usize,
+    // 本次预测的输入，供 update 使用。
+    inputs: [i32; 2],
+    mixed: i32,
+}
+
+impl Predictor {
+    fn new(order: u32) -> Predictor {
+        let half = (PROB_ONE / 2) as u16;
+        Predictor {
+            order_k: vec![half; 8 << order],
+            order_0: [half; 256],
+            weights: [[WEIGHT_ONE / 2; 2]; 8],
+            order,
+            history: 0,
+            partial: 1,
+            bit_pos: 0,
+            inputs: [0; 2],
+            mixed: 0,
+        }
+    }
+
+    fn context_k(&self) -> usize {
+        let mask = (1 << self.order) - 1;
+        (self.bit_pos << self.order) | (self.history & mask)
+    }
+
+    // 返回编码用的累积表 [0, P(0), 总和]。
+    fn predict(&mut self) -> [u32; 3] {
+        self.inputs = [
+            self.order_k[self.context_k()] as i32,
+            self.order_0[self.partial] as i32,
+        ];
+        let w = &self.weights[self.bit_pos];
+        let dot = w[0] as i64 * self.inputs[0] as i64 + w[1] as i64 * self.inputs[1] as i64;
+        self.mixed = ((dot >> 16) as i32).clamp(1, PROB_ONE - 1);
+        [0, (PROB_ONE - self.mixed) as u32, PROB_ONE as u32]
+    }
+
+    fn update(&mut self, bit: u32) {
+        let target = (bit as i32) << PROB_BITS;
+        let err = target - self.mixed;
+        let w = &mut self.weights[self.bit_pos];
+        for (wi, &p) in w.iter_mut().zip(&self.inputs) {
+            *wi = (*wi + ((err * p) >> WEIGHT_RATE)).clamp(0, 2 * WEIGHT_ONE);
+        }
+
+        let ctx = self.context_k();
+        adapt(&mut self.order_k[ctx], target);
+        adapt(&mut self.order_0[self.partial], target);
+
+        self.history = (self.history << 1) | bit as usize;
+        self.partial = (self.partial << 1) | bit as usize;
+        self.bit_pos += 1;
+        if self.bit_pos == 8 {
+            self.bit_pos = 0;
+            self.partial = 1;
+        }
+    }
+}
+
+// 概率向目标移动 1/2^PROB_RATE，保持在 [1, PROB_ONE - 1]。
+fn adapt(p: &mut u16, target: i32) {
+    let cur = *p as i32;
+    *p = (cur + ((target - cur) >> PROB_RATE)).clamp(1, PROB_ONE - 1) as u16;
+}
+
+fn check_order(order: u32) -> Result<(), Error> {
+    if order == 0 || order > MAX_CM_ORDER {
+        return Err(Error::InvalidInput("range: bad context order"));
+    }
+    Ok(())
+}
+
+// order 为 order-k 比特上下文的比特数，取值 1..=MAX_CM_ORDER。
+pub fn encode_cm(input: &[u8], order: u32) -> Result<Vec<u8>, Error> {
+    check_order(order)?;
+    let mut out = Vec::with_capacity(input.len() / 2 + HEADER_LEN + 8);
+    out.extend_from_slice(CM_MAGIC);
+    out.push(order as u8);
+    out.extend_from_slice(&(input.len() as u64).to_le_bytes());
+
+    let mut predictor = Predictor::new(order);
+    let mut enc = RangeEncoder::new(&mut out);
+    for &byte in input {
+        for shift in (0..8).rev() {
+            let bit = ((byte >> shift) & 1) as u32;
+            enc.encode_symbol(bit, &predictor.predict());
+            predictor.update(bit);
+        }
+    }
+    enc.finish();
+    Ok(out)
+}
+
+pub fn decode_cm(encoded: &[u8]) -> Result<Vec<u8>, Error> {
+    decode_cm_with_limits(encoded, &DecodeLimits::unlimited())
+}
+
+// 在 limits 约束下解码：上下文表与输出合计超过上限时在解码之前返回 LimitExceeded。
+// 概率限制在 [1, PROB_ONE - 1] 内，与 RCNB 相同每个数据体字节至多解出 MAX_BITS_PER_BODY_BYTE 个比特，
+// 长度超出数据体所能容纳的上限时返回 InvalidData，解码越过数据体末尾时返回 Truncated。
+pub fn decode_cm_with_limits(encoded: &[u8], limits: &DecodeLimits) -> Result<Vec<u8>, Error> {
+    if encoded.len() < HEADER_LEN {
+        return Err(Error::Truncated("range: input too short"));
+    }
+    if &encoded[0..4] != CM_MAGIC {
+        return Err(Error::BadMagic("range: bad magic"));
+    }
+    let order = encoded[4] as u32;
+    check_order(order).map_err(|_| Error::InvalidData("range: bad context order"))?;
+    let len = u64::from_le_bytes(encoded[5..HEADER_LEN].try_into().unwrap());
+    let body = &encoded[HEADER_LEN..];
+    let len = usize::try_from(len)
+        .ok()
+        .filter(|&len| len <= body.len().saturating_mul(MAX_BITS_PER_BODY_BYTE / 8))
+        .ok_or(Error::InvalidData("range: bad length"))?;
+    let table_bytes = ((8usize << order) * core::mem::size_of::<u16>()) as u64;
+    let budget = limits
+        .output_budget(table_bytes)
+        .ok_or(Error::LimitExceeded("range: memory limit exceeded"))?;
+    if len as u64 > budget {
+        return Err(Error::LimitExceeded("range: output limit exceeded"));
+    }
+
+    let mut predictor = Predictor::new(order);
+    let mut dec = RangeDecoder::new(body);
+    // 长度来自数据流，预分配量以编码数据的大小为界。
+    let mut out = Vec::with_capacity(len.min(encoded.len().saturating_mul(8)));
+    for _ in 0..len {
+        let mut byte = 0u8;
+        for _ in 0..8 {
+            let bit = dec.decode_symbol(&predictor.predict());
+            if dec.overrun() {
+                return Err(Error::Truncated("range: truncated data"));
+            }
+            predictor.update(bit);
+            byte = (byte << 1) | bit as u8;
+        }
+        out.push(byte);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_common::selftest::pseudo_random;
+    use encoding_common::test_util::assert_bytes_eq;
+
+    // 由随机选取的单词拼成的文本，字节级 order-0 统计之外还有明显的词内结构。
+    fn word_text(len: usize) -> Vec<u8> {
+        let words: Vec<&str> = "the range coder model context of and bits symbol table to a \
+                                frequency encode decode stream"
+            .split_whitespace()
+            .collect();
+        let picks = pseudo_random(len, 0x5DEE_CE66_D1CE_4E5B);
+        let mut text = Vec::with_capacity(len + 16);
+        for &r in &picks {
+            if text.len() >= len {
+                break;
+            }
+            text.extend_from_slice(words[r as usize % words.len()].as_bytes());
+            text.push(b' ');
+        }
+        text.truncate(len);
+        text
+    }
+
+    #[test]
+    fn cm_roundtrip_and_beats_order0_on_text() {
+        let text = word_text(100_000);
+        for order in [1, 8, DEFAULT_CM_ORDER, MAX_CM_ORDER] {
+            let enc = encode_cm(&text, order).unwrap();
+            assert_eq!(decode_cm(&enc).unwrap(), text, "order {order}");
+        }
+        for data in [&b""[..], b"a", &pseudo_random(5000, 7)] {
+            assert_bytes_eq(data, &decode_cm(&encode_cm(data, 12).unwrap()).unwrap());
+        }
+
+        let cm = encode_cm(&text, DEFAULT_CM_ORDER).unwrap().len();
+        let order0 = crate::encode(&text).unwrap().len();
+        assert!(cm * 2 < order0, "cm {cm} vs order-0 {order0}");
+
+        assert!(matches!(encode_cm(&text, 0), Err(Error::InvalidInput(_))));
+        assert!(matches!(encode_cm(&text, 21), Err(Error::InvalidInput(_))));
+        assert!(matches!(decode_cm(&[0; 8]), Err(Error::Truncated(_))));
+    }
+
+    #[test]
+    fn forged_lengths_are_rejected_without_decoding_padding() {
+        let text = word_text(10_000);
+        let enc = encode_cm(&text, 12).unwrap();
+        let with_len = |len: u64| {
+            let mut forged = enc.clone();
+            forged[5..HEADER_LEN].copy_from_slice(&len.to_le_bytes());
+            forged
+        };
+        assert!(matches!(
+            decode_cm(&with_len(u64::MAX)),
+            Err(Error::InvalidData(_))
+        ));
+        // 长度在数据体允许的范围内：越过数据体末尾即返回，不以补零继续解码。
+        assert!(matches!(
+            decode_cm(&with_len(text.len() as u64 * 100)),
+            Err(Error::Truncated(_))
+        ));
+        assert!(matches!(
+            decode_cm(&enc[..enc.len() - 1]),
+            Err(Error::Truncated(_))
+        ));
+
+        let tables = 2 * (8 << 12);
+        let limits = DecodeLimits::new(tables + text.len() as u64);
+        assert_eq!(decode_cm_with_limits(&enc, &limits).unwrap(), text);
+        let limits = DecodeLimits::new(tables + text.len() as u64 - 1);
+        assert!(matches!(
+            decode_cm_with_limits(&enc, &limits),
+            Err(Error::LimitExceeded(_))
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+// 多路交错编码：若干个逻辑流（如控制字节流与数据字节流）各用自己的静态频率表，
+// 按轮转顺序共用同一个区间编码器——第 i 轮依次编码每个尚未结束的流的第 i 个字节，
+// 解码端按相同顺序选择频率表，两端的重归一化自然同步。
+//
+// 格式：魔数 RCNI + u32 流个数 + 每个流的 u64 长度与 256 个 varint 频率 + 编码数据，整数均为小端。
+// 与 RCNZ 相同按长度解码、不编码 EOF。
+
+use crate::{
+    build_cumulative, histogram, read_varint, scale_frequencies, write_u32_le, write_varint,
+    DecodeLimits, Error, RangeDecoder, RangeEncoder, MAX_TOTAL,
+};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use encoding_common::symbol::read_u32_le;
+
+const INTERLEAVED_MAGIC: &[u8; 4] = b"RCNI";
+const BYTE_SYMBOLS: usize = 256;
+// 每个流解码时频率表与累积表占用的字节数，计入 DecodeLimits。
+const STREAM_TABLE_BYTES: u64 = ((2 * BYTE_SYMBOLS + 1) * core::mem::size_of::<u32>()) as u64;
+
+pub fn encode_interleaved(streams: &[&[u8]]) -> Vec<u8> {
+    let total_len: usize = streams.iter().map(|s| s.len()).sum();
+    let mut out = Vec::with_capacity(8 + streams.len() * 300 + total_len / 2);
+    out.extend_from_slice(INTERLEAVED_MAGIC);
+    write_u32_le(&mut out, streams.len() as u32);
+    let mut tables = Vec::with_capacity(streams.len());
+    for stream in streams {
+        let mut freq = histogram(stream, BYTE_SYMBOLS);
+        scale_frequencies(&mut freq);
+        out.extend_from_slice(&(stream.len() as u64).to_le_bytes());
+        for &f in &freq {
+            write_varint(&mut out, f);
+        }
+        tables.push(build_cumulative(&freq));
+    }
+
+    let rounds = streams.iter().map(|s| s.len()).max().unwrap_or(0);
+    let mut enc = RangeEncoder::new(&mut out);
+    for i in 0..rounds {
+        for (stream, cumulative) in streams.iter().zip(&tables) {
+            if let Some(&b) = stream.get(i) {
+                enc.encode_symbol(b as u32, cumulative);
+            }
+        }
+    }
+    enc.finish();
+    out
+}
+
+pub fn decode_interleaved(encoded: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+    decode_interleaved_with_limits(encoded, &DecodeLimits::unlimited())
+}
+
+// 在 limits 约束下解码：各流的频率表与输出合计超过上限时在解码之前返回 LimitExceeded，
+// 解码越过数据体末尾（长度伪造或数据被截断）时返回 Truncated。
+pub fn decode_interleaved_with_limits(
+    encoded: &[u8],
+    limits: &DecodeLimits,
+) -> Result<Vec<Vec<u8>>, Error> {
+    if encoded.len() < 8 {
+        return Err(Error::Truncated("range: input too short"));
+    }
+    if &encoded[0..4] != INTERLEAVED_MAGIC {
+        return Err(Error::BadMagic("range: bad magic"));
+    }
+    let mut pos = 4;
+    let count =
+        read_u32_le(encoded, &mut pos).ok_or(Error::Truncated("range: truncated header"))?;
+    // 逐个读入流的头部，流个数字段伪造时也会先因数据不足而失败，不会按它预分配。
+    let mut lens = Vec::new();
+    let mut tables = Vec::new();
+    for _ in 0..count {
+        let len = encoded
+            .get(pos..pos + 8)
+            .ok_or(Error::Truncated("range: truncated header"))?;
+        let len = u64::from_le_bytes(len.try_into().unwrap());
+        let len =
+            usize::try_from(len).map_err(|_| Error::LimitExceeded("range: output too large"))?;
+        pos += 8;
+        let freq = (0..BYTE_SYMBOLS)
+            .map(|_| {
+                read_varint(encoded, &mut pos)
+                    .ok_or(Error::Truncated("range: truncated frequencies"))
+            })
+            .collect::<Result<Vec<u32>, Error>>()?;
+        // 空流的频率表全为 0；非空流的总和上限与编码端缩放结果一致。
+        let total: u64 = freq.iter().map(|&f| f as u64).sum();
+        if len > 0 && (total == 0 || total > MAX_TOTAL as u64 + BYTE_SYMBOLS as u64) {
+            return Err(Error::InvalidModel("range: bad frequency total"));
+        }
+        lens.push(len);
+        tables.push(build_cumulative(&freq));
+    }
+    let total_len = lens
+        .iter()
+        .fold(0u64, |total, &len| total.saturating_add(len as u64));
+    let budget = limits
+        .output_budget(STREAM_TABLE_BYTES.saturating_mul(lens.len() as u64))
+        .ok_or(Error::LimitExceeded("range: memory limit exceeded"))?;
+    if total_len > budget {
+        return Err(Error::LimitExceeded("range: output limit exceeded"));
+    }
+
+    let body = &encoded[pos..];
+    let mut streams: Vec<Vec<u8>> = lens
+        .iter()
+        .map(|&len| Vec::with_capacity(len.min(body.len().saturating_mul(8))))
+        .collect();
+    let rounds = lens.iter().copied().max().unwrap_or(0);
+    let mut dec = RangeDecoder::new(body);
+    for i in 0..rounds {
+        for ((out, &len), cumulative) in streams.iter_mut().zip(&lens).zip(&tables) {
+            if i < len {
+                out.push(dec.decode_symbol(cumulative) as u8);
+                if dec.overrun() {
+                    return Err(Error::Truncated("range: truncated data"));
+                }
+            }
+        }
+    }
+    Ok(streams)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_common::selftest::pseudo_random;
+
+    #[test]
+    fn interleaved_streams_roundtrip() {
+        // 控制流只用 4 个取值，数据流为高熵字节，长度不同。
+        let noise = pseudo_random(30_000, 0x1E7E);
+        let control: Vec<u8> = noise[..5000].iter().map(|&b| b & 3).collect();
+        let data = &noise[5000..];
+        let encoded = encode_interleaved(&[&control, data]);
+        assert_eq!(&encoded[..4], b"RCNI");
+        let decoded = decode_interleaved(&encoded).unwrap();
+        assert_eq!(decoded, [control.clone(), data.to_vec()]);
+
+        // 各流使用自己的频率表：控制流约 2 位每字节，合计明显小于单表混合编码。
+        let mixed: Vec<u8> = control.iter().chain(data).copied().collect();
+        assert!(encoded.len() + 500 < crate::encode_sized(&mixed).len());
+
+        let streams: [&[u8]; 4] = [b"", b"abracadabra", &[7u8; 1000], b"z"];
+        let decoded = decode_interleaved(&encode_interleaved(&streams)).unwrap();
+        assert_eq!(decoded, streams);
+        assert!(decode_interleaved(&encode_interleaved(&[]))
+            .unwrap()
+            .is_empty());
+
+        assert!(matches!(
+            decode_interleaved(&encoded[..100]),
+            Err(Error::Truncated(_))
+        ));
+        assert!(matches!(
+            decode_interleaved(&crate::encode_sized(b"bytes")),
+            Err(Error::BadMagic(_))
+        ));
+    }
+
+    #[test]
+    fn forged_stream_lengths_are_bounded() {
+        let encoded = encode_interleaved(&[b"abracadabra", b"simsalabim"]);
+        // 第一个流的长度字段紧跟在 8 字节头部之后。
+        let with_len = |len: u64| {
+            let mut forged = encoded.clone();
+            forged[8..16].copy_from_slice(&len.to_le_bytes());
+            forged
+        };
+        // 不设上限时伪造的长度不会被预分配，解码越过数据体末尾即停止。
+        assert!(matches!(
+            decode_interleaved(&with_len(u64::MAX)),
+            Err(Error::LimitExceeded(_))
+        ));
+        for len in [1 << 40, 1 << 20] {
+            assert!(matches!(
+                decode_interleaved(&with_len(len)),
+                Err(Error::Truncated(_))
+            ));
+        }
+        let limits = DecodeLimits::new(1 << 30);
+        for len in [u64::MAX, 1 << 30] {
+            assert!(matches!(
+                decode_interleaved_with_limits(&with_len(len), &limits),
+                Err(Error::LimitExceeded(_))
+            ));
+        }
+        assert!(matches!(
+            decode_interleaved(&encoded[..encoded.len() - 1]),
+            Err(Error::Truncated(_))
+        ));
+
+        let limits = DecodeLimits::new(2 * STREAM_TABLE_BYTES + 21);
+        assert_eq!(
+            decode_interleaved_with_limits(&encoded, &limits).unwrap(),
+            [b"abracadabra".to_vec(), b"simsalabim".to_vec()]
+        );
+        let limits = DecodeLimits::new(2 * STREAM_TABLE_BYTES + 20);
+        assert!(matches!(
+            decode_interleaved_with_limits(&encoded, &limits),
+            Err(Error::LimitExceeded(_))
+        ));
+    }
+}
@@ -1,24 +1,63 @@
-use std::error::Error;
-use std::fmt;
+// 关闭默认的 std feature 时为 no_std + alloc：内存接口的编解码与各模型可用，
+// 文件、Read / Write 流式接口、多线程编码与检查点格式需要 std。
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+#[cfg(feature = "std")]
+mod checkpoint;
+mod checksum;
+pub mod cm;
+mod interleaved;
+pub mod lz;
+mod model;
+pub mod order1;
+mod symbols;
 
-const SYMBOL_LIMIT: usize = 257;
-const EOF_SYMBOL: usize = SYMBOL_LIMIT - 1;
 const MAX_TOTAL: u32 = 1 << 24;
 const RENORM_THRESHOLD: u32 = 1 << 24;
+const CANCEL_CHECK_INTERVAL: usize = 1 << 16;
 
-#[derive(Debug, Clone)]
-pub struct RangeError(&'static str);
+#[cfg(feature = "std")]
+pub use checkpoint::{
+    decode_checkpointed, decode_checkpointed_with_limits, decode_from_checkpoint, CheckpointEncoder,
+};
+use encoding_common::symbol::{read_u32_le, write_u32_le, EOF_SYMBOL, SYMBOL_LIMIT};
+pub use encoding_common::{DecodeLimits, Error};
+pub use interleaved::{decode_interleaved, encode_interleaved};
+use model::BitModel;
+pub use model::{AdaptiveModel, DecayModel, Model};
+pub use symbols::{decode_symbols, encode_symbols, MAX_ALPHABET};
 
-impl fmt::Display for RangeError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
-    }
-}
+// 旧名称，与 Error 为同一类型。
+pub type RangeError = Error;
 
-impl Error for RangeError {}
+// 频率总和超过 MAX_TOTAL 时的缩放方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScalingMode {
+    // 按比例向下取整，非零频率至少为 1；缩放后总和通常略小于 MAX_TOTAL。
+    #[default]
+    Truncate,
+    // 按比例四舍五入，非零频率至少为 1。
+    RoundNearest,
+    // 四舍五入并把每个符号的舍入误差累积到下一个符号，缩放后总和恰为 MAX_TOTAL。
+    ErrorDiffuse,
+}
 
 fn scale_frequencies(freq: &mut [u32]) {
-    let mut total: u64 = freq.iter().map(|&f| f as u64).sum();
+    scale_frequencies_with(freq, ScalingMode::Truncate);
+}
+
+fn scale_frequencies_with(freq: &mut [u32], mode: ScalingMode) {
+    let total: u64 = freq.iter().map(|&f| f as u64).sum();
     if total == 0 {
         for f in freq.iter_mut() {
             *f = 1;
@@ -28,6 +67,53 @@ fn scale_frequencies(freq: &mut [u32]) {
     if total <= MAX_TOTAL as u64 {
         return;
     }
+    match mode {
+        ScalingMode::Truncate => scale_truncate(freq, total),
+        ScalingMode::RoundNearest => {
+            for f in freq.iter_mut().filter(|f| **f != 0) {
+                let scaled = (*f as u64 * MAX_TOTAL as u64 + total / 2) / total;
+                *f = scaled.max(1) as u32;
+            }
+            settle_total(freq, false);
+        }
+        ScalingMode::ErrorDiffuse => scale_diffuse(freq, total, MAX_TOTAL),
+    }
+}
+
+// 把总和为 total 的非零频率按比例缩放到总和恰为 target（非零频率至少为 1）。
+fn scale_diffuse(freq: &mut [u32], total: u64, target: u32) {
+    // carry 为以 1/total 为单位的累积舍入误差。
+    let mut carry: i128 = 0;
+    for f in freq.iter_mut().filter(|f| **f != 0) {
+        let exact = *f as i128 * target as i128 + carry;
+        let scaled = ((exact + total as i128 / 2).div_euclid(total as i128)).max(1);
+        carry = exact - scaled * total as i128;
+        *f = scaled as u32;
+    }
+    settle_total_to(freq, target, true);
+}
+
+fn settle_total(freq: &mut [u32], exact: bool) {
+    settle_total_to(freq, MAX_TOTAL, exact);
+}
+
+// 舍入与至少为 1 的规则可能使总和偏离 target：超出时从最大的频率中扣除，
+// exact 为 true 时不足部分也补到最大的频率上。最大频率不小于 target / 符号数，足以吸收偏差。
+fn settle_total_to(freq: &mut [u32], target: u32, exact: bool) {
+    let sum: u64 = freq.iter().map(|&f| f as u64).sum();
+    let target = target as u64;
+    if sum == target || (sum < target && !exact) {
+        return;
+    }
+    let (largest, _) = freq
+        .iter()
+        .enumerate()
+        .max_by_key(|&(i, &f)| (f, core::cmp::Reverse(i)))
+        .unwrap();
+    freq[largest] = (freq[largest] as i64 + target as i64 - sum as i64) as u32;
+}
+
+fn scale_truncate(freq: &mut [u32], total: u64) {
     let mut new_total: u64 = 0;
     for f in freq.iter_mut() {
         if *f == 0 {
@@ -51,18 +137,172 @@ fn scale_frequencies(freq: &mut [u32]) {
     }
 }
 
-fn build_frequencies(data: &[u8]) -> Vec<u32> {
-    let mut freq = vec![0u32; SYMBOL_LIMIT];
-    for &b in data {
-        freq[b as usize] += 1;
+// 频率缩放造成的模型失真诊断，只读统计，不影响编码结果。
+// KL 散度需要浮点 log2，诊断与 encode_with_stats 仅在 std 下可用。
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelDiagnostics {
+    // 缩放前后的频率总和。
+    pub original_total: u64,
+    pub scaled_total: u64,
+    // 频率非零的符号个数。
+    pub symbols: usize,
+    // 按比例缩放后为 0、被强制提升为 1 的符号个数及其占比。
+    pub floored: usize,
+    pub floored_fraction: f64,
+    // 缩放前后总和之比（未缩放时为 1.0）。
+    pub total_ratio: f64,
+    // 用缩放后模型编码原分布时每个符号多付出的比特数（KL 散度）。
+    pub kl_bits_per_symbol: f64,
+}
+
+#[cfg(feature = "std")]
+impl ModelDiagnostics {
+    // 超过 1% 的符号被提升为 1，或每符号损失超过 0.01 比特时视为显著失真。
+    pub fn is_significant(&self) -> bool {
+        self.floored_fraction > 0.01 || self.kl_bits_per_symbol > 0.01
+    }
+}
+
+#[cfg(feature = "std")]
+fn diagnose(original: &[u32], scaled: &[u32]) -> ModelDiagnostics {
+    let original_total: u64 = original.iter().map(|&f| f as u64).sum();
+    let scaled_total: u64 = scaled.iter().map(|&f| f as u64).sum();
+    let symbols = original.iter().filter(|&&f| f != 0).count();
+    let floored = if original_total > MAX_TOTAL as u64 {
+        original
+            .iter()
+            .filter(|&&f| f != 0 && (f as u64 * MAX_TOTAL as u64) / original_total == 0)
+            .count()
+    } else {
+        0
+    };
+    let mut kl = 0.0;
+    if original_total > 0 && scaled_total > 0 {
+        for (&o, &q) in original.iter().zip(scaled) {
+            if o == 0 || q == 0 {
+                continue;
+            }
+            let p = o as f64 / original_total as f64;
+            let q = q as f64 / scaled_total as f64;
+            kl += p * (p / q).log2();
+        }
+    }
+    ModelDiagnostics {
+        original_total,
+        scaled_total,
+        symbols,
+        floored,
+        floored_fraction: if symbols == 0 {
+            0.0
+        } else {
+            floored as f64 / symbols as f64
+        },
+        total_ratio: if scaled_total == 0 {
+            1.0
+        } else {
+            original_total as f64 / scaled_total as f64
+        },
+        kl_bits_per_symbol: kl.max(0.0),
     }
+}
+
+// 对一张原始直方图计算缩放诊断。
+#[cfg(feature = "std")]
+pub fn model_diagnostics(freq: &[u32]) -> ModelDiagnostics {
+    let mut scaled = freq.to_vec();
+    scale_frequencies(&mut scaled);
+    diagnose(freq, &scaled)
+}
+
+// 为给定直方图建议频率总和（缩放目标）。
+// 上限：32 位编码器重归一化后区间宽度只保证不小于 RENORM_THRESHOLD，总和超过它时
+// 小概率符号可能分不到一个单位，因此不超过 MAX_TOTAL。
+// 下限：原始总和已能精确表示分布时无需更大的总和，取不小于原始总和（及非零符号数）的 2 的幂。
+pub fn recommend_max_total(freq: &[u32]) -> u32 {
+    let total: u64 = freq.iter().map(|&f| f as u64).sum();
+    let symbols = freq.iter().filter(|&&f| f != 0).count() as u64;
+    let needed = total.max(symbols).max(1).next_power_of_two();
+    needed.min(MAX_TOTAL.min(RENORM_THRESHOLD) as u64) as u32
+}
+
+// 逐项相加两张频率表（饱和加法），用于合并多个来源的直方图。
+// 两表长度不同时，较短的一侧缺失项按 0 处理。
+pub fn merge_frequencies(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|i| {
+            let x = a.get(i).copied().unwrap_or(0);
+            let y = b.get(i).copied().unwrap_or(0);
+            x.saturating_add(y)
+        })
+        .collect()
+}
+
+// 将频率表缩放到编码器可用的总量（不超过 MAX_TOTAL），全零表变为均匀分布。
+pub fn normalize(freq: &mut [u32]) {
+    scale_frequencies(freq);
+}
+
+// 与 normalize 相同，但指定缩放方式。
+pub fn normalize_with(freq: &mut [u32], mode: ScalingMode) {
+    scale_frequencies_with(freq, mode);
+}
+
+fn build_frequencies(data: &[u8]) -> Vec<u32> {
+    build_frequencies_with(data, ScalingMode::Truncate)
+}
+
+fn build_frequencies_with(data: &[u8], mode: ScalingMode) -> Vec<u32> {
+    let mut freq = histogram(data, SYMBOL_LIMIT);
     freq[EOF_SYMBOL] = 1;
+    scale_frequencies_with(&mut freq, mode);
+    freq
+}
+
+// 不为 EOF 预留概率的频率表：只有 256 个字节值，供按长度解码的格式（encode_sized 的 RCNZ）使用，
+// 省去 EOF 占用的概率与末尾编码 EOF 的比特。累积表与解码端同样只有 256 个符号，
+// 不能用于以 EOF 结束的格式（validate_model 会拒绝缺少 EOF 的 257 项表）。
+fn build_frequencies_no_eof(data: &[u8]) -> Vec<u32> {
+    let mut freq = histogram(data, BYTE_SYMBOLS);
     scale_frequencies(&mut freq);
     freq
 }
 
+fn histogram(data: &[u8], symbols: usize) -> Vec<u32> {
+    let mut freq = vec![0u32; symbols];
+    for &b in data {
+        freq[b as usize] = freq[b as usize].saturating_add(1);
+    }
+    freq
+}
+
+// 校验从数据流头部读出的频率表。
+// build_cumulative 对全零表的均匀分布回退只服务于编码端内部，
+// 解码端遇到全零表说明头部被构造或损坏，此时回退会得到与编码端不同的模型，必须报错。
+fn validate_model(freq: &[u32]) -> Result<(), Error> {
+    let total: u64 = freq.iter().map(|&f| f as u64).sum();
+    if total == 0 {
+        return Err(Error::InvalidModel("range: empty frequency table"));
+    }
+    if total > u32::MAX as u64 {
+        return Err(Error::InvalidModel("range: bad frequency total"));
+    }
+    if freq.len() == SYMBOL_LIMIT && freq[EOF_SYMBOL] == 0 {
+        return Err(Error::InvalidModel("range: missing EOF symbol"));
+    }
+    Ok(())
+}
+
 fn build_cumulative(freq: &[u32]) -> Vec<u32> {
-    let mut cumulative = vec![0u32; freq.len() + 1];
+    let mut cumulative = Vec::new();
+    build_cumulative_into(freq, &mut cumulative);
+    cumulative
+}
+
+fn build_cumulative_into(freq: &[u32], cumulative: &mut Vec<u32>) {
+    cumulative.clear();
+    cumulative.resize(freq.len() + 1, 0);
     for (i, &f) in freq.iter().enumerate() {
         cumulative[i + 1] = cumulative[i] + f;
     }
@@ -73,26 +313,6 @@ fn build_cumulative(freq: &[u32]) -> Vec<u32> {
             }
         }
     }
-    cumulative
-}
-
-fn write_u32_le(out: &mut Vec<u8>, v: u32) {
-    out.push((v & 0xFF) as u8);
-    out.push(((v >> 8) & 0xFF) as u8);
-    out.push(((v >> 16) & 0xFF) as u8);
-    out.push(((v >> 24) & 0xFF) as u8);
-}
-
-fn read_u32_le(input: &[u8], pos: &mut usize) -> Option<u32> {
-    if *pos + 4 > input.len() {
-        return None;
-    }
-    let v = (input[*pos] as u32)
-        | ((input[*pos + 1] as u32) << 8)
-        | ((input[*pos + 2] as u32) << 16)
-        | ((input[*pos + 3] as u32) << 24);
-    *pos += 4;
-    Some(v)
 }
 
 fn write_header(out: &mut Vec<u8>, freq: &[u32]) {
@@ -103,66 +323,138 @@ fn write_header(out: &mut Vec<u8>, freq: &[u32]) {
     }
 }
 
-fn read_header(input: &[u8], pos: &mut usize) -> Result<Vec<u32>, RangeError> {
+fn read_header(input: &[u8], pos: &mut usize) -> Result<Vec<u32>, Error> {
+    let mut freq = Vec::new();
+    read_header_into(input, pos, &mut freq)?;
+    Ok(freq)
+}
+
+fn read_header_into(input: &[u8], pos: &mut usize, freq: &mut Vec<u32>) -> Result<(), Error> {
     if input.len() < 8 {
-        return Err(RangeError("range: input too short"));
+        return Err(Error::Truncated("range: input too short"));
     }
     if &input[0..4] != b"RCNC" {
-        return Err(RangeError("range: bad magic"));
+        return Err(Error::BadMagic("range: bad magic"));
     }
     *pos = 4;
-    let count = read_u32_le(input, pos).ok_or(RangeError("range: truncated header"))?;
+    let count = read_u32_le(input, pos).ok_or(Error::Truncated("range: truncated header"))?;
     if count == 0 || count > 1024 {
-        return Err(RangeError("range: bad symbol count"));
+        return Err(Error::InvalidModel("range: bad symbol count"));
     }
-    let mut freq = Vec::with_capacity(count as usize);
+    freq.clear();
     for _ in 0..count {
-        let v = read_u32_le(input, pos).ok_or(RangeError("range: truncated frequencies"))?;
+        let v = read_u32_le(input, pos).ok_or(Error::Truncated("range: truncated frequencies"))?;
         freq.push(v);
     }
-    Ok(freq)
+    Ok(())
+}
+
+// 编码器的输出端。std 下为任意 Write：编码到内存时为 &mut Vec<u8>，流式编码时为带缓冲的 writer；
+// no_std 下只有 &mut Vec<u8>。
+trait ByteSink {
+    fn put(&mut self, byte: u8) -> Result<(), Error>;
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ByteSink for W {
+    fn put(&mut self, byte: u8) -> Result<(), Error> {
+        self.write_all(&[byte]).map_err(Error::Io)
+    }
 }
 
-struct RangeEncoder<'a> {
+#[cfg(not(feature = "std"))]
+impl ByteSink for &mut Vec<u8> {
+    fn put(&mut self, byte: u8) -> Result<(), Error> {
+        self.push(byte);
+        Ok(())
+    }
+}
+
+// 重归一化移出的字节随即写给输出端。
+struct RangeEncoder<W: ByteSink> {
     low: u32,
     high: u32,
-    out: &'a mut Vec<u8>,
+    out: W,
+    // 第一次写出失败的错误，由 take_error 取出；写入 Vec 时不会出现。
+    error: Option<Error>,
 }
 
-impl<'a> RangeEncoder<'a> {
-    fn new(out: &'a mut Vec<u8>) -> Self {
+impl<W: ByteSink> RangeEncoder<W> {
+    fn new(out: W) -> Self {
+        RangeEncoder::resume(out, 0, 0xFFFF_FFFF)
+    }
+
+    // 从之前保存的区间状态继续编码。
+    fn resume(out: W, low: u32, high: u32) -> Self {
         RangeEncoder {
-            low: 0,
-            high: 0xFFFF_FFFF,
+            low,
+            high,
             out,
+            error: None,
+        }
+    }
+
+    fn emit(&mut self, byte: u8) {
+        if let Err(e) = self.out.put(byte) {
+            self.error.get_or_insert(e);
         }
     }
 
+    // 只有流式接口的 writer 会写出失败。
+    #[cfg(feature = "std")]
+    fn take_error(&mut self) -> Result<(), Error> {
+        self.error.take().map_or(Ok(()), Err)
+    }
+
     fn encode_symbol(&mut self, symbol: u32, cumulative: &[u32]) {
-        let range = (self.high as u64).wrapping_sub(self.low as u64) + 1;
         let total = *cumulative.last().unwrap() as u64;
+        self.fit(total);
+        let range = (self.high as u64) - (self.low as u64) + 1;
         let sym_low = cumulative[symbol as usize] as u64;
         let sym_high = cumulative[symbol as usize + 1] as u64;
 
-        self.high = self
-            .low
-            .wrapping_add(((range * sym_high) / total - 1) as u32);
-        self.low = self
-            .low
-            .wrapping_add(((range * sym_low) / total) as u32);
+        // 新区间是 [low, high] 的子区间，low + range - 1 = high 不超过 u32::MAX，加法不会进位：
+        // 区间跨越字节边界且宽度不足时由 fit 收窄到边界一侧（Subbotin 式），而不是等待进位，
+        // 已写出的字节之后不会再被修改，因此不需要 cache 字节与待定 0xFF 计数。
+        self.high = self.low + ((range * sym_high) / total - 1) as u32;
+        self.low += ((range * sym_low) / total) as u32;
+        // fit 保证 range >= total，频率不小于 1 的符号至少分到 floor(range / total) >= 1 的宽度，
+        // 因此 high - 1 之后区间也不会倒置。
+        debug_assert!(self.low <= self.high, "range: inverted interval");
+        self.renormalize();
+    }
 
+    fn renormalize(&mut self) {
         while (self.low ^ self.high) < RENORM_THRESHOLD {
-            let byte = (self.low >> 24) as u8;
-            self.out.push(byte);
+            self.emit((self.low >> 24) as u8);
             self.low <<= 8;
             self.high = (self.high << 8) | 0xFF;
         }
     }
 
+    // 区间跨越最高字节边界时宽度可能小于模型总和，此时频率为 1 的符号会分不到宽度，
+    // 区间随之倒置。编码前若宽度不足，保留边界两侧中较宽的一侧并移出字节，
+    // 直到宽度不小于 total。解码端在同一位置做相同的调整。
+    fn fit(&mut self, total: u64) {
+        while (self.high - self.low) as u64 + 1 < total {
+            let boundary = self.high & !(RENORM_THRESHOLD - 1);
+            if boundary - self.low >= self.high - boundary {
+                self.high = boundary - 1;
+            } else {
+                self.low = boundary;
+            }
+            self.renormalize();
+        }
+    }
+
+    // 以均匀分布编码 bits 位的 value（bits <= 24），无需构造完整的累积表。
+    fn encode_uniform(&mut self, value: u32, bits: u32) {
+        self.encode_symbol(0, &[value, value + 1, 1 << bits]);
+    }
+
     fn finish(&mut self) {
         for _ in 0..4 {
-            let byte = (self.low >> 24) as u8;
-            self.out.push(byte);
+            self.emit((self.low >> 24) as u8);
             self.low <<= 8;
         }
     }
@@ -172,8 +464,14 @@ struct RangeDecoder<'a> {
     low: u32,
     high: u32,
     code: u32,
+    // 与 code 相同，但输入耗尽后补 0xFF 而非 0x00。
+    // 真实码值必然落在 [code, code_max] 之间，用于判断截断流中的符号是否确定。
+    code_max: u32,
     data: &'a [u8],
     pos: usize,
+    // 是否已在输入耗尽后补过字节。编码端 finish 写出的字节恰好等于解码端移入的字节数，
+    // 完整的数据流解码到最后一个符号也不会越过末尾，按长度解码的格式据此识别截断或伪造的长度。
+    overrun: bool,
 }
 
 impl<'a> RangeDecoder<'a> {
@@ -182,31 +480,64 @@ impl<'a> RangeDecoder<'a> {
             low: 0,
             high: 0xFFFF_FFFF,
             code: 0,
+            code_max: 0,
             data,
             pos: 0,
+            overrun: false,
         };
         for _ in 0..4 {
-            let b = dec.read_byte() as u32;
-            dec.code = (dec.code << 8) | b;
+            dec.shift_in();
         }
         dec
     }
 
-    fn read_byte(&mut self) -> u8 {
+    fn shift_in(&mut self) {
         if self.pos < self.data.len() {
-            let b = self.data[self.pos];
+            let b = self.data[self.pos] as u32;
             self.pos += 1;
-            b
+            self.code = (self.code << 8) | b;
+            self.code_max = (self.code_max << 8) | b;
         } else {
-            0
+            self.code <<= 8;
+            self.code_max = (self.code_max << 8) | 0xFF;
+            self.overrun = true;
         }
     }
 
-    fn decode_symbol(&mut self, cumulative: &[u32]) -> u32 {
-        let range = (self.high as u64).wrapping_sub(self.low as u64) + 1;
-        let total = *cumulative.last().unwrap() as u64;
-        let offset = (self.code as u64).wrapping_sub(self.low as u64);
-        let value = ((offset + 1) * total - 1) / range;
+    fn overrun(&self) -> bool {
+        self.overrun
+    }
+
+    fn renormalize(&mut self) {
+        while (self.low ^ self.high) < RENORM_THRESHOLD {
+            self.low <<= 8;
+            self.high = (self.high << 8) | 0xFF;
+            self.shift_in();
+        }
+    }
+
+    // 与 RangeEncoder::fit 对应。
+    fn fit(&mut self, total: u64) {
+        while (self.high - self.low) as u64 + 1 < total {
+            let boundary = self.high & !(RENORM_THRESHOLD - 1);
+            if boundary - self.low >= self.high - boundary {
+                self.high = boundary - 1;
+            } else {
+                self.low = boundary;
+            }
+            self.renormalize();
+        }
+    }
+
+    // code 在当前区间内对应的频率刻度值，位于 [0, total)。
+    fn scaled_value(&self, code: u32, total: u64) -> u64 {
+        let range = (self.high as u64) - (self.low as u64) + 1;
+        let offset = code.wrapping_sub(self.low) as u64;
+        ((offset + 1) * total - 1) / range
+    }
+
+    fn lookup(&self, code: u32, cumulative: &[u32]) -> u32 {
+        let value = self.scaled_value(code, *cumulative.last().unwrap() as u64);
 
         let mut lo: u32 = 0;
         let mut hi: u32 = cumulative.len() as u32 - 1;
@@ -218,8 +549,14 @@ impl<'a> RangeDecoder<'a> {
                 lo = mid;
             }
         }
-        let symbol = lo;
+        lo
+    }
 
+    // 与编码端相同的区间划分；损坏的数据流可能让 decode_uniform 得到超出 total 的值，
+    // 这里用回绕加法而不是 panic，错误由上层的 EOF / 长度检查发现。
+    fn update(&mut self, symbol: u32, cumulative: &[u32]) {
+        let range = (self.high as u64) - (self.low as u64) + 1;
+        let total = *cumulative.last().unwrap() as u64;
         let sym_low = cumulative[symbol as usize] as u64;
         let sym_high = cumulative[symbol as usize + 1] as u64;
 
@@ -229,83 +566,4050 @@ impl<'a> RangeDecoder<'a> {
         self.low = self
             .low
             .wrapping_add(((range * sym_low) / total) as u32);
+        self.renormalize();
+    }
 
-        while (self.low ^ self.high) < RENORM_THRESHOLD {
-            self.low <<= 8;
-            self.high = (self.high << 8) | 0xFF;
-            let b = self.read_byte() as u32;
-            self.code = (self.code << 8) | b;
-        }
+    fn decode_symbol(&mut self, cumulative: &[u32]) -> u32 {
+        self.fit(*cumulative.last().unwrap() as u64);
+        let symbol = self.lookup(self.code, cumulative);
+        self.update(symbol, cumulative);
+        symbol
+    }
+
+    // 与 RangeEncoder::encode_uniform 对应。
+    fn decode_uniform(&mut self, bits: u32) -> u32 {
+        let total = 1u32 << bits;
+        self.fit(total as u64);
+        let value = self.scaled_value(self.code, total as u64) as u32;
+        self.update(0, &[value, value + 1, total]);
+        value
+    }
 
+    // 与 decode_symbol 结果相同，但用 table 代替二分查找。
+    fn decode_symbol_table(&mut self, cumulative: &[u32], table: &SymbolTable) -> u32 {
+        let total = *cumulative.last().unwrap() as u64;
+        self.fit(total);
+        let symbol = table.find(self.scaled_value(self.code, total), cumulative);
+        self.update(symbol, cumulative);
         symbol
     }
+
+    // 仅当无论被截断的字节取何值都会解出同一符号时才返回该符号。
+    fn decode_certain_symbol(&mut self, cumulative: &[u32]) -> Option<u32> {
+        self.fit(*cumulative.last().unwrap() as u64);
+        let lo = self.lookup(self.code.clamp(self.low, self.high), cumulative);
+        let hi = self.lookup(self.code_max.clamp(self.low, self.high), cumulative);
+        if lo != hi {
+            return None;
+        }
+        self.update(lo, cumulative);
+        Some(lo)
+    }
+}
+
+// 解码查找表的桶数（以 2 为底的对数）。
+const TABLE_BITS: u32 = 12;
+
+// 解码查找表：把 [0, total) 均分为 2^TABLE_BITS 个桶，每个桶记录其起点所在的符号，
+// 查找时从该符号向后线性扫描，通常一两步即可命中，代替 O(log n) 的二分查找。
+// 表为 2^TABLE_BITS 个 u16（8 KiB），与字母表大小无关。
+struct SymbolTable {
+    start: Vec<u16>,
+    total: u64,
+}
+
+impl SymbolTable {
+    fn new(cumulative: &[u32]) -> SymbolTable {
+        let total = *cumulative.last().unwrap() as u64;
+        let mut start = Vec::with_capacity(1 << TABLE_BITS);
+        let mut symbol = 0;
+        for bucket in 0..1u64 << TABLE_BITS {
+            let value = (bucket * total) >> TABLE_BITS;
+            while cumulative[symbol + 1] as u64 <= value {
+                symbol += 1;
+            }
+            start.push(symbol as u16);
+        }
+        SymbolTable { start, total }
+    }
+
+    // 满足 cumulative[s] <= value < cumulative[s + 1] 的符号 s。
+    fn find(&self, value: u64, cumulative: &[u32]) -> u32 {
+        let bucket = ((value << TABLE_BITS) / self.total) as usize;
+        let mut symbol = self.start[bucket] as usize;
+        while cumulative[symbol + 1] as u64 <= value {
+            symbol += 1;
+        }
+        symbol as u32
+    }
+}
+
+// 输出只取决于输入字节，与输入缓冲区的容量、来源与对齐方式无关，可用于内容寻址存储。
+pub fn encode(input: &[u8]) -> Result<Vec<u8>, Error> {
+    encode_cancellable(input, || false)
+}
+
+// 与 encode 相同，但每编码 CANCEL_CHECK_INTERVAL 个符号检查一次 should_cancel，
+// 返回 true 时中止编码并返回 "range: cancelled"。
+pub fn encode_cancellable(
+    input: &[u8],
+    should_cancel: impl Fn() -> bool,
+) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::new();
+    encode_cancellable_into(input, should_cancel, &mut out)?;
+    Ok(out)
+}
+
+// 与 encode 相同，但结果写入调用方的 out（先清空，保留其容量），
+// 循环中编码大量小消息时可复用同一个输出缓冲区。
+pub fn encode_into(input: &[u8], out: &mut Vec<u8>) -> Result<(), Error> {
+    out.clear();
+    encode_cancellable_into(input, || false, out)
 }
 
-pub fn encode(input: &[u8]) -> Result<Vec<u8>, RangeError> {
+// 编码结果追加到 out 之后。
+fn encode_cancellable_into(
+    input: &[u8],
+    should_cancel: impl Fn() -> bool,
+    out: &mut Vec<u8>,
+) -> Result<(), Error> {
+    if let Some(value) = single_byte(input) {
+        out.extend_from_slice(&encode_single(value, input.len() as u64));
+        return Ok(());
+    }
     let freq = build_frequencies(input);
     let cumulative = build_cumulative(&freq);
 
-    let mut out = Vec::with_capacity(input.len());
-    write_header(&mut out, &freq);
+    out.reserve(input.len());
+    write_header(out, &freq);
+    encode_body(out, input, &cumulative, should_cancel)
+}
 
-    {
-        let mut enc = RangeEncoder::new(&mut out);
-        for &b in input {
-            enc.encode_symbol(b as u32, &cumulative);
+fn encode_body(
+    out: &mut Vec<u8>,
+    input: &[u8],
+    cumulative: &[u32],
+    should_cancel: impl Fn() -> bool,
+) -> Result<(), Error> {
+    let mut enc = RangeEncoder::new(out);
+    for chunk in input.chunks(CANCEL_CHECK_INTERVAL) {
+        if should_cancel() {
+            return Err(Error::Cancelled("range: cancelled"));
+        }
+        for &b in chunk {
+            enc.encode_symbol(b as u32, cumulative);
         }
-        enc.encode_symbol(EOF_SYMBOL as u32, &cumulative);
-        enc.finish();
     }
+    // EOF 总是模型中的最后一个符号。
+    enc.encode_symbol((cumulative.len() - 2) as u32, cumulative);
+    enc.finish();
+    Ok(())
+}
+
+pub fn decode(encoded: &[u8]) -> Result<Vec<u8>, Error> {
+    decode_with_limit(encoded, usize::MAX)
+}
+
+// 编码后立即逐字节解码并与 input 比较，第一个不一致处即返回 Ok(false)，不分配完整的输出；
+// 用于模糊测试与验证自定义模型。编码本身失败时返回 Err。
+pub fn verify(input: &[u8]) -> Result<bool, Error> {
+    let encoded = encode(input)?;
+    Ok(DecodeIter::new(&encoded)?.eq(input.iter().copied()))
+}
+
+// 与 decode 相同，但结果写入调用方的 out（先清空，保留其容量）；
+// 需要同时复用模型表时使用 RangeCoder::decode_reuse。
+pub fn decode_into(encoded: &[u8], out: &mut Vec<u8>) -> Result<(), Error> {
+    RangeCoder::new().decode_reuse(encoded, out)
+}
+
+// 解码不可信输入时使用：输出将超过 max_output 字节时立即返回
+// "range: output limit exceeded"，构造的或损坏的数据流不会无限制地分配内存。
+pub fn decode_with_limit(encoded: &[u8], max_output: usize) -> Result<Vec<u8>, Error> {
+    decode_with_options(encoded, &DecodeOptions::new().with_max_output(max_output))
+}
 
+fn decode_body(data: &[u8], cumulative: &[u32], opts: &DecodeOptions) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::with_capacity(data.len().min(opts.max_output));
+    decode_body_into(data, cumulative, opts, &mut out)?;
     Ok(out)
 }
 
-pub fn decode(encoded: &[u8]) -> Result<Vec<u8>, RangeError> {
-    let mut pos: usize = 0;
-    let freq = read_header(encoded, &mut pos)?;
-    if freq.len() != SYMBOL_LIMIT {
-        return Err(RangeError("range: unexpected symbol count"));
+// 解码结果追加到 out 之后。
+fn decode_body_into(
+    data: &[u8],
+    cumulative: &[u32],
+    opts: &DecodeOptions,
+    out: &mut Vec<u8>,
+) -> Result<(), Error> {
+    if data.is_empty() {
+        return Ok(());
     }
-    let cumulative = build_cumulative(&freq);
 
-    if pos >= encoded.len() {
-        return Ok(Vec::new());
+    let max_output = opts.max_output.saturating_add(out.len());
+    let mut body = BodyDecoder::new(data, cumulative, opts);
+    while let Some(b) = body.next_byte(cumulative) {
+        if out.len() >= max_output {
+            return Err(Error::LimitExceeded("range: output limit exceeded"));
+        }
+        out.push(b);
     }
 
-    let mut dec = RangeDecoder::new(&encoded[pos..]);
-    let mut out = Vec::with_capacity(encoded.len());
-    loop {
-        let sym = dec.decode_symbol(&cumulative);
-        if sym as usize == EOF_SYMBOL {
-            break;
+    Ok(())
+}
+
+// 数据体的逐符号解码状态，解到 EOF 之前可以随时暂停，之后用同一累积表继续。
+struct BodyDecoder<'a> {
+    dec: RangeDecoder<'a>,
+    table: Option<SymbolTable>,
+    eof: usize,
+}
+
+impl<'a> BodyDecoder<'a> {
+    fn new(data: &'a [u8], cumulative: &[u32], opts: &DecodeOptions) -> BodyDecoder<'a> {
+        BodyDecoder {
+            dec: RangeDecoder::new(data),
+            table: opts.lookup_table.then(|| SymbolTable::new(cumulative)),
+            eof: cumulative.len() - 2,
         }
-        out.push(sym as u8);
     }
 
-    Ok(out)
+    // 解出下一个字节；遇到 EOF 时返回 None。
+    fn next_byte(&mut self, cumulative: &[u32]) -> Option<u8> {
+        let sym = match &self.table {
+            Some(table) => self.dec.decode_symbol_table(cumulative, table),
+            None => self.dec.decode_symbol(cumulative),
+        };
+        (sym as usize != self.eof).then_some(sym as u8)
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+// 分多次解码的状态，见 decode_budgeted。
+pub struct DecodeState<'a> {
+    cumulative: Vec<u32>,
+    source: BudgetedSource<'a>,
+}
 
-    #[test]
-    fn roundtrip_empty() {
-        let data: Vec<u8> = Vec::new();
-        let enc = encode(&data).unwrap();
-        let dec = decode(&enc).unwrap();
-        assert_eq!(dec, data);
+enum BudgetedSource<'a> {
+    Body(BodyDecoder<'a>),
+    // RCNS：剩余的重复次数。
+    Repeat(u8, u64),
+    Done,
+}
+
+// decode_budgeted 的返回值。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeProgress {
+    // 预算用完，流尚未结束，需要再次调用。
+    Pending,
+    // 已解码到流末尾，之后的调用不再产生输出。
+    Done,
+}
+
+impl<'a> DecodeState<'a> {
+    // 解析头部并准备解码。支持静态模型格式 RCNC / RCNV / RCNQ / RCNS，其他格式返回 InvalidInput。
+    pub fn new(encoded: &'a [u8]) -> Result<DecodeState<'a>, Error> {
+        if encoded.len() >= 4 && &encoded[0..4] == SINGLE_MAGIC {
+            let (value, len) = read_single_header(encoded)?;
+            return Ok(DecodeState {
+                cumulative: Vec::new(),
+                source: BudgetedSource::Repeat(value, len),
+            });
+        }
+        let mut pos = 0;
+        let freq = match encoded.get(0..4) {
+            Some(magic) if magic == FIXED16_MAGIC => read_fixed16_header(encoded, &mut pos)?,
+            Some(magic) if magic == SPARSE_MAGIC => read_sparse_header(encoded, &mut pos)?,
+            Some(magic) if magic == SPARSE16_MAGIC => read_sparse16_header(encoded, &mut pos)?,
+            Some(magic) if magic == CAPPED16_MAGIC => read_u16_table(encoded, &mut pos)?,
+            Some(magic)
+                if magic == DENSE_MAGIC || magic == FRAME_MAGIC || magic == ADAPTIVE_MAGIC =>
+            {
+                return Err(Error::InvalidInput(
+                    "range: format not supported by budgeted decode",
+                ));
+            }
+            _ => read_header(encoded, &mut pos)?,
+        };
+        if freq.len() != SYMBOL_LIMIT {
+            return Err(Error::InvalidModel("range: unexpected symbol count"));
+        }
+        validate_model(&freq)?;
+        let cumulative = build_cumulative(&freq);
+        let source = match &encoded[pos..] {
+            [] => BudgetedSource::Done,
+            body => {
+                BudgetedSource::Body(BodyDecoder::new(body, &cumulative, &DecodeOptions::new()))
+            }
+        };
+        Ok(DecodeState { cumulative, source })
     }
 
-    #[test]
-    fn roundtrip_random() {
-        use rand::RngCore;
-        use rand::SeedableRng;
-        let mut data = vec![0u8; 10000];
-        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
-        rng.fill_bytes(&mut data);
-        let enc = encode(&data).unwrap();
-        let dec = decode(&enc).unwrap();
-        assert_eq!(dec, data);
+    // 解出下一个字节；流结束后始终返回 None。
+    fn next_byte(&mut self) -> Option<u8> {
+        let next = match &mut self.source {
+            BudgetedSource::Body(body) => body.next_byte(&self.cumulative),
+            BudgetedSource::Repeat(_, 0) | BudgetedSource::Done => None,
+            BudgetedSource::Repeat(value, remaining) => {
+                *remaining -= 1;
+                Some(*value)
+            }
+        };
+        if next.is_none() {
+            self.source = BudgetedSource::Done;
+        }
+        next
+    }
+}
+
+// 逐字节拉取解码结果的迭代器，不缓存整个输出，适合流式消费。
+// 支持的格式同 DecodeState::new；解到 EOF 后一直返回 None。
+pub struct DecodeIter<'a> {
+    state: DecodeState<'a>,
+}
+
+impl<'a> DecodeIter<'a> {
+    pub fn new(encoded: &'a [u8]) -> Result<DecodeIter<'a>, Error> {
+        Ok(DecodeIter {
+            state: DecodeState::new(encoded)?,
+        })
+    }
+}
+
+impl Iterator for DecodeIter<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        self.state.next_byte()
+    }
+}
+
+impl core::iter::FusedIterator for DecodeIter<'_> {}
+
+// 最多解出 max_bytes 个字节追加到 out 后返回，解码状态保留在 state 中，
+// 适合协作式调度：每次调用的耗时有上界，不会长时间阻塞事件循环。
+pub fn decode_budgeted(
+    state: &mut DecodeState,
+    max_bytes: usize,
+    out: &mut Vec<u8>,
+) -> DecodeProgress {
+    for _ in 0..max_bytes {
+        match state.next_byte() {
+            Some(b) => out.push(b),
+            None => return DecodeProgress::Done,
+        }
+    }
+    match state.source {
+        BudgetedSource::Done | BudgetedSource::Repeat(_, 0) => DecodeProgress::Done,
+        _ => DecodeProgress::Pending,
+    }
+}
+
+// 数据流的模型头部格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FormatVersion {
+    // 魔数 RCNC + 完整的 u32 频率表，与 Go/C++ 实现兼容。
+    #[default]
+    Classic,
+    // 魔数 RCNV + 仅保存非零频率的 (symbol, freq) 对，均为 LEB128 varint。
+    SparseVarint,
+    // 魔数 RCN2 + u16 出现符号数 + (u8 字节, u16 频率) 对 + u16 EOF 频率，定长字段便于其他实现解析。
+    // 频率超过 u16 时先按比例缩放到总和 2^16。
+    Sparse16,
+    // 魔数 RCNW + 257 个 u16 小端频率：按 Classic 方式缩放后再把单个符号的频率截到 65535，
+    // 头部只有 RCNC 的一半，而模型总和仍可达 MAX_TOTAL，精度高于 Fixed16。
+    Capped16,
+    // 魔数 RCNM + 稠密字母表：只为输入中出现的字节（加 EOF）建模，
+    // 头部保存出现字节的有序列表与对应频率，数据体编码的是稠密下标。
+    DenseRemap,
+    // 魔数 RCNQ + 257 个 16 位小端定点概率（总和恰为 2^16），
+    // 便于与以 16 位定点数表示概率的实现逐位互通。
+    Fixed16,
+    // 魔数 RCNS + 取值 + 重复次数，只适用于由同一字节重复组成的非空输入。
+    // 其余格式遇到这类输入时都会自动改用它；显式指定时输入必须满足该条件。
+    SingleByte,
+}
+
+// 外层容器中完整性校验所用的算法。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChecksumKind {
+    #[default]
+    Crc32,
+    // 计算更快，但检错能力弱于 CRC32，适合吞吐优先的场景。
+    Adler32,
+}
+
+impl ChecksumKind {
+    pub fn compute(self, data: &[u8]) -> u32 {
+        match self {
+            ChecksumKind::Crc32 => checksum::crc32(data),
+            ChecksumKind::Adler32 => checksum::adler32(data),
+        }
+    }
+}
+
+// encode_with_options 的配置，按 builder 方式组合：
+// EncodeOptions::new().with_crc(true).with_stored_fallback(true).format(FormatVersion::SparseVarint)
+#[derive(Debug, Clone, Default)]
+pub struct EncodeOptions {
+    format: FormatVersion,
+    crc: bool,
+    checksum: ChecksumKind,
+    stored_fallback: bool,
+    error_on_expansion: bool,
+    length: bool,
+    metadata: Option<Vec<u8>>,
+    scaling: ScalingMode,
+}
+
+impl EncodeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 在外层容器中保存原始数据的 CRC32，解码时校验。
+    pub fn with_crc(mut self, enabled: bool) -> Self {
+        self.crc = enabled;
+        self
+    }
+
+    // 启用完整性校验并指定算法；with_crc(true) 等同于 with_checksum(ChecksumKind::Crc32)。
+    pub fn with_checksum(mut self, kind: ChecksumKind) -> Self {
+        self.crc = true;
+        self.checksum = kind;
+        self
+    }
+
+    // 编码结果不小于原始数据时改为直接存储原始字节。
+    pub fn with_stored_fallback(mut self, enabled: bool) -> Self {
+        self.stored_fallback = enabled;
+        self
+    }
+
+    // 编码结果（不含外层容器）大于原始数据时返回 LimitExceeded，由调用方自行决定如何存储；
+    // 同时启用 with_stored_fallback 时以存储回退为准，不会报错。
+    pub fn with_error_on_expansion(mut self, enabled: bool) -> Self {
+        self.error_on_expansion = enabled;
+        self
+    }
+
+    // 在外层容器中保存原始数据长度，配合 with_crc 可由 inspect 直接列出而无需解码。
+    pub fn with_length(mut self, enabled: bool) -> Self {
+        self.length = enabled;
+        self
+    }
+
+    pub fn format(mut self, format: FormatVersion) -> Self {
+        self.format = format;
+        self
+    }
+
+    // 输入超过 MAX_TOTAL 字节、频率表需要缩放时使用的缩放方式；解码端无需知道。
+    pub fn scaling(mut self, mode: ScalingMode) -> Self {
+        self.scaling = mode;
+        self
+    }
+
+    // 在外层容器中附带一段用户元数据（如时间戳、内容类型），见 read_metadata。
+    pub fn with_metadata(mut self, metadata: &[u8]) -> Self {
+        self.metadata = Some(metadata.to_vec());
+        self
+    }
+}
+
+// decode_with_options 的配置。
+#[derive(Debug, Clone)]
+pub struct DecodeOptions {
+    max_output: usize,
+    lookup_table: bool,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        DecodeOptions {
+            max_output: usize::MAX,
+            lookup_table: false,
+        }
+    }
+}
+
+impl DecodeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 解码输出超过 max_output 字节时返回 "range: output limit exceeded"。
+    pub fn with_max_output(mut self, max_output: usize) -> Self {
+        self.max_output = max_output;
+        self
+    }
+
+    // 用 8 KiB 的桶查找表代替二分查找来定位符号，结果相同；
+    // 完整字母表上收益最大，小消息上建表的开销可能超过收益。
+    pub fn with_lookup_table(mut self, enabled: bool) -> Self {
+        self.lookup_table = enabled;
+        self
+    }
+}
+
+const SPARSE_MAGIC: &[u8; 4] = b"RCNV";
+// 定长稀疏头部：魔数 RCN2 + 2 字节小端出现符号数 n（含 EOF，1..=SYMBOL_LIMIT）
+// + n - 1 个 (1 字节字节值, 2 字节小端频率) 对（字节值严格递增）+ 2 字节小端 EOF 频率，频率均非零。
+const SPARSE16_MAGIC: &[u8; 4] = b"RCN2";
+// 单字节格式：魔数 RCNS + 1 字节取值 + 8 字节小端重复次数，共 13 字节。
+const SINGLE_MAGIC: &[u8; 4] = b"RCNS";
+// 16 位定点概率：魔数 RCNQ + SYMBOL_LIMIT 个 u16 小端概率，总和恰为 FIXED16_TOTAL。
+const FIXED16_MAGIC: &[u8; 4] = b"RCNQ";
+// u16 频率表：魔数 RCNW + SYMBOL_LIMIT 个 u16 小端频率，总和不超过 MAX_TOTAL。
+const CAPPED16_MAGIC: &[u8; 4] = b"RCNW";
+const FIXED16_TOTAL: u32 = 1 << 16;
+// 稠密字母表：魔数 RCNM + varint 字节个数 n + n 个严格递增的字节 + n+1 个 varint 频率（最后一个为 EOF）。
+const DENSE_MAGIC: &[u8; 4] = b"RCNM";
+
+// 外层容器：魔数 RCNF + 1 字节标志 + [4 字节元数据长度 + 元数据] + [4 字节原始数据校验和]
+// + [8 字节小端原始数据长度] + 内层数据流或原始字节。
+// 校验和默认为 CRC32，同时设置 FLAG_ADLER 时为 Adler-32。
+const FRAME_MAGIC: &[u8; 4] = b"RCNF";
+const FLAG_CRC: u8 = 0x01;
+const FLAG_STORED: u8 = 0x02;
+const FLAG_METADATA: u8 = 0x04;
+const FLAG_LENGTH: u8 = 0x08;
+const FLAG_ADLER: u8 = 0x10;
+
+fn write_varint(out: &mut Vec<u8>, mut v: u32) {
+    while v >= 0x80 {
+        out.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn read_varint(input: &[u8], pos: &mut usize) -> Option<u32> {
+    let mut v: u32 = 0;
+    for shift in (0..35).step_by(7) {
+        let b = *input.get(*pos)?;
+        *pos += 1;
+        if shift == 28 && b > 0x0F {
+            return None;
+        }
+        v |= ((b & 0x7F) as u32) << shift;
+        if b & 0x80 == 0 {
+            return Some(v);
+        }
+    }
+    None
+}
+
+fn write_sparse_header(out: &mut Vec<u8>, freq: &[u32]) {
+    out.extend_from_slice(SPARSE_MAGIC);
+    write_sparse_pairs(out, freq);
+}
+
+fn write_sparse_pairs(out: &mut Vec<u8>, freq: &[u32]) {
+    let present = freq.iter().filter(|&&f| f != 0).count();
+    write_varint(out, present as u32);
+    for (symbol, &f) in freq.iter().enumerate() {
+        if f != 0 {
+            write_varint(out, symbol as u32);
+            write_varint(out, f);
+        }
+    }
+}
+
+fn read_sparse_header(input: &[u8], pos: &mut usize) -> Result<Vec<u32>, Error> {
+    *pos = 4;
+    read_sparse_pairs(input, pos)
+}
+
+fn read_sparse_pairs(input: &[u8], pos: &mut usize) -> Result<Vec<u32>, Error> {
+    let mut freq = Vec::new();
+    read_sparse_pairs_into(input, pos, &mut freq)?;
+    Ok(freq)
+}
+
+fn read_sparse_pairs_into(input: &[u8], pos: &mut usize, freq: &mut Vec<u32>) -> Result<(), Error> {
+    let present = read_varint(input, pos).ok_or(Error::Truncated("range: truncated header"))?;
+    if present as usize > SYMBOL_LIMIT {
+        return Err(Error::InvalidModel("range: bad symbol count"));
+    }
+    freq.clear();
+    freq.resize(SYMBOL_LIMIT, 0);
+    let mut next_symbol = 0usize;
+    for _ in 0..present {
+        let symbol = read_varint(input, pos)
+            .ok_or(Error::Truncated("range: truncated frequencies"))? as usize;
+        let f = read_varint(input, pos).ok_or(Error::Truncated("range: truncated frequencies"))?;
+        if symbol < next_symbol || symbol >= SYMBOL_LIMIT || f == 0 {
+            return Err(Error::InvalidModel("range: bad sparse header"));
+        }
+        freq[symbol] = f;
+        next_symbol = symbol + 1;
+    }
+    Ok(())
+}
+
+// 输入非空且所有字节相同时返回该字节。
+fn single_byte(input: &[u8]) -> Option<u8> {
+    let (&first, rest) = input.split_first()?;
+    rest.iter().all(|&b| b == first).then_some(first)
+}
+
+fn encode_single(value: u8, len: u64) -> Vec<u8> {
+    let mut out = Vec::with_capacity(13);
+    out.extend_from_slice(SINGLE_MAGIC);
+    out.push(value);
+    out.extend_from_slice(&len.to_le_bytes());
+    out
+}
+
+// 返回 (取值, 重复次数)。
+fn read_single_header(encoded: &[u8]) -> Result<(u8, u64), Error> {
+    if encoded.len() < 13 {
+        return Err(Error::Truncated("range: truncated header"));
+    }
+    let value = encoded[4];
+    let len = u64::from_le_bytes(encoded[5..13].try_into().unwrap());
+    if len == 0 {
+        return Err(Error::InvalidData("range: empty single-byte stream"));
+    }
+    Ok((value, len))
+}
+
+// 返回解码结果以及 encode 对同一输入会构建的频率表。
+fn decode_single(encoded: &[u8], opts: &DecodeOptions) -> Result<(Vec<u8>, Vec<u32>), Error> {
+    let (value, len) = read_single_header(encoded)?;
+    let len = usize::try_from(len)
+        .ok()
+        .filter(|&len| len <= opts.max_output)
+        .ok_or(Error::LimitExceeded("range: output limit exceeded"))?;
+    // 13 字节的头部可以声明任意长度：按声明的长度一次性尝试分配，失败时返回错误而不是中止进程。
+    let mut out = Vec::new();
+    out.try_reserve_exact(len)
+        .map_err(|_| Error::LimitExceeded("range: output too large"))?;
+    out.resize(len, value);
+    let mut freq = vec![0u32; SYMBOL_LIMIT];
+    freq[value as usize] = u32::try_from(len).unwrap_or(u32::MAX);
+    freq[EOF_SYMBOL] = 1;
+    scale_frequencies(&mut freq);
+    Ok((out, freq))
+}
+
+// 将输入重映射到只含出现字节的稠密字母表，返回（有序的出现字节，稠密下标序列）。
+fn dense_remap(input: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut present = [false; 256];
+    for &b in input {
+        present[b as usize] = true;
+    }
+    let mut alphabet = Vec::new();
+    let mut index = [0u8; 256];
+    for b in 0..256usize {
+        if present[b] {
+            index[b] = alphabet.len() as u8;
+            alphabet.push(b as u8);
+        }
+    }
+    let symbols = input.iter().map(|&b| index[b as usize]).collect();
+    (alphabet, symbols)
+}
+
+fn encode_dense(out: &mut Vec<u8>, input: &[u8], mode: ScalingMode) -> Result<(), Error> {
+    let (alphabet, symbols) = dense_remap(input);
+    let mut freq = vec![0u32; alphabet.len() + 1];
+    for &s in &symbols {
+        freq[s as usize] += 1;
+    }
+    freq[alphabet.len()] = 1;
+    scale_frequencies_with(&mut freq, mode);
+    let cumulative = build_cumulative(&freq);
+
+    out.extend_from_slice(DENSE_MAGIC);
+    write_varint(out, alphabet.len() as u32);
+    out.extend_from_slice(&alphabet);
+    for &f in &freq {
+        write_varint(out, f);
+    }
+    encode_body(out, &symbols, &cumulative, || false)
+}
+
+fn read_dense_header(input: &[u8], pos: &mut usize) -> Result<(Vec<u8>, Vec<u32>), Error> {
+    *pos = 4;
+    let n = read_varint(input, pos).ok_or(Error::Truncated("range: truncated header"))? as usize;
+    if n > 256 {
+        return Err(Error::InvalidModel("range: bad symbol count"));
+    }
+    let alphabet = input
+        .get(*pos..*pos + n)
+        .ok_or(Error::Truncated("range: truncated header"))?
+        .to_vec();
+    *pos += n;
+    if alphabet.windows(2).any(|w| w[0] >= w[1]) {
+        return Err(Error::InvalidModel("range: bad dense header"));
+    }
+    let mut freq = Vec::with_capacity(n + 1);
+    for _ in 0..=n {
+        let f = read_varint(input, pos).ok_or(Error::Truncated("range: truncated frequencies"))?;
+        if f == 0 {
+            return Err(Error::InvalidModel("range: bad dense header"));
+        }
+        freq.push(f);
+    }
+    Ok((alphabet, freq))
+}
+
+// 返回解码结果以及展开回完整 SYMBOL_LIMIT 字母表的频率表。
+fn decode_dense(encoded: &[u8], opts: &DecodeOptions) -> Result<(Vec<u8>, Vec<u32>), Error> {
+    let mut pos = 0;
+    let (alphabet, freq) = read_dense_header(encoded, &mut pos)?;
+    validate_model(&freq)?;
+    let cumulative = build_cumulative(&freq);
+    let mut out = decode_body(&encoded[pos..], &cumulative, opts)?;
+    for b in out.iter_mut() {
+        *b = alphabet[*b as usize];
+    }
+    let mut model = vec![0u32; SYMBOL_LIMIT];
+    for (&b, &f) in alphabet.iter().zip(&freq) {
+        model[b as usize] = f;
+    }
+    model[EOF_SYMBOL] = freq[alphabet.len()];
+    Ok((out, model))
+}
+
+// 把频率表换算为总和恰为 2^16 的定点概率，每项都能用 u16 表示。
+// 只有一个非零符号（空输入只含 EOF）时它会占满 2^16，此时让出 1 个单位给另一个符号。
+fn fixed16_model(freq: &[u32]) -> Vec<u32> {
+    let mut fixed = freq.to_vec();
+    let total: u64 = fixed.iter().map(|&f| f as u64).sum();
+    scale_diffuse(&mut fixed, total, FIXED16_TOTAL);
+    if let Some(i) = fixed.iter().position(|&f| f == FIXED16_TOTAL) {
+        let next = (i + 1) % fixed.len();
+        fixed[i] -= 1;
+        fixed[next] += 1;
+    }
+    fixed
+}
+
+// 频率超过 u16 时按比例缩放到总和 FIXED16_TOTAL（非零项至少为 1），
+// EOF 非零，因此缩放后每项都不超过 u16::MAX。
+fn sparse16_model(freq: &[u32]) -> Vec<u32> {
+    let mut freq = freq.to_vec();
+    if freq.iter().any(|&f| f > u16::MAX as u32) {
+        let total = freq.iter().map(|&f| f as u64).sum();
+        scale_diffuse(&mut freq, total, FIXED16_TOTAL);
+    }
+    freq
+}
+
+fn write_sparse16_header(out: &mut Vec<u8>, freq: &[u32]) {
+    out.extend_from_slice(SPARSE16_MAGIC);
+    let present = freq.iter().filter(|&&f| f != 0).count();
+    out.extend_from_slice(&(present as u16).to_le_bytes());
+    for (symbol, &f) in freq[..EOF_SYMBOL].iter().enumerate() {
+        if f != 0 {
+            out.push(symbol as u8);
+            out.extend_from_slice(&(f as u16).to_le_bytes());
+        }
+    }
+    out.extend_from_slice(&(freq[EOF_SYMBOL] as u16).to_le_bytes());
+}
+
+fn read_sparse16_header(encoded: &[u8], pos: &mut usize) -> Result<Vec<u32>, Error> {
+    let read_u16 = |pos: &mut usize| -> Result<u32, Error> {
+        let bytes = encoded
+            .get(*pos..*pos + 2)
+            .ok_or(Error::Truncated("range: truncated header"))?;
+        *pos += 2;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]) as u32)
+    };
+    *pos = 4;
+    let present = read_u16(pos)? as usize;
+    if present == 0 || present > SYMBOL_LIMIT {
+        return Err(Error::InvalidModel("range: bad symbol count"));
+    }
+    let mut freq = vec![0u32; SYMBOL_LIMIT];
+    let mut next_symbol = 0usize;
+    for _ in 1..present {
+        let symbol = *encoded
+            .get(*pos)
+            .ok_or(Error::Truncated("range: truncated header"))? as usize;
+        *pos += 1;
+        // 字节值必须大于前一对的字节值，重复或倒序的对视为越界。
+        if symbol < next_symbol {
+            return Err(Error::InvalidModel("range: sparse symbol out of range"));
+        }
+        freq[symbol] = read_u16(pos)?;
+        next_symbol = symbol + 1;
+    }
+    freq[EOF_SYMBOL] = read_u16(pos)?;
+    if freq.iter().filter(|&&f| f != 0).count() != present {
+        return Err(Error::InvalidModel("range: bad sparse header"));
+    }
+    Ok(freq)
+}
+
+// 让最大频率不超过 u16::MAX：超出时整表按比例缩小（保持各符号的相对概率），
+// 舍入误差落在最大频率上，最后再截到 u16::MAX 兜底。只会减小总和，仍满足 MAX_TOTAL 上限。
+fn capped16_model(freq: &[u32]) -> Vec<u32> {
+    let mut freq = freq.to_vec();
+    let max = freq.iter().copied().max().unwrap_or(0) as u64;
+    if max > u16::MAX as u64 {
+        let total: u64 = freq.iter().map(|&f| f as u64).sum();
+        scale_diffuse(&mut freq, total, (total * u16::MAX as u64 / max) as u32);
+        for f in freq.iter_mut() {
+            *f = (*f).min(u16::MAX as u32);
+        }
+    }
+    freq
+}
+
+fn write_u16_table(out: &mut Vec<u8>, magic: &[u8; 4], freq: &[u32]) {
+    out.extend_from_slice(magic);
+    for &f in freq {
+        out.extend_from_slice(&(f as u16).to_le_bytes());
+    }
+}
+
+// 读取魔数之后的 SYMBOL_LIMIT 个 u16 小端频率（RCNQ / RCNW）。
+fn read_u16_table(encoded: &[u8], pos: &mut usize) -> Result<Vec<u32>, Error> {
+    let end = 4 + 2 * SYMBOL_LIMIT;
+    let header = encoded
+        .get(4..end)
+        .ok_or(Error::Truncated("range: truncated header"))?;
+    *pos = end;
+    Ok(header
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]) as u32)
+        .collect())
+}
+
+fn read_fixed16_header(encoded: &[u8], pos: &mut usize) -> Result<Vec<u32>, Error> {
+    let freq = read_u16_table(encoded, pos)?;
+    if freq.iter().sum::<u32>() != FIXED16_TOTAL {
+        return Err(Error::InvalidModel("range: bad probability total"));
+    }
+    Ok(freq)
+}
+
+fn decode_fixed16(encoded: &[u8], opts: &DecodeOptions) -> Result<(Vec<u8>, Vec<u32>), Error> {
+    let mut pos = 0;
+    let freq = read_fixed16_header(encoded, &mut pos)?;
+    validate_model(&freq)?;
+    let out = decode_body(&encoded[pos..], &build_cumulative(&freq), opts)?;
+    Ok((out, freq))
+}
+
+// 按给定选项编码；默认选项的输出与 encode 完全相同。
+pub fn encode_with_options(input: &[u8], opts: &EncodeOptions) -> Result<Vec<u8>, Error> {
+    let freq = build_frequencies_with(input, opts.scaling);
+    let cumulative = build_cumulative(&freq);
+
+    let mut inner = Vec::with_capacity(input.len());
+    match (opts.format, single_byte(input)) {
+        (_, Some(value)) => inner = encode_single(value, input.len() as u64),
+        (FormatVersion::SingleByte, None) => {
+            return Err(Error::InvalidInput(
+                "range: input is not a single repeated byte",
+            ));
+        }
+        (FormatVersion::Classic, None) => {
+            write_header(&mut inner, &freq);
+            encode_body(&mut inner, input, &cumulative, || false)?;
+        }
+        (FormatVersion::SparseVarint, None) => {
+            write_sparse_header(&mut inner, &freq);
+            encode_body(&mut inner, input, &cumulative, || false)?;
+        }
+        (FormatVersion::Sparse16, None) => {
+            let freq = sparse16_model(&freq);
+            write_sparse16_header(&mut inner, &freq);
+            encode_body(&mut inner, input, &build_cumulative(&freq), || false)?;
+        }
+        (FormatVersion::DenseRemap, None) => encode_dense(&mut inner, input, opts.scaling)?,
+        (FormatVersion::Fixed16, None) => {
+            let freq = fixed16_model(&freq);
+            write_u16_table(&mut inner, FIXED16_MAGIC, &freq);
+            encode_body(&mut inner, input, &build_cumulative(&freq), || false)?;
+        }
+        (FormatVersion::Capped16, None) => {
+            let freq = capped16_model(&freq);
+            write_u16_table(&mut inner, CAPPED16_MAGIC, &freq);
+            encode_body(&mut inner, input, &build_cumulative(&freq), || false)?;
+        }
+    }
+
+    let stored = opts.stored_fallback && inner.len() >= input.len();
+    if opts.error_on_expansion && !opts.stored_fallback && inner.len() > input.len() {
+        return Err(Error::LimitExceeded(
+            "range: encoded output larger than input",
+        ));
+    }
+    if !opts.crc && !stored && !opts.length && opts.metadata.is_none() {
+        return Ok(inner);
+    }
+
+    let mut flags = 0u8;
+    if opts.crc {
+        flags |= FLAG_CRC;
+        if opts.checksum == ChecksumKind::Adler32 {
+            flags |= FLAG_ADLER;
+        }
+    }
+    if stored {
+        flags |= FLAG_STORED;
+    }
+    if opts.metadata.is_some() {
+        flags |= FLAG_METADATA;
+    }
+    if opts.length {
+        flags |= FLAG_LENGTH;
+    }
+    let payload: &[u8] = if stored { input } else { &inner };
+    let mut out = Vec::with_capacity(payload.len() + 17);
+    out.extend_from_slice(FRAME_MAGIC);
+    out.push(flags);
+    if let Some(metadata) = &opts.metadata {
+        if metadata.len() > u32::MAX as usize {
+            return Err(Error::InvalidInput("range: metadata too large"));
+        }
+        write_u32_le(&mut out, metadata.len() as u32);
+        out.extend_from_slice(metadata);
+    }
+    if opts.crc {
+        write_u32_le(&mut out, opts.checksum.compute(input));
+    }
+    if opts.length {
+        out.extend_from_slice(&(input.len() as u64).to_le_bytes());
+    }
+    out.extend_from_slice(payload);
+    Ok(out)
+}
+
+// 解码 encode / encode_with_options 产生的任意格式。
+pub fn decode_with_options(encoded: &[u8], opts: &DecodeOptions) -> Result<Vec<u8>, Error> {
+    decode_with_model_options(encoded, opts).map(|(out, _)| out)
+}
+
+// 解码时频率表与累积表占用的字节数，计入 DecodeLimits。
+const MODEL_TABLE_BYTES: u64 = ((2 * SYMBOL_LIMIT + 1) * core::mem::size_of::<u32>()) as u64;
+
+// 在 limits 约束下解码：模型表与输出合计超过上限时返回 LimitExceeded。
+pub fn decode_with_limits(encoded: &[u8], limits: &DecodeLimits) -> Result<Vec<u8>, Error> {
+    let budget = limits
+        .output_budget(MODEL_TABLE_BYTES)
+        .ok_or(Error::LimitExceeded("range: memory limit exceeded"))?;
+    let max_output = usize::try_from(budget).unwrap_or(usize::MAX);
+    decode_with_options(encoded, &DecodeOptions::new().with_max_output(max_output))
+}
+
+// 解码的同时返回数据流头部中的频率表（SYMBOL_LIMIT 项，含 EOF），
+// 可直接作为后续相关消息的字典模型，无需重新统计直方图。
+// 以原样存储的数据流不含模型，返回 InvalidInput。
+pub fn decode_with_model(encoded: &[u8]) -> Result<(Vec<u8>, Vec<u32>), Error> {
+    let (out, model) = decode_with_model_options(encoded, &DecodeOptions::new())?;
+    let model = model.ok_or(Error::InvalidInput("range: stored stream has no model"))?;
+    Ok((out, model))
+}
+
+fn decode_with_model_options(
+    encoded: &[u8],
+    opts: &DecodeOptions,
+) -> Result<(Vec<u8>, Option<Vec<u32>>), Error> {
+    if encoded.len() >= 4 && &encoded[0..4] == FRAME_MAGIC {
+        return decode_frame(encoded, opts);
+    }
+    let (out, model) = decode_stream(encoded, opts)?;
+    Ok((out, Some(model)))
+}
+
+fn decode_stream(encoded: &[u8], opts: &DecodeOptions) -> Result<(Vec<u8>, Vec<u32>), Error> {
+    if encoded.len() >= 4 && &encoded[0..4] == DENSE_MAGIC {
+        return decode_dense(encoded, opts);
+    }
+    if encoded.len() >= 4 && &encoded[0..4] == SINGLE_MAGIC {
+        return decode_single(encoded, opts);
+    }
+    if encoded.len() >= 4 && &encoded[0..4] == FIXED16_MAGIC {
+        return decode_fixed16(encoded, opts);
+    }
+    let mut pos: usize = 0;
+    let freq = if encoded.len() >= 4 && &encoded[0..4] == SPARSE_MAGIC {
+        read_sparse_header(encoded, &mut pos)?
+    } else if encoded.len() >= 4 && &encoded[0..4] == SPARSE16_MAGIC {
+        read_sparse16_header(encoded, &mut pos)?
+    } else if encoded.len() >= 4 && &encoded[0..4] == CAPPED16_MAGIC {
+        read_u16_table(encoded, &mut pos)?
+    } else {
+        read_header(encoded, &mut pos)?
+    };
+    if freq.len() != SYMBOL_LIMIT {
+        return Err(Error::InvalidModel("range: unexpected symbol count"));
+    }
+    validate_model(&freq)?;
+    let cumulative = build_cumulative(&freq);
+    let out = decode_body(&encoded[pos..], &cumulative, opts)?;
+    Ok((out, freq))
+}
+
+// 解析外层容器的标志与元数据，返回 (标志, 元数据, 元数据之后的位置)。
+fn read_frame_header(encoded: &[u8]) -> Result<(u8, &[u8], usize), Error> {
+    let mut pos = 4;
+    let flags = *encoded
+        .get(pos)
+        .ok_or(Error::Truncated("range: truncated header"))?;
+    pos += 1;
+    let known = FLAG_CRC | FLAG_STORED | FLAG_METADATA | FLAG_LENGTH | FLAG_ADLER;
+    if flags & !known != 0 || (flags & FLAG_ADLER != 0 && flags & FLAG_CRC == 0) {
+        return Err(Error::BadMagic("range: bad flags"));
+    }
+    let mut metadata: &[u8] = &[];
+    if flags & FLAG_METADATA != 0 {
+        let len =
+            read_u32_le(encoded, &mut pos).ok_or(Error::Truncated("range: truncated header"))?;
+        metadata = encoded
+            .get(pos..pos + len as usize)
+            .ok_or(Error::Truncated("range: truncated metadata"))?;
+        pos += len as usize;
+    }
+    Ok((flags, metadata, pos))
+}
+
+// 读取 encode_with_metadata 写入的元数据而不解码数据体；没有元数据的数据流返回空切片。
+pub fn read_metadata(encoded: &[u8]) -> Result<&[u8], Error> {
+    if encoded.len() >= 4 && &encoded[0..4] == FRAME_MAGIC {
+        let (_, metadata, _) = read_frame_header(encoded)?;
+        return Ok(metadata);
+    }
+    Ok(&[])
+}
+
+// 与 encode 相同的编码，另在外层容器中保存一段用户元数据；decode 会透明地跳过它。
+pub fn encode_with_metadata(input: &[u8], metadata: &[u8]) -> Result<Vec<u8>, Error> {
+    encode_with_options(input, &EncodeOptions::new().with_metadata(metadata))
+}
+
+fn decode_frame(
+    encoded: &[u8],
+    opts: &DecodeOptions,
+) -> Result<(Vec<u8>, Option<Vec<u32>>), Error> {
+    let (flags, _, mut pos) = read_frame_header(encoded)?;
+    let (expected_crc, expected_len) = read_frame_checks(encoded, flags, &mut pos)?;
+    if expected_len.is_some_and(|len| len > opts.max_output as u64) {
+        return Err(Error::LimitExceeded("range: output limit exceeded"));
+    }
+
+    let payload = &encoded[pos..];
+    let (out, model) = if flags & FLAG_STORED != 0 {
+        if payload.len() > opts.max_output {
+            return Err(Error::LimitExceeded("range: output limit exceeded"));
+        }
+        (payload.to_vec(), None)
+    } else {
+        let (out, model) = decode_stream(payload, opts)?;
+        (out, Some(model))
+    };
+
+    if expected_len.is_some_and(|len| len != out.len() as u64) {
+        return Err(Error::InvalidData("range: length mismatch"));
+    }
+    if let Some(crc) = expected_crc {
+        if checksum_kind(flags).compute(&out) != crc {
+            return Err(Error::ChecksumMismatch("range: checksum mismatch"));
+        }
+    }
+    Ok((out, model))
+}
+
+fn checksum_kind(flags: u8) -> ChecksumKind {
+    if flags & FLAG_ADLER != 0 {
+        ChecksumKind::Adler32
+    } else {
+        ChecksumKind::Crc32
+    }
+}
+
+// 读取元数据之后的校验和与原始长度字段（按标志可选）。
+fn read_frame_checks(
+    encoded: &[u8],
+    flags: u8,
+    pos: &mut usize,
+) -> Result<(Option<u32>, Option<u64>), Error> {
+    let crc = if flags & FLAG_CRC != 0 {
+        Some(read_u32_le(encoded, pos).ok_or(Error::Truncated("range: truncated header"))?)
+    } else {
+        None
+    };
+    let len = if flags & FLAG_LENGTH != 0 {
+        let bytes = encoded
+            .get(*pos..*pos + 8)
+            .ok_or(Error::Truncated("range: truncated header"))?;
+        *pos += 8;
+        Some(u64::from_le_bytes(bytes.try_into().unwrap()))
+    } else {
+        None
+    };
+    Ok((crc, len))
+}
+
+// inspect 的结果：不解码数据体即可得到的信息，类似 unzip -l 的一行。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inspection {
+    // 数据体的格式；容器中原样存储时为 None。
+    pub format: Option<FormatVersion>,
+    // 原始数据长度与校验和，仅当编码时启用 with_length / with_crc（with_checksum）才有；
+    // 校验和按所用算法填入 crc32 或 adler32。
+    pub original_len: Option<u64>,
+    pub crc32: Option<u32>,
+    pub adler32: Option<u32>,
+    // 整个编码结果的字节数。
+    pub compressed_len: usize,
+}
+
+fn format_of(stream: &[u8]) -> Result<FormatVersion, Error> {
+    match stream.get(0..4) {
+        Some(b"RCNC") => Ok(FormatVersion::Classic),
+        Some(m) if m == SPARSE_MAGIC => Ok(FormatVersion::SparseVarint),
+        Some(m) if m == SPARSE16_MAGIC => Ok(FormatVersion::Sparse16),
+        Some(m) if m == CAPPED16_MAGIC => Ok(FormatVersion::Capped16),
+        Some(m) if m == DENSE_MAGIC => Ok(FormatVersion::DenseRemap),
+        Some(m) if m == SINGLE_MAGIC => Ok(FormatVersion::SingleByte),
+        Some(m) if m == FIXED16_MAGIC => Ok(FormatVersion::Fixed16),
+        Some(_) => Err(Error::BadMagic("range: bad magic")),
+        None => Err(Error::Truncated("range: input too short")),
+    }
+}
+
+// 读取 encode / encode_with_options 输出的头部信息而不解码数据体。
+pub fn inspect(encoded: &[u8]) -> Result<Inspection, Error> {
+    let mut inspection = Inspection {
+        format: None,
+        original_len: None,
+        crc32: None,
+        adler32: None,
+        compressed_len: encoded.len(),
+    };
+    if encoded.get(0..4) != Some(FRAME_MAGIC) {
+        inspection.format = Some(format_of(encoded)?);
+        return Ok(inspection);
+    }
+    let (flags, _, mut pos) = read_frame_header(encoded)?;
+    let (checksum, original_len) = read_frame_checks(encoded, flags, &mut pos)?;
+    inspection.original_len = original_len;
+    match checksum_kind(flags) {
+        ChecksumKind::Crc32 => inspection.crc32 = checksum,
+        ChecksumKind::Adler32 => inspection.adler32 = checksum,
+    }
+    if flags & FLAG_STORED == 0 {
+        inspection.format = Some(format_of(&encoded[pos..])?);
+    }
+    Ok(inspection)
+}
+
+// encode_with_stats 返回的编码统计。
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodeStats {
+    pub input_len: usize,
+    pub output_len: usize,
+    pub header_len: usize,
+    pub diagnostics: ModelDiagnostics,
+}
+
+// 与 encode 输出相同，同时返回编码统计与模型诊断。
+#[cfg(feature = "std")]
+pub fn encode_with_stats(input: &[u8]) -> Result<(Vec<u8>, EncodeStats), Error> {
+    let mut counts = histogram(input, SYMBOL_LIMIT);
+    counts[EOF_SYMBOL] = 1;
+    let mut freq = counts.clone();
+    scale_frequencies(&mut freq);
+    let cumulative = build_cumulative(&freq);
+
+    let mut out = Vec::with_capacity(input.len());
+    let header_len = if let Some(value) = single_byte(input) {
+        out = encode_single(value, input.len() as u64);
+        out.len()
+    } else {
+        write_header(&mut out, &freq);
+        let header_len = out.len();
+        encode_body(&mut out, input, &cumulative, || false)?;
+        header_len
+    };
+
+    let stats = EncodeStats {
+        input_len: input.len(),
+        output_len: out.len(),
+        header_len,
+        diagnostics: diagnose(&counts, &freq),
+    };
+    Ok((out, stats))
+}
+
+// 多个小消息共享的静态模型（类似 zstd 字典）。
+// 编码端与解码端必须使用完全相同的字典；数据流中只保存字典 id 用于校验，不保存频率表。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dictionary {
+    freq: Vec<u32>,
+}
+
+const DICT_MAGIC: &[u8; 4] = b"RCND";
+
+impl Dictionary {
+    pub fn freq(&self) -> &[u32] {
+        &self.freq
+    }
+
+    // 字典 id：序列化模型的 CRC32。
+    pub fn id(&self) -> u32 {
+        checksum::crc32(&self.to_bytes())
+    }
+
+    // 序列化为 RCNC 头部格式（魔数 + 频率表，无数据体）。
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(8 + self.freq.len() * 4);
+        write_header(&mut out, &self.freq);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Dictionary, Error> {
+        let mut pos = 0;
+        let freq = read_header(bytes, &mut pos)?;
+        if freq.len() != SYMBOL_LIMIT {
+            return Err(Error::InvalidModel("range: unexpected symbol count"));
+        }
+        validate_model(&freq)?;
+        if freq.contains(&0) {
+            return Err(Error::InvalidModel("range: bad dictionary"));
+        }
+        Ok(Dictionary { freq })
+    }
+
+    // 由调用方统计的 257 项频率表（如语料直方图）构造字典：为 0 的符号按 1 计以保证任意输入都可编码，
+    // 总和过大时按 normalize 缩放。
+    pub fn from_frequencies(freq: &[u32]) -> Result<Dictionary, Error> {
+        if freq.len() != SYMBOL_LIMIT {
+            return Err(Error::InvalidInput("range: unexpected symbol count"));
+        }
+        let mut freq: Vec<u32> = freq.iter().map(|&f| f.max(1)).collect();
+        scale_frequencies(&mut freq);
+        Ok(Dictionary { freq })
+    }
+}
+
+// 由样本训练字典：合并所有样本的直方图，每个符号至少计 1 次以保证任意输入都可编码。
+pub fn train_dictionary(samples: &[&[u8]]) -> Dictionary {
+    let mut freq = vec![1u32; SYMBOL_LIMIT];
+    for sample in samples {
+        for (f, count) in freq.iter_mut().zip(histogram(sample, SYMBOL_LIMIT)) {
+            *f = f.saturating_add(count);
+        }
+    }
+    freq[EOF_SYMBOL] = 1;
+    scale_frequencies(&mut freq);
+    Dictionary { freq }
+}
+
+// 使用字典编码：魔数 RCND + 4 字节字典 id + 编码数据，不含频率表。
+pub fn encode_with_dictionary(input: &[u8], dict: &Dictionary) -> Result<Vec<u8>, Error> {
+    let cumulative = build_cumulative(&dict.freq);
+    let mut out = Vec::with_capacity(input.len() + 8);
+    out.extend_from_slice(DICT_MAGIC);
+    write_u32_le(&mut out, dict.id());
+    encode_body(&mut out, input, &cumulative, || false)?;
+    Ok(out)
+}
+
+pub fn decode_with_dictionary(encoded: &[u8], dict: &Dictionary) -> Result<Vec<u8>, Error> {
+    decode_with_dictionary_with_limits(encoded, dict, &DecodeLimits::unlimited())
+}
+
+// 在 limits 约束下解码：累积表与输出合计超过上限时返回 LimitExceeded。
+pub fn decode_with_dictionary_with_limits(
+    encoded: &[u8],
+    dict: &Dictionary,
+    limits: &DecodeLimits,
+) -> Result<Vec<u8>, Error> {
+    if encoded.len() < 8 {
+        return Err(Error::Truncated("range: input too short"));
+    }
+    if &encoded[0..4] != DICT_MAGIC {
+        return Err(Error::BadMagic("range: bad magic"));
+    }
+    let mut pos = 4;
+    let id = read_u32_le(encoded, &mut pos).ok_or(Error::Truncated("range: truncated header"))?;
+    if id != dict.id() {
+        return Err(Error::InvalidModel("range: dictionary mismatch"));
+    }
+    let budget = limits
+        .output_budget(MODEL_TABLE_BYTES)
+        .ok_or(Error::LimitExceeded("range: memory limit exceeded"))?;
+    let opts = DecodeOptions::new().with_max_output(usize::try_from(budget).unwrap_or(usize::MAX));
+    let cumulative = build_cumulative(&dict.freq);
+    decode_body(&encoded[pos..], &cumulative, &opts)
+}
+
+// 无头部的字典编码：只有编码数据，不含魔数与字典 id，每条消息的开销只剩编码比特本身。
+// 数据流中没有任何校验，用错字典时 decode_with_dictionary_raw 不会报错而是得到错误的数据，
+// 调用方须自行保证两端的字典一致（如在外层协议中约定字典版本）。
+pub fn encode_with_dictionary_raw(input: &[u8], dict: &Dictionary) -> Result<Vec<u8>, Error> {
+    let cumulative = build_cumulative(&dict.freq);
+    let mut out = Vec::with_capacity(input.len() + 4);
+    encode_body(&mut out, input, &cumulative, || false)?;
+    Ok(out)
+}
+
+pub fn decode_with_dictionary_raw(encoded: &[u8], dict: &Dictionary) -> Result<Vec<u8>, Error> {
+    let cumulative = build_cumulative(&dict.freq);
+    decode_body(encoded, &cumulative, &DecodeOptions::new())
+}
+
+// 参考差分格式：魔数 RCNX + 4 字节参考数据 CRC32 + 差分序列的 encode 输出。
+// 差分序列第 i 字节为 input[i] ^ reference[i]，超出参考数据长度的部分保留原字节；
+// 新旧版本相似时差分几乎全为 0，熵编码后只有变化的区域占用比特。
+const DELTA_MAGIC: &[u8; 4] = b"RCNX";
+
+fn xor_with_reference(reference: &[u8], data: &mut [u8]) {
+    for (b, &r) in data.iter_mut().zip(reference) {
+        *b ^= r;
+    }
+}
+
+// 以 reference 为参考编码 input；解码端必须持有同一份 reference。
+pub fn encode_against(reference: &[u8], input: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut diff = input.to_vec();
+    xor_with_reference(reference, &mut diff);
+    let body = encode(&diff)?;
+    let mut out = Vec::with_capacity(body.len() + 8);
+    out.extend_from_slice(DELTA_MAGIC);
+    write_u32_le(&mut out, checksum::crc32(reference));
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+pub fn decode_against(reference: &[u8], encoded: &[u8]) -> Result<Vec<u8>, Error> {
+    decode_against_with_limits(reference, encoded, &DecodeLimits::unlimited())
+}
+
+// 在 limits 约束下解码差分序列，含义同 decode_with_limits。
+pub fn decode_against_with_limits(
+    reference: &[u8],
+    encoded: &[u8],
+    limits: &DecodeLimits,
+) -> Result<Vec<u8>, Error> {
+    if encoded.len() < 8 {
+        return Err(Error::Truncated("range: input too short"));
+    }
+    if &encoded[0..4] != DELTA_MAGIC {
+        return Err(Error::BadMagic("range: bad magic"));
+    }
+    let mut pos = 4;
+    let crc = read_u32_le(encoded, &mut pos).ok_or(Error::Truncated("range: truncated header"))?;
+    if crc != checksum::crc32(reference) {
+        return Err(Error::InvalidInput("range: reference mismatch"));
+    }
+    let mut out = decode_with_limits(&encoded[pos..], limits)?;
+    xor_with_reference(reference, &mut out);
+    Ok(out)
+}
+
+// 定长格式：魔数 RCNZ + 8 字节小端原始长度 + 256 个 u32 小端频率 + 编码数据。
+// 解码端按长度解出恰好这么多个符号，不需要 EOF：字母表正好是 256 个字节值，
+// 也省去每个流末尾编码 EOF 的开销。默认的 encode 仍使用带 EOF 的 RCNC 以保持兼容。
+const SIZED_MAGIC: &[u8; 4] = b"RCNZ";
+const BYTE_SYMBOLS: usize = 256;
+
+pub fn encode_sized(input: &[u8]) -> Vec<u8> {
+    let freq = build_frequencies_no_eof(input);
+    let cumulative = build_cumulative(&freq);
+
+    let mut out = Vec::with_capacity(12 + 4 * BYTE_SYMBOLS + input.len() / 2);
+    out.extend_from_slice(SIZED_MAGIC);
+    out.extend_from_slice(&(input.len() as u64).to_le_bytes());
+    for &f in &freq {
+        write_u32_le(&mut out, f);
+    }
+    let mut enc = RangeEncoder::new(&mut out);
+    for &b in input {
+        enc.encode_symbol(b as u32, &cumulative);
+    }
+    enc.finish();
+    out
+}
+
+pub fn decode_sized(encoded: &[u8]) -> Result<Vec<u8>, Error> {
+    decode_sized_with_limits(encoded, &DecodeLimits::unlimited())
+}
+
+// RCNZ 解码时频率表与累积表占用的字节数，计入 DecodeLimits。
+const SIZED_TABLE_BYTES: u64 = ((2 * BYTE_SYMBOLS + 1) * core::mem::size_of::<u32>()) as u64;
+
+// 在 limits 约束下解码：头部的长度与模型表合计超过上限时在解码之前返回 LimitExceeded，
+// 解码越过数据体末尾（长度伪造或数据被截断）时返回 Truncated。
+pub fn decode_sized_with_limits(encoded: &[u8], limits: &DecodeLimits) -> Result<Vec<u8>, Error> {
+    if encoded.len() < 12 {
+        return Err(Error::Truncated("range: input too short"));
+    }
+    if &encoded[0..4] != SIZED_MAGIC {
+        return Err(Error::BadMagic("range: bad magic"));
+    }
+    let len = u64::from_le_bytes(encoded[4..12].try_into().unwrap());
+    let budget = limits
+        .output_budget(SIZED_TABLE_BYTES)
+        .ok_or(Error::LimitExceeded("range: memory limit exceeded"))?;
+    let len = usize::try_from(len)
+        .ok()
+        .filter(|&len| len as u64 <= budget)
+        .ok_or(Error::LimitExceeded("range: output limit exceeded"))?;
+    let mut pos = 12;
+    let freq = (0..BYTE_SYMBOLS)
+        .map(|_| {
+            read_u32_le(encoded, &mut pos).ok_or(Error::Truncated("range: truncated frequencies"))
+        })
+        .collect::<Result<Vec<u32>, Error>>()?;
+    validate_model(&freq)?;
+    let cumulative = build_cumulative(&freq);
+
+    // 长度字段来自数据流，预分配量以数据体大小为界，避免伪造的长度导致大块分配。
+    let mut out = Vec::with_capacity(len.min(encoded.len().saturating_mul(8)));
+    let mut dec = RangeDecoder::new(&encoded[pos..]);
+    for _ in 0..len {
+        out.push(dec.decode_symbol(&cumulative) as u8);
+        if dec.overrun() {
+            return Err(Error::Truncated("range: truncated data"));
+        }
+    }
+    Ok(out)
+}
+
+// 重复标志模型：魔数 RCNR + 2 个 u32 标志频率（与前一字节相同 / 不同）+ 稀疏 varint 字面量频率表 + 编码数据。
+// 每个位置先用二元模型编码"是否与前一字节相同"，不同时再用 order-0 模型编码字面量，
+// 相当于把 RLE 折叠进熵编码器，单遍即可处理大量连续重复的数据。数据末尾编码"不同" + EOF。
+const RLE_MODEL_MAGIC: &[u8; 4] = b"RCNR";
+const FLAG_SAME: u32 = 0;
+const FLAG_LITERAL: u32 = 1;
+
+pub fn encode_rle_model(input: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut flags = vec![0u32; 2];
+    let mut literals = vec![0u32; SYMBOL_LIMIT];
+    let mut prev: Option<u8> = None;
+    for &b in input {
+        if prev == Some(b) {
+            flags[FLAG_SAME as usize] += 1;
+        } else {
+            flags[FLAG_LITERAL as usize] += 1;
+            literals[b as usize] += 1;
+        }
+        prev = Some(b);
+    }
+    flags[FLAG_LITERAL as usize] += 1;
+    literals[EOF_SYMBOL] = 1;
+    scale_frequencies(&mut flags);
+    scale_frequencies(&mut literals);
+    let flag_cum = build_cumulative(&flags);
+    let literal_cum = build_cumulative(&literals);
+
+    let mut out = Vec::with_capacity(input.len() / 2 + 16);
+    out.extend_from_slice(RLE_MODEL_MAGIC);
+    write_u32_le(&mut out, flags[0]);
+    write_u32_le(&mut out, flags[1]);
+    write_sparse_pairs(&mut out, &literals);
+
+    let mut enc = RangeEncoder::new(&mut out);
+    let mut prev: Option<u8> = None;
+    for &b in input {
+        if prev == Some(b) {
+            enc.encode_symbol(FLAG_SAME, &flag_cum);
+        } else {
+            enc.encode_symbol(FLAG_LITERAL, &flag_cum);
+            enc.encode_symbol(b as u32, &literal_cum);
+        }
+        prev = Some(b);
+    }
+    enc.encode_symbol(FLAG_LITERAL, &flag_cum);
+    enc.encode_symbol(EOF_SYMBOL as u32, &literal_cum);
+    enc.finish();
+    Ok(out)
+}
+
+pub fn decode_rle_model(encoded: &[u8]) -> Result<Vec<u8>, Error> {
+    decode_rle_model_with_limits(encoded, &DecodeLimits::unlimited())
+}
+
+// 在 limits 约束下解码：模型表与输出合计超过上限时返回 LimitExceeded。
+// "相同"标志的概率可以非常接近 1，输出不受数据体大小约束；解码越过数据体末尾时返回 Truncated。
+pub fn decode_rle_model_with_limits(
+    encoded: &[u8],
+    limits: &DecodeLimits,
+) -> Result<Vec<u8>, Error> {
+    if encoded.len() < 12 {
+        return Err(Error::Truncated("range: input too short"));
+    }
+    if &encoded[0..4] != RLE_MODEL_MAGIC {
+        return Err(Error::BadMagic("range: bad magic"));
+    }
+    let mut pos = 4;
+    let mut flags = vec![0u32; 2];
+    for f in flags.iter_mut() {
+        *f = read_u32_le(encoded, &mut pos).ok_or(Error::Truncated("range: truncated header"))?;
+    }
+    let literals = read_sparse_pairs(encoded, &mut pos)?;
+    validate_model(&flags)?;
+    validate_model(&literals)?;
+    if flags[FLAG_LITERAL as usize] == 0 {
+        return Err(Error::InvalidModel("range: bad frequency table"));
+    }
+    let flag_cum = build_cumulative(&flags);
+    let literal_cum = build_cumulative(&literals);
+    let max_output = limits
+        .output_budget(MODEL_TABLE_BYTES)
+        .ok_or(Error::LimitExceeded("range: memory limit exceeded"))?;
+    let max_output = usize::try_from(max_output).unwrap_or(usize::MAX);
+
+    let mut dec = RangeDecoder::new(&encoded[pos..]);
+    let mut out = Vec::with_capacity(encoded.len().saturating_mul(2).min(max_output));
+    loop {
+        let flag = dec.decode_symbol(&flag_cum);
+        let sym = match flag {
+            FLAG_SAME => None,
+            _ => Some(dec.decode_symbol(&literal_cum)),
+        };
+        if dec.overrun() {
+            return Err(Error::Truncated("range: truncated data"));
+        }
+        if sym == Some(EOF_SYMBOL as u32) {
+            break;
+        }
+        if out.len() >= max_output {
+            return Err(Error::LimitExceeded("range: output limit exceeded"));
+        }
+        match sym {
+            Some(sym) => out.push(sym as u8),
+            // 第一个位置没有前一字节，不可能是"相同"。
+            None => {
+                let &prev = out
+                    .last()
+                    .ok_or(Error::InvalidData("range: bad repeat flag"))?;
+                out.push(prev);
+            }
+        }
+    }
+    Ok(out)
+}
+
+// 可复用的解码器：连续解码大量小消息时复用频率表与累积表，避免每条消息重新分配。
+#[derive(Debug, Default)]
+pub struct RangeCoder {
+    freq: Vec<u32>,
+    cumulative: Vec<u32>,
+}
+
+impl RangeCoder {
+    pub fn new() -> RangeCoder {
+        RangeCoder::default()
+    }
+
+    // 解码一条消息，结果写入 out（先清空，保留其容量）。
+    // RCNC / RCNV 格式在内部缓冲区上完成，其余格式退回 decode。
+    pub fn decode_reuse(&mut self, encoded: &[u8], out: &mut Vec<u8>) -> Result<(), Error> {
+        out.clear();
+        let magic = encoded.get(0..4);
+        let mut pos = 0;
+        if magic == Some(SPARSE_MAGIC) {
+            pos = 4;
+            read_sparse_pairs_into(encoded, &mut pos, &mut self.freq)?;
+        } else if magic == Some(FRAME_MAGIC)
+            || magic == Some(DENSE_MAGIC)
+            || magic == Some(SINGLE_MAGIC)
+            || magic == Some(FIXED16_MAGIC)
+            || magic == Some(SPARSE16_MAGIC)
+            || magic == Some(CAPPED16_MAGIC)
+        {
+            out.extend_from_slice(&decode(encoded)?);
+            return Ok(());
+        } else {
+            read_header_into(encoded, &mut pos, &mut self.freq)?;
+        }
+        if self.freq.len() != SYMBOL_LIMIT {
+            return Err(Error::InvalidModel("range: unexpected symbol count"));
+        }
+        validate_model(&self.freq)?;
+        build_cumulative_into(&self.freq, &mut self.cumulative);
+        decode_body_into(
+            &encoded[pos..],
+            &self.cumulative,
+            &DecodeOptions::new(),
+            out,
+        )
+    }
+}
+
+// 自适应格式：魔数 RCNA + 编码数据，不含频率表。
+// 模型随已编码的符号更新，解码时必须传入与编码时同类型、同参数的新模型。
+const ADAPTIVE_MAGIC: &[u8; 4] = b"RCNA";
+
+fn check_model(model: &impl Model) -> Result<(), Error> {
+    let cumulative = model.cumulative();
+    if cumulative.len() != SYMBOL_LIMIT + 1 || cumulative[SYMBOL_LIMIT] > MAX_TOTAL {
+        return Err(Error::InvalidInput("range: bad adaptive model"));
+    }
+    Ok(())
+}
+
+pub fn encode_adaptive(input: &[u8], model: impl Model) -> Result<Vec<u8>, Error> {
+    encode_adaptive_iter(input.iter().copied(), input.len() / 2 + 8, model)
+}
+
+// 逐字节从迭代器读取并编码：encode 需要先统计频率，只能先收集到缓冲区。
+pub fn encode_iter(iter: impl Iterator<Item = u8>) -> Result<Vec<u8>, Error> {
+    let input: Vec<u8> = iter.collect();
+    encode(&input)
+}
+
+// 使用 AdaptiveModel 真正单遍地编码迭代器，不缓冲输入；输出与对收集后的数据调用
+// encode_adaptive(input, AdaptiveModel::new()) 相同，用 decode_adaptive 解码。
+pub fn encode_iter_adaptive(iter: impl Iterator<Item = u8>) -> Result<Vec<u8>, Error> {
+    let capacity = iter.size_hint().0 / 2 + 8;
+    encode_adaptive_iter(iter, capacity, AdaptiveModel::new())
+}
+
+fn encode_adaptive_iter(
+    iter: impl Iterator<Item = u8>,
+    capacity: usize,
+    mut model: impl Model,
+) -> Result<Vec<u8>, Error> {
+    check_model(&model)?;
+    let mut out = Vec::with_capacity(capacity);
+    out.extend_from_slice(ADAPTIVE_MAGIC);
+    encode_adaptive_body(&mut out, iter, &mut model);
+    Ok(out)
+}
+
+// 逐字节编码并更新模型，最后编码 EOF。
+fn encode_adaptive_body(out: &mut Vec<u8>, iter: impl Iterator<Item = u8>, model: &mut impl Model) {
+    let mut enc = RangeEncoder::new(out);
+    for b in iter {
+        enc.encode_symbol(b as u32, model.cumulative());
+        model.update(b as usize);
+    }
+    enc.encode_symbol(EOF_SYMBOL as u32, model.cumulative());
+    enc.finish();
+}
+
+// 解码越过数据末尾仍未遇到 EOF 时返回 Truncated，截断或伪造的数据不会无限解码下去。
+// 模型表与输出合计超过 limits 时返回 LimitExceeded。
+fn decode_adaptive_body(
+    data: &[u8],
+    model: &mut impl Model,
+    limits: &DecodeLimits,
+) -> Result<Vec<u8>, Error> {
+    let budget = limits
+        .output_budget(MODEL_TABLE_BYTES)
+        .ok_or(Error::LimitExceeded("range: memory limit exceeded"))?;
+    let max_output = usize::try_from(budget).unwrap_or(usize::MAX);
+    let mut dec = RangeDecoder::new(data);
+    let mut out = Vec::with_capacity(data.len().saturating_mul(2).min(max_output));
+    loop {
+        let sym = dec.decode_symbol(model.cumulative()) as usize;
+        if dec.overrun() {
+            return Err(Error::Truncated("range: truncated data"));
+        }
+        if sym == EOF_SYMBOL {
+            return Ok(out);
+        }
+        if out.len() >= max_output {
+            return Err(Error::LimitExceeded("range: output limit exceeded"));
+        }
+        out.push(sym as u8);
+        model.update(sym);
+    }
+}
+
+pub fn decode_adaptive(encoded: &[u8], model: impl Model) -> Result<Vec<u8>, Error> {
+    decode_adaptive_with_limits(encoded, model, &DecodeLimits::unlimited())
+}
+
+// 在 limits 约束下解码，含义同 decode_with_limits。
+pub fn decode_adaptive_with_limits(
+    encoded: &[u8],
+    mut model: impl Model,
+    limits: &DecodeLimits,
+) -> Result<Vec<u8>, Error> {
+    check_model(&model)?;
+    if encoded.len() < 4 {
+        return Err(Error::Truncated("range: input too short"));
+    }
+    if &encoded[0..4] != ADAPTIVE_MAGIC {
+        return Err(Error::BadMagic("range: bad magic"));
+    }
+    decode_adaptive_body(&encoded[4..], &mut model, limits)
+}
+
+// 预置前缀（类似 zstd 字典）：先用 dict 的字节更新一个新的 AdaptiveModel，再以它为起点
+// 自适应地编码 input；dict 本身不写入输出，解码端用同一 dict 预热出相同的模型。
+// 格式：魔数 RCNH + 4 字节 dict 的 CRC32 + 编码数据。编解码两端的 dict 必须逐字节相同，
+// 不同时由 CRC32 检出并返回 InvalidModel。每次调用都要重新处理一遍 dict。
+const PREFIX_MAGIC: &[u8; 4] = b"RCNH";
+
+fn primed_model(dict: &[u8]) -> AdaptiveModel {
+    let mut model = AdaptiveModel::new();
+    for &b in dict {
+        model.update(b as usize);
+    }
+    model
+}
+
+pub fn encode_with_prefix(dict: &[u8], input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len() / 2 + 12);
+    out.extend_from_slice(PREFIX_MAGIC);
+    write_u32_le(&mut out, checksum::crc32(dict));
+    encode_adaptive_body(&mut out, input.iter().copied(), &mut primed_model(dict));
+    out
+}
+
+pub fn decode_with_prefix(dict: &[u8], encoded: &[u8]) -> Result<Vec<u8>, Error> {
+    decode_with_prefix_with_limits(dict, encoded, &DecodeLimits::unlimited())
+}
+
+// 在 limits 约束下解码，含义同 decode_with_limits。
+pub fn decode_with_prefix_with_limits(
+    dict: &[u8],
+    encoded: &[u8],
+    limits: &DecodeLimits,
+) -> Result<Vec<u8>, Error> {
+    if encoded.len() < 8 {
+        return Err(Error::Truncated("range: input too short"));
+    }
+    if &encoded[0..4] != PREFIX_MAGIC {
+        return Err(Error::BadMagic("range: bad magic"));
+    }
+    let mut pos = 4;
+    let id = read_u32_le(encoded, &mut pos).ok_or(Error::Truncated("range: truncated header"))?;
+    if id != checksum::crc32(dict) {
+        return Err(Error::InvalidModel("range: dictionary mismatch"));
+    }
+    decode_adaptive_body(&encoded[pos..], &mut primed_model(dict), limits)
+}
+
+// 逐比特格式：魔数 RCNB + 4 字节比特数 + 编码数据。
+// 每字节按高位在前展开为比特，以前 BIT_CONTEXT_BITS 个比特为上下文选择一个二元自适应模型，
+// 适合按 8 像素/字节打包的黑白图像等 1 位数据。比特数已知，无需 EOF。
+const BITS_MAGIC: &[u8; 4] = b"RCNB";
+const BIT_CONTEXT_BITS: u32 = 12;
+// 每个数据体字节最多能解出的比特数。二元模型的概率不超过 1 - 1/BIT_LIMIT，
+// 每比特至少占 1/(BIT_LIMIT·ln 2) 位信息，一个字节约 2.3 万个比特，这里取 2^15 留出余量。
+const MAX_BITS_PER_BODY_BYTE: usize = 1 << 15;
+
+fn bit_models() -> Vec<BitModel> {
+    vec![BitModel::new(); 1 << BIT_CONTEXT_BITS]
+}
+
+// 编码 packed 的前 bit_count 个比特；最后一个字节中多余的低位被忽略。
+pub fn encode_bits_packed(packed: &[u8], bit_count: usize) -> Result<Vec<u8>, Error> {
+    if bit_count.div_ceil(8) > packed.len() {
+        return Err(Error::InvalidInput("range: bit count exceeds input"));
+    }
+    let count =
+        u32::try_from(bit_count).map_err(|_| Error::InvalidInput("range: too many bits"))?;
+    let mut out = Vec::with_capacity(bit_count / 16 + 16);
+    out.extend_from_slice(BITS_MAGIC);
+    write_u32_le(&mut out, count);
+
+    let mut models = bit_models();
+    let mask = (1 << BIT_CONTEXT_BITS) - 1;
+    let mut context = 0usize;
+    let mut enc = RangeEncoder::new(&mut out);
+    for i in 0..bit_count {
+        let bit = ((packed[i / 8] >> (7 - i % 8)) & 1) as u32;
+        enc.encode_symbol(bit, models[context].cumulative());
+        models[context].update(bit);
+        context = ((context << 1) | bit as usize) & mask;
+    }
+    enc.finish();
+    Ok(out)
+}
+
+// 返回 (打包后的字节, 比特数)；最后一个字节中多余的低位为 0。
+pub fn decode_bits_packed(encoded: &[u8]) -> Result<(Vec<u8>, usize), Error> {
+    decode_bits_packed_with_limits(encoded, &DecodeLimits::unlimited())
+}
+
+// 在 limits 约束下解码：上下文模型表与打包后的输出合计超过上限时返回 LimitExceeded。
+// 比特数超出数据体所能容纳的上限时在分配之前返回 InvalidData，解码越过数据体末尾时返回 Truncated。
+pub fn decode_bits_packed_with_limits(
+    encoded: &[u8],
+    limits: &DecodeLimits,
+) -> Result<(Vec<u8>, usize), Error> {
+    if encoded.len() < 8 {
+        return Err(Error::Truncated("range: input too short"));
+    }
+    if &encoded[0..4] != BITS_MAGIC {
+        return Err(Error::BadMagic("range: bad magic"));
+    }
+    let mut pos = 4;
+    let bit_count =
+        read_u32_le(encoded, &mut pos).ok_or(Error::Truncated("range: truncated header"))? as usize;
+    let body = &encoded[pos..];
+    if bit_count > body.len().saturating_mul(MAX_BITS_PER_BODY_BYTE) {
+        return Err(Error::InvalidData("range: bit count exceeds body"));
+    }
+    let table_bytes = ((1usize << BIT_CONTEXT_BITS) * core::mem::size_of::<BitModel>()) as u64;
+    let budget = limits
+        .output_budget(table_bytes)
+        .ok_or(Error::LimitExceeded("range: memory limit exceeded"))?;
+    if bit_count.div_ceil(8) as u64 > budget {
+        return Err(Error::LimitExceeded("range: output limit exceeded"));
+    }
+
+    let mut models = bit_models();
+    let mask = (1 << BIT_CONTEXT_BITS) - 1;
+    let mut context = 0usize;
+    let mut dec = RangeDecoder::new(body);
+    let mut packed = vec![0u8; bit_count.div_ceil(8)];
+    for i in 0..bit_count {
+        let bit = dec.decode_symbol(models[context].cumulative());
+        if dec.overrun() {
+            return Err(Error::Truncated("range: truncated data"));
+        }
+        models[context].update(bit);
+        packed[i / 8] |= (bit as u8) << (7 - i % 8);
+        context = ((context << 1) | bit as usize) & mask;
+    }
+    Ok((packed, bit_count))
+}
+
+// 分块格式：魔数 RCNP + 4 字节块大小 + 4 字节块数 n + n 组 (4 字节偏移, 4 字节长度) 索引
+// + 各块的 encode 输出依次拼接。偏移相对于索引之后的数据区起点。
+// 每块都是自带频率表并以 EOF 结束的完整数据流，可以独立解码。
+const PARALLEL_MAGIC: &[u8; 4] = b"RCNP";
+
+// 可用核心数；no_std 下为 1。
+fn default_threads() -> usize {
+    #[cfg(feature = "std")]
+    return std::thread::available_parallelism().map_or(1, |n| n.get());
+    #[cfg(not(feature = "std"))]
+    1
+}
+
+// 对序号 0..count 的块执行 work，结果按序号返回。std 下用 threads 个线程，
+// 第 t 个线程负责序号 i % threads == t 的块；no_std 下依次执行。
+fn run_blocks<T: Send>(
+    count: usize,
+    threads: usize,
+    work: impl Fn(usize) -> Result<T, Error> + Sync,
+) -> Result<Vec<T>, Error> {
+    #[cfg(feature = "std")]
+    {
+        let threads = threads.clamp(1, count.max(1));
+        let mut results: Vec<Option<T>> = (0..count).map(|_| None).collect();
+        std::thread::scope(|scope| -> Result<(), Error> {
+            let workers: Vec<_> = (0..threads)
+                .map(|t| {
+                    let work = &work;
+                    scope.spawn(move || {
+                        (t..count)
+                            .step_by(threads)
+                            .map(|i| work(i).map(|r| (i, r)))
+                            .collect::<Result<Vec<_>, Error>>()
+                    })
+                })
+                .collect();
+            for worker in workers {
+                for (i, r) in worker.join().expect("range: block thread panicked")? {
+                    results[i] = Some(r);
+                }
+            }
+            Ok(())
+        })?;
+        Ok(results.into_iter().map(Option::unwrap).collect())
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        let _ = threads;
+        (0..count).map(work).collect()
+    }
+}
+
+// 将输入按 block_size 切块并用所有可用核心并行编码。
+// 输入不超过一块时直接返回 encode 的结果。
+pub fn encode_parallel(input: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+    encode_parallel_with_threads(input, block_size, default_threads())
+}
+
+// 与 encode_parallel 相同，但指定线程数。
+// 输出与线程数无关、逐字节确定：块的划分只取决于 block_size，各块独立编码，
+// 最后按块序号拼接并写出索引，因此适合内容寻址存储等需要可复现输出的场景。
+pub fn encode_parallel_with_threads(
+    input: &[u8],
+    block_size: usize,
+    threads: usize,
+) -> Result<Vec<u8>, Error> {
+    if block_size == 0 || block_size > u32::MAX as usize {
+        return Err(Error::InvalidInput("range: bad block size"));
+    }
+    if input.len() <= block_size {
+        return encode(input);
+    }
+
+    let blocks: Vec<&[u8]> = input.chunks(block_size).collect();
+    let encoded = run_blocks(blocks.len(), threads, |i| encode(blocks[i]))?;
+
+    let total: usize = encoded.iter().map(|e| e.len()).sum();
+    if total > u32::MAX as usize {
+        return Err(Error::InvalidInput(
+            "range: input too large for block index",
+        ));
+    }
+    let mut out = Vec::with_capacity(12 + encoded.len() * 8 + total);
+    out.extend_from_slice(PARALLEL_MAGIC);
+    write_u32_le(&mut out, block_size as u32);
+    write_u32_le(&mut out, encoded.len() as u32);
+    let mut offset = 0u32;
+    for e in &encoded {
+        write_u32_le(&mut out, offset);
+        write_u32_le(&mut out, e.len() as u32);
+        offset += e.len() as u32;
+    }
+    for e in &encoded {
+        out.extend_from_slice(e);
+    }
+    Ok(out)
+}
+
+// 解码 encode_parallel 的输出（单块时即普通数据流），各块用所有可用核心并行解码。
+pub fn decode_parallel(encoded: &[u8]) -> Result<Vec<u8>, Error> {
+    decode_parallel_with_threads(encoded, default_threads())
+}
+
+// 与 decode_parallel 相同，但指定线程数；threads 为 1 时在当前线程依次解码。
+// 每块都以自己的 EOF 结束，块边界不会影响相邻块的解码。
+pub fn decode_parallel_with_threads(encoded: &[u8], threads: usize) -> Result<Vec<u8>, Error> {
+    decode_blocks(encoded, threads, usize::MAX)
+}
+
+// 在 limits 约束下解码（按单块的模型表计入）：块数与块大小表明输出必然超过上限时在解码之前返回
+// LimitExceeded，每块的输出也不超过剩余额度。
+pub fn decode_parallel_with_limits(
+    encoded: &[u8],
+    limits: &DecodeLimits,
+) -> Result<Vec<u8>, Error> {
+    let budget = limits
+        .output_budget(MODEL_TABLE_BYTES)
+        .ok_or(Error::LimitExceeded("range: memory limit exceeded"))?;
+    decode_blocks(
+        encoded,
+        default_threads(),
+        usize::try_from(budget).unwrap_or(usize::MAX),
+    )
+}
+
+fn decode_blocks(encoded: &[u8], threads: usize, max_output: usize) -> Result<Vec<u8>, Error> {
+    if encoded.len() < 4 || &encoded[0..4] != PARALLEL_MAGIC {
+        return decode_with_limit(encoded, max_output);
+    }
+    let mut pos = 4;
+    let block_size =
+        read_u32_le(encoded, &mut pos).ok_or(Error::Truncated("range: truncated header"))?;
+    let count =
+        read_u32_le(encoded, &mut pos).ok_or(Error::Truncated("range: truncated header"))?;
+    if block_size == 0 || count == 0 {
+        return Err(Error::InvalidData("range: bad block index"));
+    }
+    // 除最后一块外每块恰好 block_size 字节。
+    if (count as u64 - 1) * block_size as u64 > max_output as u64 {
+        return Err(Error::LimitExceeded("range: output limit exceeded"));
+    }
+    let mut index = Vec::with_capacity((count as usize).min(encoded.len() / 8));
+    for _ in 0..count {
+        let offset = read_u32_le(encoded, &mut pos)
+            .ok_or(Error::Truncated("range: truncated block index"))?;
+        let len = read_u32_le(encoded, &mut pos)
+            .ok_or(Error::Truncated("range: truncated block index"))?;
+        index.push((offset as usize, len as usize));
+    }
+    let data = &encoded[pos..];
+    let blocks = index
+        .iter()
+        .map(|&(offset, len)| {
+            data.get(offset..offset + len)
+                .ok_or(Error::Truncated("range: truncated block"))
+        })
+        .collect::<Result<Vec<&[u8]>, Error>>()?;
+
+    let opts = DecodeOptions::new().with_max_output((block_size as usize).min(max_output));
+    let decoded = run_blocks(blocks.len(), threads, |i| {
+        let decoded = decode_with_options(blocks[i], &opts)?;
+        // 除最后一块外每块都必须恰好是 block_size 字节。
+        if i + 1 < blocks.len() && decoded.len() != block_size as usize {
+            return Err(Error::InvalidData("range: bad block length"));
+        }
+        Ok(decoded)
+    })?;
+    if decoded.iter().map(Vec::len).sum::<usize>() > max_output {
+        return Err(Error::LimitExceeded("range: output limit exceeded"));
+    }
+    Ok(decoded.concat())
+}
+
+// 尽可能解码（可能被截断的）数据流的前缀，用于损坏归档的恢复。
+// 只输出能被确定为正确的字节；第二个返回值表示是否完整解码到 EOF 符号。
+pub fn decode_lossy(encoded: &[u8]) -> (Vec<u8>, bool) {
+    if encoded.get(0..4) == Some(SINGLE_MAGIC) {
+        // 单字节格式只有头部，要么完整要么无法恢复任何字节。
+        return match decode_single(encoded, &DecodeOptions::new()) {
+            Ok((out, _)) => (out, true),
+            Err(_) => (Vec::new(), false),
+        };
+    }
+    let mut pos: usize = 0;
+    let freq = match read_header(encoded, &mut pos) {
+        Ok(freq) if freq.len() == SYMBOL_LIMIT && validate_model(&freq).is_ok() => freq,
+        _ => return (Vec::new(), false),
+    };
+    let cumulative = build_cumulative(&freq);
+
+    let mut dec = RangeDecoder::new(&encoded[pos..]);
+    let mut out = Vec::with_capacity(encoded.len());
+    loop {
+        match dec.decode_certain_symbol(&cumulative) {
+            Some(sym) if sym as usize == EOF_SYMBOL => return (out, true),
+            Some(sym) => out.push(sym as u8),
+            None => return (out, false),
+        }
+    }
+}
+
+// 流式编解码每次从 reader 读入的字节数。
+#[cfg(feature = "std")]
+const STREAM_CHUNK: usize = 1 << 16;
+// 解码一个符号时 fit 与重归一化合计移入字节数的上界（fit 至多 4 轮、每轮至多 4 字节，
+// 更新后再至多 4 字节），留有余量。缓冲区中剩余字节少于此数且 reader 未结束时先补充输入。
+#[cfg(feature = "std")]
+const SYMBOL_LOOKAHEAD: usize = 32;
+
+// 从 reader 读满 buf（清空后追加至多 limit 字节），返回是否已到达输入末尾。
+#[cfg(feature = "std")]
+fn fill_chunk(input: &mut impl Read, buf: &mut Vec<u8>, limit: usize) -> io::Result<bool> {
+    let want = limit.saturating_sub(buf.len());
+    let n = input.by_ref().take(want as u64).read_to_end(buf)?;
+    Ok(n < want)
+}
+
+// 流式编码：输出与 encode 对同一数据的结果逐字节相同，内存占用与输入大小无关。
+// 头部的频率表需要先统计整个输入，因此 input 须可 Seek：第一遍从当前位置读到末尾统计频率，
+// 随后回到起始位置再读一遍编码。两遍之间输入被修改时返回 InvalidInput。
+#[cfg(feature = "std")]
+pub fn encode_to_writer<R: Read + Seek, W: Write>(input: &mut R, out: &mut W) -> Result<(), Error> {
+    let start = input.stream_position()?;
+    let mut counts = [0u64; 256];
+    let mut buf = Vec::with_capacity(STREAM_CHUNK);
+    loop {
+        buf.clear();
+        let done = fill_chunk(input, &mut buf, STREAM_CHUNK)?;
+        for &b in &buf {
+            counts[b as usize] += 1;
+        }
+        if done {
+            break;
+        }
+    }
+    input.seek(SeekFrom::Start(start))?;
+
+    let len: u64 = counts.iter().sum();
+    let mut out = io::BufWriter::with_capacity(STREAM_CHUNK, out);
+    if let Some(value) = (0..256).find(|&b| len > 0 && counts[b] == len) {
+        out.write_all(&encode_single(value as u8, len))?;
+        out.flush()?;
+        return Ok(());
+    }
+
+    // 超出 u32 的计数先整体右移（非零项至少保留 1），之后与 build_frequencies 相同。
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let shift = (64 - max.leading_zeros()).saturating_sub(32);
+    let mut freq = vec![0u32; SYMBOL_LIMIT];
+    for (f, &c) in freq.iter_mut().zip(&counts) {
+        if c != 0 {
+            *f = (c >> shift).max(1) as u32;
+        }
+    }
+    freq[EOF_SYMBOL] = 1;
+    scale_frequencies(&mut freq);
+    let cumulative = build_cumulative(&freq);
+    let mut header = Vec::with_capacity(8 + 4 * SYMBOL_LIMIT);
+    write_header(&mut header, &freq);
+    out.write_all(&header)?;
+
+    let mut enc = RangeEncoder::new(&mut out);
+    let mut seen = 0u64;
+    loop {
+        buf.clear();
+        let done = fill_chunk(input, &mut buf, STREAM_CHUNK)?;
+        seen += buf.len() as u64;
+        if seen > len || buf.iter().any(|&b| freq[b as usize] == 0) {
+            return Err(Error::InvalidInput("range: input changed during encoding"));
+        }
+        for &b in &buf {
+            enc.encode_symbol(b as u32, &cumulative);
+        }
+        enc.take_error()?;
+        if done {
+            break;
+        }
+    }
+    if seen != len {
+        return Err(Error::InvalidInput("range: input changed during encoding"));
+    }
+    enc.encode_symbol(EOF_SYMBOL as u32, &cumulative);
+    enc.finish();
+    enc.take_error()?;
+    out.flush()?;
+    Ok(())
+}
+
+// 流式解码 RCNC（encode_to_writer / encode 的输出）与 RCNS 格式，按 STREAM_CHUNK 分块读入，
+// 内存占用与数据大小无关；其他格式返回 InvalidInput。
+// 解码在数据流结束处停止，但 input 中位于其后的字节可能已被读取。
+// 与 decode 不同，数据体被截断时返回 Truncated，而不是以补零的方式继续解码。
+#[cfg(feature = "std")]
+pub fn decode_from_reader<R: Read, W: Write>(input: &mut R, out: &mut W) -> Result<(), Error> {
+    let mut header = [0u8; 13];
+    input
+        .read_exact(&mut header[..4])
+        .map_err(|e| Error::from_read(e, "range: input too short"))?;
+    if &header[..4] == SINGLE_MAGIC {
+        input
+            .read_exact(&mut header[4..])
+            .map_err(|e| Error::from_read(e, "range: truncated header"))?;
+        let (value, mut remaining) = read_single_header(&header)?;
+        let run = [value; STREAM_CHUNK];
+        while remaining > 0 {
+            let n = remaining.min(STREAM_CHUNK as u64) as usize;
+            out.write_all(&run[..n])?;
+            remaining -= n as u64;
+        }
+        out.flush()?;
+        return Ok(());
+    }
+    if &header[..4] != b"RCNC" {
+        // 本 crate 的其他格式（RCNV、RCNA 等）均以 RCN 开头。
+        return Err(match header.starts_with(b"RCN") {
+            true => Error::InvalidInput("range: format not supported by streaming decode"),
+            false => Error::BadMagic("range: bad magic"),
+        });
+    }
+    input
+        .read_exact(&mut header[4..8])
+        .map_err(|e| Error::from_read(e, "range: truncated header"))?;
+    let count = u32::from_le_bytes(header[4..8].try_into().unwrap());
+    if count as usize != SYMBOL_LIMIT {
+        return Err(Error::InvalidModel("range: unexpected symbol count"));
+    }
+    let mut table = vec![0u8; 4 * SYMBOL_LIMIT];
+    input
+        .read_exact(&mut table)
+        .map_err(|e| Error::from_read(e, "range: truncated frequencies"))?;
+    let freq: Vec<u32> = table
+        .chunks_exact(4)
+        .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+        .collect();
+    validate_model(&freq)?;
+    let cumulative = build_cumulative(&freq);
+
+    let mut buf = Vec::with_capacity(STREAM_CHUNK);
+    let mut decoded = Vec::with_capacity(STREAM_CHUNK);
+    // 换用新缓冲区时保存的 (low, high, code, code_max)。
+    let mut registers = None;
+    loop {
+        let done = fill_chunk(input, &mut buf, STREAM_CHUNK)?;
+        if registers.is_none() && buf.is_empty() {
+            break;
+        }
+        let mut dec = match registers {
+            None => RangeDecoder::new(&buf),
+            Some((low, high, code, code_max)) => RangeDecoder {
+                low,
+                high,
+                code,
+                code_max,
+                data: &buf,
+                pos: 0,
+                overrun: false,
+            },
+        };
+        let refill_at = match done {
+            true => usize::MAX,
+            false => buf.len() - SYMBOL_LOOKAHEAD,
+        };
+        while dec.pos <= refill_at {
+            let sym = dec.decode_symbol(&cumulative) as usize;
+            if sym == EOF_SYMBOL {
+                out.write_all(&decoded)?;
+                out.flush()?;
+                return Ok(());
+            }
+            // 有效数据流在解出 EOF 之前不会越过末尾；code 与 code_max 不同说明已在补位。
+            if dec.code != dec.code_max {
+                return Err(Error::Truncated("range: truncated body"));
+            }
+            decoded.push(sym as u8);
+            if decoded.len() >= STREAM_CHUNK {
+                out.write_all(&decoded)?;
+                decoded.clear();
+            }
+        }
+        registers = Some((dec.low, dec.high, dec.code, dec.code_max));
+        let consumed = dec.pos;
+        buf.drain(..consumed);
+    }
+    out.flush()?;
+    Ok(())
+}
+
+// 流式读取输入文件编码后写入输出文件，内存占用与文件大小无关。
+#[cfg(feature = "std")]
+pub fn encode_file(input_path: &str, output_path: &str) -> Result<(), Error> {
+    if input_path == STDIO_PATH {
+        // 标准输入无法回到起点读第二遍，整体读入内存后编码，输出与 encode_to_writer 相同。
+        let data = read_input(input_path, u64::MAX)?;
+        return write_output(output_path, &encode(&data)?);
+    }
+    let mut input = fs::File::open(input_path)?;
+    let mut output = create_output(output_path)?;
+    encode_to_writer(&mut input, &mut output)?;
+    output.flush()?;
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+pub fn decode_file(input_path: &str, output_path: &str) -> Result<(), Error> {
+    decode_file_with_limits(input_path, output_path, &DecodeLimits::unlimited())
+}
+
+// 输入文件整体读入内存，其大小同样计入 limits。
+#[cfg(feature = "std")]
+pub fn decode_file_with_limits(
+    input_path: &str,
+    output_path: &str,
+    limits: &DecodeLimits,
+) -> Result<(), Error> {
+    let data = read_input(input_path, limits.max_memory())?;
+    let remaining = limits
+        .output_budget(data.len() as u64)
+        .ok_or(Error::LimitExceeded("range: memory limit exceeded"))?;
+    let decoded = decode_with_limits(&data, &DecodeLimits::new(remaining))?;
+    write_output(output_path, &decoded)
+}
+
+// 文件接口的路径为 "-" 时，输入读自标准输入、输出写到标准输出（与 huffman、rle 相同），
+// 按原始字节读写，不做任何换行或编码转换。
+#[cfg(feature = "std")]
+const STDIO_PATH: &str = "-";
+
+#[cfg(feature = "std")]
+fn create_output(output_path: &str) -> io::Result<io::BufWriter<Box<dyn Write>>> {
+    let output: Box<dyn Write> = if output_path == STDIO_PATH {
+        Box::new(io::stdout().lock())
+    } else {
+        Box::new(fs::File::create(output_path)?)
+    };
+    Ok(io::BufWriter::new(output))
+}
+
+// 读入整个输入，超过 max_len 字节时返回 LimitExceeded；文件在读入之前按元数据检查。
+#[cfg(feature = "std")]
+fn read_input(input_path: &str, max_len: u64) -> Result<Vec<u8>, Error> {
+    let mut data = Vec::new();
+    if input_path == STDIO_PATH {
+        io::stdin()
+            .lock()
+            .take(max_len.saturating_add(1))
+            .read_to_end(&mut data)?;
+    } else {
+        let mut file = fs::File::open(input_path)?;
+        if file.metadata()?.len() > max_len {
+            return Err(Error::LimitExceeded("range: memory limit exceeded"));
+        }
+        file.read_to_end(&mut data)?;
+    }
+    if data.len() as u64 > max_len {
+        return Err(Error::LimitExceeded("range: memory limit exceeded"));
+    }
+    Ok(data)
+}
+
+#[cfg(feature = "std")]
+fn write_output(output_path: &str, data: &[u8]) -> Result<(), Error> {
+    let mut writer = create_output(output_path)?;
+    writer.write_all(data)?;
+    writer.flush()?;
+    Ok(())
+}
+
+// 由语料文件训练模型并写入 model_path，模型文件格式与 Dictionary::to_bytes 相同（RCNC 头部，无数据体）。
+#[cfg(feature = "std")]
+pub fn train_model_file(corpus_path: &str, model_path: &str) -> Result<(), Error> {
+    let corpus = fs::read(corpus_path)?;
+    fs::write(model_path, train_dictionary(&[&corpus]).to_bytes())?;
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+fn read_model_file(model_path: &str) -> Result<Dictionary, Error> {
+    Dictionary::from_bytes(&fs::read(model_path)?)
+}
+
+// 使用模型文件编码，输出与 encode_with_dictionary 相同。
+#[cfg(feature = "std")]
+pub fn encode_with_model_file(
+    input_path: &str,
+    output_path: &str,
+    model_path: &str,
+) -> Result<(), Error> {
+    let dict = read_model_file(model_path)?;
+    let data = fs::read(input_path)?;
+    fs::write(output_path, encode_with_dictionary(&data, &dict)?)?;
+    Ok(())
+}
+
+// 解码 encode_with_model_file 的输出，需使用与编码时相同的模型文件。
+#[cfg(feature = "std")]
+pub fn decode_with_model_file(
+    input_path: &str,
+    output_path: &str,
+    model_path: &str,
+) -> Result<(), Error> {
+    let dict = read_model_file(model_path)?;
+    let data = fs::read(input_path)?;
+    fs::write(output_path, decode_with_dictionary(&data, &dict)?)?;
+    Ok(())
+}
+
+// 与 encode_file 输出相同，但通过内存映射读取输入，避免大文件的读缓冲拷贝。
+// 无法映射时（空文件、特殊文件或平台不支持）回退到 encode_file。
+#[cfg(feature = "mmap")]
+pub fn encode_file_mmap(input_path: &str, output_path: &str) -> Result<(), Error> {
+    let file = fs::File::open(input_path)?;
+    // SAFETY: 映射只读使用；若其他进程在编码期间修改该文件，结果未定义，调用方需保证输入文件不被并发修改。
+    let map = match unsafe { memmap2::Mmap::map(&file) } {
+        Ok(map) => map,
+        Err(_) => return encode_file(input_path, output_path),
+    };
+    let encoded = encode(&map)?;
+    fs::write(output_path, encoded)?;
+    Ok(())
+}
+
+// 以 bytes::Bytes 收发数据，便于接入 tokio / hyper 等基于 bytes 的管道。
+// 输出与 encode / decode 相同；结果 Vec 的缓冲区直接转为 Bytes，不再复制。
+#[cfg(feature = "bytes")]
+pub fn encode_bytes(input: &[u8]) -> Result<bytes::Bytes, Error> {
+    encode(input).map(bytes::Bytes::from)
+}
+
+#[cfg(feature = "bytes")]
+pub fn decode_bytes(input: bytes::Bytes) -> Result<bytes::Bytes, Error> {
+    decode(&input).map(bytes::Bytes::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_common::test_util::assert_bytes_eq;
+
+    #[test]
+    fn roundtrip_empty() {
+        let data: Vec<u8> = Vec::new();
+        let enc = encode(&data).unwrap();
+        let dec = decode(&enc).unwrap();
+        assert_bytes_eq(&data, &dec);
+    }
+
+    #[test]
+    fn roundtrip_random() {
+        use rand::RngCore;
+        use rand::SeedableRng;
+        let mut data = vec![0u8; 10000];
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        rng.fill_bytes(&mut data);
+        let enc = encode(&data).unwrap();
+        let dec = decode(&enc).unwrap();
+        assert_bytes_eq(&data, &dec);
+    }
+
+    #[test]
+    fn merge_single_byte_histograms() {
+        let a = build_frequencies(b"aaaa");
+        let b = build_frequencies(b"bb");
+        let merged = merge_frequencies(&a, &b);
+        assert_eq!(merged.len(), SYMBOL_LIMIT);
+        assert_eq!(merged[b'a' as usize], 4);
+        assert_eq!(merged[b'b' as usize], 2);
+        assert_eq!(merged[EOF_SYMBOL], 2);
+        assert_eq!(merged.iter().sum::<u32>(), 8);
+
+        let saturated = merge_frequencies(&[u32::MAX, 1], &[1]);
+        assert_eq!(saturated, vec![u32::MAX, 1]);
+    }
+
+    #[test]
+    fn recommend_max_total_bounds() {
+        let small = build_frequencies(b"0123456789");
+        assert_eq!(recommend_max_total(&small), 16);
+
+        let mut skewed = vec![1u32; SYMBOL_LIMIT];
+        skewed[0] = 100_000_000;
+        let large = recommend_max_total(&skewed);
+        assert!(large > recommend_max_total(&small));
+        assert_eq!(large, MAX_TOTAL);
+
+        assert_eq!(recommend_max_total(&[0; SYMBOL_LIMIT]), 1);
+    }
+
+    #[test]
+    fn minimal_frequency_run_at_max_total() {
+        // 总和恰为 MAX_TOTAL，'a' 与 EOF 的频率都是 1：每个 'a' 分到的宽度都处于精度极限。
+        let mut freq = vec![0u32; SYMBOL_LIMIT];
+        freq[b'a' as usize] = 1;
+        freq[b'b' as usize] = MAX_TOTAL - 2;
+        freq[EOF_SYMBOL] = 1;
+        let cumulative = build_cumulative(&freq);
+        let mut data = vec![b'a'; 5000];
+        data.extend_from_slice(&[b'b'; 3000]);
+        data.extend((0..4000).map(|i| if i % 3 == 0 { b'a' } else { b'b' }));
+
+        let mut body = Vec::new();
+        let mut enc = RangeEncoder::new(&mut body);
+        for &b in &data {
+            enc.encode_symbol(b as u32, &cumulative);
+            assert!(enc.low <= enc.high);
+        }
+        enc.encode_symbol(EOF_SYMBOL as u32, &cumulative);
+        enc.finish();
+        let decoded = decode_body(&body, &cumulative, &DecodeOptions::new()).unwrap();
+        assert_bytes_eq(&data, &decoded);
+    }
+
+    #[test]
+    fn extreme_skew_near_entropy() {
+        // 所有 256 个字节值都出现，其中一个占 99.9%；总数超过 MAX_TOTAL，需要缩放。
+        let n = 1usize << 25;
+        let data: Vec<u8> = (0..n)
+            .map(|i| {
+                if i % 1000 == 0 {
+                    (i / 1000 % 255 + 1) as u8
+                } else {
+                    0
+                }
+            })
+            .collect();
+        let mut hist = vec![0u64; 256];
+        for &b in &data {
+            hist[b as usize] += 1;
+        }
+        assert!(hist.iter().all(|&c| c > 0));
+        let entropy_bits: f64 = hist
+            .iter()
+            .map(|&c| {
+                let p = c as f64 / n as f64;
+                -(c as f64) * p.log2()
+            })
+            .sum();
+
+        let (enc, stats) = encode_with_stats(&data).unwrap();
+        assert!(stats.diagnostics.original_total > MAX_TOTAL as u64);
+        assert!(
+            !stats.diagnostics.is_significant(),
+            "{:?}",
+            stats.diagnostics
+        );
+        let body_bits = ((stats.output_len - stats.header_len) * 8) as f64;
+        assert!(
+            body_bits <= entropy_bits * 1.01 + 64.0,
+            "{body_bits} vs {entropy_bits}"
+        );
+        assert_bytes_eq(&data, &decode(&enc).unwrap());
+    }
+
+    #[test]
+    fn normalize_caps_total() {
+        let mut freq = merge_frequencies(&[MAX_TOTAL, 0, 3], &[MAX_TOTAL, 0, 5]);
+        normalize(&mut freq);
+        assert!(freq.iter().map(|&f| f as u64).sum::<u64>() <= MAX_TOTAL as u64);
+        assert_eq!(freq[1], 0);
+        assert!(freq[2] >= 1);
+    }
+
+    #[test]
+    fn decode_lossy_complete_stream() {
+        let data: Vec<u8> = (0..5000u32).map(|i| (i * 31 + 7) as u8).collect();
+        let enc = encode(&data).unwrap();
+        assert_eq!(decode_lossy(&enc), (data, true));
+    }
+
+    #[test]
+    fn decode_lossy_recovers_prefix_of_truncated_stream() {
+        use rand::RngCore;
+        use rand::SeedableRng;
+        let mut data = vec![0u8; 20000];
+        rand::rngs::StdRng::seed_from_u64(2).fill_bytes(&mut data);
+        let enc = encode(&data).unwrap();
+        let header_len = 8 + SYMBOL_LIMIT * 4;
+        let body_len = enc.len() - header_len;
+
+        for cut in [1, 2, 100, body_len / 2, body_len - 8] {
+            let (recovered, complete) = decode_lossy(&enc[..header_len + cut]);
+            assert!(!complete);
+            assert!(recovered.len() <= data.len());
+            assert_eq!(recovered[..], data[..recovered.len()]);
+            // 随机数据接近 1 字节输入对应 1 字节输出，截断后恢复的前缀应接近截断位置。
+            assert!(
+                recovered.len() + 8 >= cut.min(data.len()),
+                "cut {cut}: {}",
+                recovered.len()
+            );
+        }
+
+        // 结尾的 4 个 flush 字节并非都必需：缺少最后一个字节时仍能确定全部内容。
+        assert_eq!(decode_lossy(&enc[..enc.len() - 1]), (data, true));
+        assert_eq!(decode_lossy(&enc[..header_len - 1]), (Vec::new(), false));
+    }
+
+    #[test]
+    fn encode_with_default_options_matches_encode() {
+        let data = b"hello hello hello range coder".to_vec();
+        let enc = encode_with_options(&data, &EncodeOptions::new()).unwrap();
+        assert_eq!(enc, encode(&data).unwrap());
+    }
+
+    #[test]
+    fn encode_with_options_roundtrip_combinations() {
+        use rand::RngCore;
+        use rand::SeedableRng;
+        let mut random = vec![0u8; 4096];
+        rand::rngs::StdRng::seed_from_u64(3).fill_bytes(&mut random);
+        let inputs: Vec<Vec<u8>> = vec![
+            Vec::new(),
+            b"a".to_vec(),
+            b"abracadabra abracadabra".repeat(20),
+            random,
+        ];
+        for format in [
+            FormatVersion::Classic,
+            FormatVersion::SparseVarint,
+            FormatVersion::Sparse16,
+            FormatVersion::DenseRemap,
+            FormatVersion::Fixed16,
+            FormatVersion::Capped16,
+        ] {
+            for crc in [false, true] {
+                for stored in [false, true] {
+                    let opts = EncodeOptions::new()
+                        .with_crc(crc)
+                        .with_stored_fallback(stored)
+                        .format(format);
+                    for data in &inputs {
+                        let enc = encode_with_options(data, &opts).unwrap();
+                        assert_eq!(decode(&enc).unwrap(), *data, "{opts:?}");
+                        if stored {
+                            assert!(enc.len() <= data.len() + 9, "{opts:?}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sparse_header_smaller_for_few_symbols() {
+        let data = b"0123456789".repeat(10);
+        let classic = encode(&data).unwrap();
+        let sparse = encode_with_options(
+            &data,
+            &EncodeOptions::new().format(FormatVersion::SparseVarint),
+        )
+        .unwrap();
+        assert!(sparse.len() + 900 < classic.len());
+        assert_eq!(&sparse[..4], b"RCNV");
+    }
+
+    #[test]
+    fn dense_remap_digits() {
+        let data = b"3141592653589793238462643383279502884197".repeat(40);
+        let sparse = encode_with_options(
+            &data,
+            &EncodeOptions::new().format(FormatVersion::SparseVarint),
+        )
+        .unwrap();
+        let dense = encode_with_options(
+            &data,
+            &EncodeOptions::new().format(FormatVersion::DenseRemap),
+        )
+        .unwrap();
+        assert_eq!(&dense[..4], b"RCNM");
+
+        let mut pos = 0;
+        let (alphabet, freq) = read_dense_header(&dense, &mut pos).unwrap();
+        assert_eq!(alphabet, b"0123456789");
+        assert_eq!(freq.len(), 11);
+        assert!(pos < 40);
+        assert!(dense.len() < sparse.len());
+        assert_bytes_eq(&data, &decode(&dense).unwrap());
+    }
+
+    #[test]
+    fn rle_model_beats_rle_then_range() {
+        let mut data = Vec::new();
+        for i in 0..2000u32 {
+            let run = 1 + (i * 7 % 13) as usize;
+            data.extend(std::iter::repeat_n((i % 5) as u8 + b'a', run));
+        }
+        let mut rle = rle::RleEncoder::new(Vec::new());
+        for &b in &data {
+            rle.push(b).unwrap();
+        }
+        let chained = encode(&rle.finish().unwrap()).unwrap();
+
+        let enc = encode_rle_model(&data).unwrap();
+        assert!(
+            enc.len() < chained.len(),
+            "{} >= {}",
+            enc.len(),
+            chained.len()
+        );
+        assert_bytes_eq(&data, &decode_rle_model(&enc).unwrap());
+        for data in [&b""[..], b"a", b"ab", b"aaab"] {
+            assert_eq!(
+                decode_rle_model(&encode_rle_model(data).unwrap()).unwrap(),
+                data
+            );
+        }
+
+        assert!(matches!(
+            decode_rle_model(&enc[..enc.len() - 1]),
+            Err(Error::Truncated(_))
+        ));
+        let limits = DecodeLimits::new(MODEL_TABLE_BYTES + data.len() as u64);
+        assert_bytes_eq(&data, &decode_rle_model_with_limits(&enc, &limits).unwrap());
+        let limits = DecodeLimits::new(MODEL_TABLE_BYTES + data.len() as u64 - 1);
+        assert!(matches!(
+            decode_rle_model_with_limits(&enc, &limits),
+            Err(Error::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn metadata_roundtrip() {
+        let data = b"payload with metadata ".repeat(30);
+        let metadata = b"content-type: text/plain; ts=1700000000";
+        let enc = encode_with_metadata(&data, metadata).unwrap();
+        assert_eq!(read_metadata(&enc).unwrap(), metadata);
+        assert_bytes_eq(&data, &decode(&enc).unwrap());
+
+        let opts = EncodeOptions::new().with_crc(true).with_metadata(b"");
+        let enc = encode_with_options(&data, &opts).unwrap();
+        assert_eq!(read_metadata(&enc).unwrap(), b"");
+        assert_bytes_eq(&data, &decode(&enc).unwrap());
+
+        assert_eq!(read_metadata(&encode(&data).unwrap()).unwrap(), b"");
+        let enc = encode_with_metadata(&data, metadata).unwrap();
+        assert!(matches!(
+            read_metadata(&enc[..12]),
+            Err(Error::Truncated(_))
+        ));
+    }
+
+    #[test]
+    fn decode_with_model_returns_embedded_table() {
+        let data = b"model reuse for related messages ".repeat(40);
+        let enc = encode(&data).unwrap();
+        let mut pos = 0;
+        let embedded = read_header(&enc, &mut pos).unwrap();
+        let (out, model) = decode_with_model(&enc).unwrap();
+        assert_bytes_eq(&data, &out);
+        assert_eq!(model, embedded);
+        assert_eq!(model, build_frequencies(&data));
+
+        let opts = EncodeOptions::new()
+            .with_crc(true)
+            .format(FormatVersion::SparseVarint);
+        let (out, sparse_model) =
+            decode_with_model(&encode_with_options(&data, &opts).unwrap()).unwrap();
+        assert_bytes_eq(&data, &out);
+        assert_eq!(sparse_model, embedded);
+
+        // 稠密重映射格式的模型展开回完整字母表，只有出现的字节与 EOF 非零。
+        let opts = EncodeOptions::new().format(FormatVersion::DenseRemap);
+        let (out, dense_model) =
+            decode_with_model(&encode_with_options(&data, &opts).unwrap()).unwrap();
+        assert_bytes_eq(&data, &out);
+        assert_eq!(dense_model.len(), SYMBOL_LIMIT);
+        for (s, &f) in dense_model.iter().enumerate() {
+            assert_eq!(
+                f > 0,
+                s == EOF_SYMBOL || data.contains(&(s as u8)),
+                "symbol {s}"
+            );
+        }
+
+        let opts = EncodeOptions::new().with_stored_fallback(true);
+        let stored: Vec<u8> = (0..=255u8).collect();
+        let enc = encode_with_options(&stored, &opts).unwrap();
+        assert!(matches!(
+            decode_with_model(&enc),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn decay_model_adapts_faster_to_drift() {
+        // 前后两半使用互不相交的偏斜分布，中途切换。
+        let mut data = Vec::with_capacity(1 << 17);
+        let mut state = 1u32;
+        for half in 0..2u8 {
+            for _ in 0..1 << 16 {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                let r = (state >> 16) % 16;
+                let sym = match r {
+                    0..=9 => 0,
+                    10..=13 => 1,
+                    _ => 2 + (r & 1) as u8,
+                };
+                data.push(half * 100 + sym);
+            }
+        }
+
+        let plain = encode_adaptive(&data, AdaptiveModel::new()).unwrap();
+        let decay = encode_adaptive(&data, DecayModel::default()).unwrap();
+        assert_bytes_eq(
+            &data,
+            &decode_adaptive(&plain, AdaptiveModel::new()).unwrap(),
+        );
+        assert_eq!(
+            decode_adaptive(&decay, DecayModel::default()).unwrap(),
+            data
+        );
+
+        assert!(
+            decay.len() < plain.len(),
+            "{} >= {}",
+            decay.len(),
+            plain.len()
+        );
+
+        // 切换后前 4096 个符号的增量代价：旧统计衰减得越快，代价越低。
+        fn cost_after_switch<M: Model>(data: &[u8], model: impl Fn() -> M) -> usize {
+            let switch = data.len() / 2;
+            let prefix = |len: usize| encode_adaptive(&data[..len], model()).unwrap().len();
+            prefix(switch + 4096) - prefix(switch)
+        }
+        let plain_cost = cost_after_switch(&data, AdaptiveModel::new);
+        let decay_cost = cost_after_switch(&data, DecayModel::default);
+        assert!(decay_cost < plain_cost, "{decay_cost} >= {plain_cost}");
+
+        for len in [0usize, 1, 1000] {
+            let model = DecayModel::new(7, 3);
+            let enc = encode_adaptive(&data[..len], model.clone()).unwrap();
+            assert_eq!(decode_adaptive(&enc, model).unwrap(), &data[..len]);
+        }
+    }
+
+    #[test]
+    fn packed_bits_beat_byte_level_on_bitmap() {
+        // 256x256 的黑白位图：几个实心圆与矩形，每行 32 字节，高位为左侧像素。
+        let (width, height) = (256usize, 256usize);
+        let mut packed = vec![0u8; width * height / 8];
+        for y in 0..height {
+            for x in 0..width {
+                let in_circle = |cx: i64, cy: i64, r: i64| {
+                    let (dx, dy) = (x as i64 - cx, y as i64 - cy);
+                    dx * dx + dy * dy <= r * r
+                };
+                let black = in_circle(80, 80, 50)
+                    || in_circle(190, 150, 40)
+                    || (30..120).contains(&x) && (170..230).contains(&y);
+                if black {
+                    packed[(y * width + x) / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+
+        let bits = encode_bits_packed(&packed, width * height).unwrap();
+        let bytes = encode(&packed).unwrap();
+        assert!(
+            bits.len() < bytes.len(),
+            "{} >= {}",
+            bits.len(),
+            bytes.len()
+        );
+        assert_eq!(
+            decode_bits_packed(&bits).unwrap(),
+            (packed.clone(), width * height)
+        );
+
+        // 不足一个字节的尾部：多余的低位不参与编码，解码为 0。
+        let enc = encode_bits_packed(&[0xFF, 0xFF], 11).unwrap();
+        assert_eq!(decode_bits_packed(&enc).unwrap(), (vec![0xFF, 0xE0], 11));
+        let enc = encode_bits_packed(&[], 0).unwrap();
+        assert_eq!(decode_bits_packed(&enc).unwrap(), (Vec::new(), 0));
+        assert!(matches!(
+            encode_bits_packed(&[0xFF], 9),
+            Err(Error::InvalidInput(_))
+        ));
+
+        // 全零的 2^20 个比特压缩率接近模型上限，仍在数据体长度允许的范围内。
+        let zeros = encode_bits_packed(&[0; 1 << 17], 1 << 20).unwrap();
+        assert!((zeros.len() - 8) * MAX_BITS_PER_BODY_BYTE >= 1 << 20);
+        assert_eq!(decode_bits_packed(&zeros).unwrap().1, 1 << 20);
+
+        // 8 字节的头部声明 2^32 - 1 个比特：在分配与解码之前拒绝。
+        let mut bomb = BITS_MAGIC.to_vec();
+        bomb.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            decode_bits_packed(&bomb),
+            Err(Error::InvalidData(_))
+        ));
+        // 比特数在数据体允许的范围内，但数据体不足以解出这么多比特。
+        let mut short = bits[..12].to_vec();
+        short[4..8].copy_from_slice(&(1u32 << 17).to_le_bytes());
+        assert!(matches!(
+            decode_bits_packed(&short),
+            Err(Error::Truncated(_))
+        ));
+        assert!(matches!(
+            decode_bits_packed(&bits[..bits.len() - 1]),
+            Err(Error::Truncated(_))
+        ));
+        assert!(matches!(
+            decode_bits_packed_with_limits(&bits, &DecodeLimits::new(packed.len() as u64)),
+            Err(Error::LimitExceeded(_))
+        ));
+        assert!(decode_bits_packed_with_limits(&bits, &DecodeLimits::new(1 << 20)).is_ok());
+    }
+
+    #[test]
+    fn into_variants_reuse_buffers_without_stale_bytes() {
+        let mut encoded = Vec::new();
+        let mut decoded = Vec::new();
+        // 长短交替，较短的消息写入之前装过更长内容的缓冲区。
+        for len in [5000usize, 3, 0, 1, 2000, 17, 4096, 1] {
+            let msg: Vec<u8> = (0..len).map(|i| (i * 31 % 11) as u8).collect();
+            encode_into(&msg, &mut encoded).unwrap();
+            assert_eq!(encoded, encode(&msg).unwrap(), "len {len}");
+            decode_into(&encoded, &mut decoded).unwrap();
+            assert_bytes_eq(&msg, &decoded);
+        }
+        assert!(encoded.capacity() >= 4096);
+        assert!(matches!(
+            decode_into(b"XXXXabcd", &mut decoded),
+            Err(Error::BadMagic(_))
+        ));
+        assert!(decoded.is_empty());
+    }
+
+    #[test]
+    fn decode_reuse_matches_decode() {
+        let mut coder = RangeCoder::new();
+        let mut out = Vec::new();
+        let sparse = EncodeOptions::new().format(FormatVersion::SparseVarint);
+        let dense = EncodeOptions::new().format(FormatVersion::DenseRemap);
+        for i in 0..50usize {
+            let msg: Vec<u8> = (0..i * 7).map(|j| (j * i % 13) as u8 + b'a').collect();
+            for enc in [
+                encode(&msg).unwrap(),
+                encode_with_options(&msg, &sparse).unwrap(),
+                encode_with_options(&msg, &dense).unwrap(),
+            ] {
+                coder.decode_reuse(&enc, &mut out).unwrap();
+                assert_eq!(out, msg, "message {i}");
+            }
+        }
+        assert!(matches!(
+            coder.decode_reuse(b"XXXXabcd", &mut out),
+            Err(Error::BadMagic(_))
+        ));
+    }
+
+    #[test]
+    fn scaling_modes_distance_and_roundtrip() {
+        // 一个主导符号加大量小计数：截断会系统性地低估小概率符号。
+        let mut hist = vec![0u32; SYMBOL_LIMIT];
+        hist[0] = 40_000_000;
+        for (s, f) in hist.iter_mut().enumerate().skip(1) {
+            *f = 1 + (s as u32 * 7919) % 1000;
+        }
+        let mut distance = Vec::new();
+        for mode in [
+            ScalingMode::Truncate,
+            ScalingMode::RoundNearest,
+            ScalingMode::ErrorDiffuse,
+        ] {
+            let mut scaled = hist.clone();
+            normalize_with(&mut scaled, mode);
+            let total: u64 = scaled.iter().map(|&f| f as u64).sum();
+            assert!(total <= MAX_TOTAL as u64, "{mode:?}");
+            assert!(scaled.iter().all(|&f| f > 0), "{mode:?}");
+            if mode == ScalingMode::ErrorDiffuse {
+                assert_eq!(total, MAX_TOTAL as u64);
+            }
+            distance.push(diagnose(&hist, &scaled).kl_bits_per_symbol);
+        }
+        // 两种舍入方式都比截断更接近原分布。
+        assert!(distance[1] < distance[0], "{distance:?}");
+        assert!(distance[2] < distance[0], "{distance:?}");
+
+        // 超过 MAX_TOTAL 字节的输入才会触发缩放。
+        let mut data = vec![b'a'; MAX_TOTAL as usize + 4096];
+        for (i, b) in data.iter_mut().enumerate().step_by(997) {
+            *b = (i % 251) as u8;
+        }
+        for mode in [ScalingMode::RoundNearest, ScalingMode::ErrorDiffuse] {
+            let opts = EncodeOptions::new().scaling(mode);
+            let enc = encode_with_options(&data, &opts).unwrap();
+            assert_eq!(decode(&enc).unwrap(), data, "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn encode_iter_matches_collected() {
+        let collected: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let enc = encode_iter((0..1000).map(|i| i as u8)).unwrap();
+        assert_eq!(enc, encode(&collected).unwrap());
+        assert_eq!(decode(&enc).unwrap(), collected);
+
+        let enc = encode_iter_adaptive((0..1000).map(|i| i as u8)).unwrap();
+        assert_eq!(
+            enc,
+            encode_adaptive(&collected, AdaptiveModel::new()).unwrap()
+        );
+        assert_eq!(
+            decode_adaptive(&enc, AdaptiveModel::new()).unwrap(),
+            collected
+        );
+    }
+
+    #[test]
+    fn adler32_flag_honored_on_decode() {
+        let data = b"high throughput payload ".repeat(100);
+        let adler = EncodeOptions::new().with_checksum(ChecksumKind::Adler32);
+        let enc = encode_with_options(&data, &adler).unwrap();
+        assert_eq!(enc[4], FLAG_CRC | FLAG_ADLER);
+        assert_bytes_eq(&data, &decode(&enc).unwrap());
+        let info = inspect(&enc).unwrap();
+        assert_eq!(
+            (info.crc32, info.adler32),
+            (None, Some(checksum::adler32(&data)))
+        );
+
+        // 默认仍为 CRC32，两种容器只差标志位与校验和字段。
+        let crc = encode_with_options(&data, &EncodeOptions::new().with_crc(true)).unwrap();
+        assert_eq!(crc[4], FLAG_CRC);
+        assert_eq!(crc[9..], enc[9..]);
+
+        // 标志决定校验算法：把 Adler-32 容器的标志改成 CRC32 后校验失败。
+        let mut relabeled = enc.clone();
+        relabeled[4] = FLAG_CRC;
+        assert!(matches!(
+            decode(&relabeled),
+            Err(Error::ChecksumMismatch(_))
+        ));
+        let mut corrupted = enc.clone();
+        corrupted[5] ^= 1;
+        assert!(matches!(
+            decode(&corrupted),
+            Err(Error::ChecksumMismatch(_))
+        ));
+        // 没有校验和字段时 Adler 标志非法。
+        let mut orphan =
+            encode_with_options(&data, &EncodeOptions::new().with_length(true)).unwrap();
+        orphan[4] |= FLAG_ADLER;
+        assert!(matches!(decode(&orphan), Err(Error::BadMagic(_))));
+    }
+
+    #[test]
+    fn inspect_reports_length_and_crc() {
+        let data = b"archive member contents ".repeat(50);
+        let opts = EncodeOptions::new().with_crc(true).with_length(true);
+        let enc = encode_with_options(&data, &opts).unwrap();
+        let info = inspect(&enc).unwrap();
+        assert_eq!(info.original_len, Some(data.len() as u64));
+        assert_eq!(info.crc32, Some(checksum::crc32(&data)));
+        assert_eq!(info.format, Some(FormatVersion::Classic));
+        assert_eq!(info.compressed_len, enc.len());
+        assert_bytes_eq(&data, &decode(&enc).unwrap());
+
+        // 原样存储与带元数据的容器同样可以列出。
+        let noise: Vec<u8> = (0..=255u8).collect();
+        let opts = opts.with_stored_fallback(true).with_metadata(b"name=noise");
+        let info = inspect(&encode_with_options(&noise, &opts).unwrap()).unwrap();
+        assert_eq!((info.format, info.original_len), (None, Some(256)));
+
+        let info = inspect(&encode(&data).unwrap()).unwrap();
+        assert_eq!((info.original_len, info.crc32), (None, None));
+
+        // 长度字段与数据体不符时解码报错，超过输出上限时在解码前拒绝。
+        let opts = EncodeOptions::new().with_length(true);
+        let mut enc = encode_with_options(&data, &opts).unwrap();
+        assert!(matches!(
+            decode_with_options(&enc, &DecodeOptions::new().with_max_output(10)),
+            Err(Error::LimitExceeded(_))
+        ));
+        enc[5] ^= 1;
+        assert!(matches!(decode(&enc), Err(Error::InvalidData(_))));
+    }
+
+    #[test]
+    fn delta_filter_improves_ramps() {
+        use encoding_common::delta::{delta_decode_stride, delta_encode_stride};
+        // 缓慢上升并带少量噪声的读数，以及两路交错的通道。
+        let noise = encoding_common::selftest::pseudo_random(60_000, 0x4A3D);
+        let ramp: Vec<u8> = (0..60_000)
+            .map(|i| (i / 7) as u8 ^ (noise[i] & 1))
+            .collect();
+        let interleaved: Vec<u8> = (0..60_000)
+            .map(|i| match i % 2 {
+                0 => (i / 11) as u8,
+                _ => 200u8.wrapping_sub((i / 5) as u8),
+            })
+            .collect();
+        for (data, stride) in [(&ramp, 1), (&interleaved, 2)] {
+            let filtered = delta_encode_stride(data, stride);
+            let plain = encode(data).unwrap().len();
+            let delta = encode(&filtered).unwrap().len();
+            assert!(delta * 2 < plain, "delta {delta} vs plain {plain}");
+            let decoded = decode(&encode(&filtered).unwrap()).unwrap();
+            assert_bytes_eq(data, &delta_decode_stride(&decoded, stride));
+        }
+    }
+
+    #[test]
+    fn delta_against_reference_is_small() {
+        use rand::RngCore;
+        use rand::SeedableRng;
+        let mut old = vec![0u8; 64 * 1024];
+        rand::rngs::StdRng::seed_from_u64(11).fill_bytes(&mut old);
+        // 新版本：改动两处、末尾追加一段。
+        let mut new = old.clone();
+        new[1000..1016].copy_from_slice(b"patched region!!");
+        new[40_000] ^= 0x5A;
+        new.extend_from_slice(b"appended trailer");
+
+        let delta = encode_against(&old, &new).unwrap();
+        let alone = encode(&new).unwrap();
+        assert!(
+            delta.len() * 50 < alone.len(),
+            "{} vs {}",
+            delta.len(),
+            alone.len()
+        );
+        assert_eq!(decode_against(&old, &delta).unwrap(), new);
+
+        // 新版本比参考短时同样可以还原；参考不符时报错。
+        let shorter = &new[..500];
+        let enc = encode_against(&old, shorter).unwrap();
+        assert_eq!(decode_against(&old, &enc).unwrap(), shorter);
+        assert!(matches!(
+            decode_against(&new, &delta),
+            Err(Error::InvalidInput(_))
+        ));
+        for cut in [0, 4, 7] {
+            assert!(matches!(
+                decode_against(&old, &delta[..cut]),
+                Err(Error::Truncated(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn decode_limits_enforced() {
+        let data = vec![b'z'; 10_000];
+        let enc = encode(&data).unwrap();
+        let exact = DecodeLimits::new(MODEL_TABLE_BYTES + data.len() as u64);
+        assert_bytes_eq(&data, &decode_with_limits(&enc, &exact).unwrap());
+        let err = decode_with_limits(&enc, &DecodeLimits::new(MODEL_TABLE_BYTES + 9_999));
+        assert!(matches!(err, Err(Error::LimitExceeded(_))));
+        let err = decode_with_limits(&enc, &DecodeLimits::new(100));
+        assert!(matches!(err, Err(Error::LimitExceeded(_))));
+    }
+
+    #[test]
+    fn model_variants_enforce_limits() {
+        let data: Vec<u8> = b"the quick brown fox jumps over the lazy dog "
+            .iter()
+            .copied()
+            .cycle()
+            .take(5_000)
+            .collect();
+        let exact = DecodeLimits::new(MODEL_TABLE_BYTES + data.len() as u64);
+        let short = DecodeLimits::new(MODEL_TABLE_BYTES + data.len() as u64 - 1);
+        let tiny = DecodeLimits::new(100);
+
+        let enc = encode_adaptive(&data, AdaptiveModel::new()).unwrap();
+        assert_bytes_eq(
+            &data,
+            &decode_adaptive_with_limits(&enc, AdaptiveModel::new(), &exact).unwrap(),
+        );
+        for limits in [&short, &tiny] {
+            let err = decode_adaptive_with_limits(&enc, AdaptiveModel::new(), limits);
+            assert!(matches!(err, Err(Error::LimitExceeded(_))));
+        }
+
+        let prefix = b"the quick brown fox";
+        let enc = encode_with_prefix(prefix, &data);
+        assert_bytes_eq(
+            &data,
+            &decode_with_prefix_with_limits(prefix, &enc, &exact).unwrap(),
+        );
+        for limits in [&short, &tiny] {
+            let err = decode_with_prefix_with_limits(prefix, &enc, limits);
+            assert!(matches!(err, Err(Error::LimitExceeded(_))));
+        }
+
+        let dict = train_dictionary(&[&data[..1_000]]);
+        let enc = encode_with_dictionary(&data, &dict).unwrap();
+        assert_bytes_eq(
+            &data,
+            &decode_with_dictionary_with_limits(&enc, &dict, &exact).unwrap(),
+        );
+        for limits in [&short, &tiny] {
+            let err = decode_with_dictionary_with_limits(&enc, &dict, limits);
+            assert!(matches!(err, Err(Error::LimitExceeded(_))));
+        }
+
+        let mut reference = data.clone();
+        reference[100] ^= 0x55;
+        let enc = encode_against(&reference, &data).unwrap();
+        assert_bytes_eq(
+            &data,
+            &decode_against_with_limits(&reference, &enc, &exact).unwrap(),
+        );
+        for limits in [&short, &tiny] {
+            let err = decode_against_with_limits(&reference, &enc, limits);
+            assert!(matches!(err, Err(Error::LimitExceeded(_))));
+        }
+    }
+
+    #[test]
+    fn constant_input_uses_single_byte_format() {
+        let data = vec![0x42u8; 1 << 20];
+        let enc = encode(&data).unwrap();
+        assert_eq!(enc.len(), 13);
+        assert_bytes_eq(&data, &decode(&enc).unwrap());
+        assert_eq!(
+            inspect(&enc).unwrap().format,
+            Some(FormatVersion::SingleByte)
+        );
+        let (_, model) = decode_with_model(&enc).unwrap();
+        assert_eq!(model, build_frequencies(&data));
+
+        // 所有格式都会自动选用；显式指定时输入必须是单一字节。
+        let opts = EncodeOptions::new()
+            .format(FormatVersion::DenseRemap)
+            .with_crc(true);
+        assert_eq!(
+            decode(&encode_with_options(&data, &opts).unwrap()).unwrap(),
+            data
+        );
+        let single = EncodeOptions::new().format(FormatVersion::SingleByte);
+        assert!(matches!(
+            encode_with_options(b"ab", &single),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            decode_with_options(&enc, &DecodeOptions::new().with_max_output(1000)),
+            Err(Error::LimitExceeded(_))
+        ));
+        assert!(matches!(decode(&enc[..12]), Err(Error::Truncated(_))));
+        assert_eq!(decode(&encode(b"").unwrap()).unwrap(), b"");
+
+        // 13 字节的头部声明 2^62 字节：超出上限时不分配，不设上限时分配失败也只返回错误。
+        let bomb = encode_single(0x42, 1 << 62);
+        assert!(matches!(decode(&bomb), Err(Error::LimitExceeded(_))));
+        assert!(matches!(
+            decode_with_limit(&bomb, 1 << 30),
+            Err(Error::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn fixed16_conformance_vector() {
+        let opts = EncodeOptions::new().format(FormatVersion::Fixed16);
+        let enc = encode_with_options(b"abracadabra", &opts).unwrap();
+        // 计数 a:5 b:2 c:1 d:1 r:2 EOF:1（共 12）按误差扩散换算到 2^16。
+        let prob = |sym: usize| u16::from_le_bytes([enc[4 + 2 * sym], enc[5 + 2 * sym]]);
+        let expected = [
+            (b'a' as usize, 27307),
+            (b'b' as usize, 10922),
+            (b'c' as usize, 5462),
+            (b'd' as usize, 5461),
+            (b'r' as usize, 10923),
+            (EOF_SYMBOL, 5461),
+        ];
+        for (sym, p) in expected {
+            assert_eq!(prob(sym), p, "symbol {sym}");
+        }
+        assert_eq!(
+            (0..SYMBOL_LIMIT).map(|s| prob(s) as u32).sum::<u32>(),
+            1 << 16
+        );
+        assert_eq!(&enc[..4], b"RCNQ");
+        assert_eq!(
+            enc[4 + 2 * SYMBOL_LIMIT..],
+            [0x3A, 0x87, 0x1B, 0xE8, 0x08, 0x68]
+        );
+        assert_eq!(decode(&enc).unwrap(), b"abracadabra");
+        assert_eq!(format_of(&enc).unwrap(), FormatVersion::Fixed16);
+
+        // 空输入只有 EOF，让出一个单位后每项仍能用 u16 表示。
+        let empty = encode_with_options(b"", &opts).unwrap();
+        assert_eq!(decode(&empty).unwrap(), b"");
+        let mut bad = enc.clone();
+        bad[4 + 2 * b'a' as usize] ^= 1;
+        assert!(matches!(decode(&bad), Err(Error::InvalidModel(_))));
+        assert!(matches!(decode(&enc[..100]), Err(Error::Truncated(_))));
+    }
+
+    #[test]
+    fn lookup_table_decode_matches_binary_search() {
+        let random = encoding_common::selftest::pseudo_random(40_000, 5);
+        let skewed: Vec<u8> = random
+            .iter()
+            .map(|&r| ((r as u32).pow(3) >> 16) as u8)
+            .collect();
+        let sparse: Vec<u8> = random.iter().map(|&r| b'a' + r % 5).collect();
+        let table = DecodeOptions::new().with_lookup_table(true);
+        for data in [&random, &skewed, &sparse] {
+            for format in [
+                FormatVersion::Classic,
+                FormatVersion::DenseRemap,
+                FormatVersion::Fixed16,
+            ] {
+                let enc = encode_with_options(data, &EncodeOptions::new().format(format)).unwrap();
+                assert_bytes_eq(data, &decode_with_options(&enc, &table).unwrap());
+            }
+        }
+        // 每个桶的起点符号满足 cumulative[s] <= 起点值 < cumulative[s + 1]，零频符号被跳过。
+        let cumulative = build_cumulative(&[0, 3, 0, 0, 1, 4092]);
+        let symbols = SymbolTable::new(&cumulative);
+        for value in 0..4096 {
+            let s = symbols.find(value, &cumulative) as usize;
+            assert!(cumulative[s] as u64 <= value && value < cumulative[s + 1] as u64);
+        }
+    }
+
+    #[test]
+    fn output_independent_of_buffer_capacity() {
+        let data = encoding_common::selftest::pseudo_random(50_000, 11);
+        let expected = encode(&data).unwrap();
+        let mut roomy = Vec::with_capacity(data.len() * 3);
+        roomy.extend_from_slice(&data);
+        let mut exact = Vec::with_capacity(data.len());
+        exact.extend_from_slice(&data);
+        // 同样的字节位于一个更大缓冲区中的非对齐位置。
+        let mut padded = vec![0xEEu8; data.len() + 7];
+        padded[3..3 + data.len()].copy_from_slice(&data);
+        let opts = EncodeOptions::new().with_crc(true).with_length(true);
+        for input in [&roomy[..], &exact[..], &padded[3..3 + data.len()]] {
+            assert_eq!(encode(input).unwrap(), expected);
+            assert_eq!(
+                encode_with_options(input, &opts).unwrap(),
+                encode_with_options(&data, &opts).unwrap()
+            );
+            assert_eq!(
+                encode_parallel_with_threads(input, 8192, 3).unwrap(),
+                encode_parallel_with_threads(&data, 8192, 1).unwrap()
+            );
+        }
+
+        let mut enc_roomy = Vec::with_capacity(expected.len() * 4);
+        enc_roomy.extend_from_slice(&expected);
+        let mut enc_padded = vec![0u8; expected.len() + 5];
+        enc_padded[1..1 + expected.len()].copy_from_slice(&expected);
+        for encoded in [&enc_roomy[..], &enc_padded[1..1 + expected.len()]] {
+            assert_bytes_eq(&data, &decode(encoded).unwrap());
+        }
+    }
+
+    #[test]
+    fn parallel_output_independent_of_thread_count() {
+        use rand::RngCore;
+        use rand::SeedableRng;
+        let mut data = vec![0u8; 200_000];
+        rand::rngs::StdRng::seed_from_u64(5).fill_bytes(&mut data[..100_000]);
+        for (i, b) in data[100_000..].iter_mut().enumerate() {
+            *b = b"deterministic"[i % 13];
+        }
+
+        let single = encode_parallel_with_threads(&data, 16 * 1024, 1).unwrap();
+        for threads in [2, 8] {
+            assert_eq!(
+                encode_parallel_with_threads(&data, 16 * 1024, threads).unwrap(),
+                single
+            );
+        }
+        assert_eq!(encode_parallel(&data, 16 * 1024).unwrap(), single);
+        assert_eq!(&single[..4], PARALLEL_MAGIC);
+        assert_bytes_eq(&data, &decode_parallel(&single).unwrap());
+        for threads in [1, 3, 64] {
+            assert_bytes_eq(
+                &data,
+                &decode_parallel_with_threads(&single, threads).unwrap(),
+            );
+        }
+        // 某一块损坏时整体返回错误，而不是拼出错位的输出。
+        let mut corrupt = single.clone();
+        let first_len = u32::from_le_bytes(corrupt[16..20].try_into().unwrap()) as usize;
+        corrupt[12 + 13 * 8 + first_len + 4] ^= 0xFF;
+        assert!(decode_parallel_with_threads(&corrupt, 4).is_err());
+
+        // 不超过一块的输入与 encode 相同。
+        let small = &data[..1000];
+        assert_eq!(
+            encode_parallel_with_threads(small, 4096, 8).unwrap(),
+            encode(small).unwrap()
+        );
+        assert_eq!(decode_parallel(&encode(small).unwrap()).unwrap(), small);
+
+        let limits = DecodeLimits::new(MODEL_TABLE_BYTES + data.len() as u64);
+        assert_bytes_eq(
+            &data,
+            &decode_parallel_with_limits(&single, &limits).unwrap(),
+        );
+        for max_output in [data.len() - 1, 100_000, 1000] {
+            let limits = DecodeLimits::new(MODEL_TABLE_BYTES + max_output as u64);
+            assert!(matches!(
+                decode_parallel_with_limits(&single, &limits),
+                Err(Error::LimitExceeded(_))
+            ));
+        }
+        assert!(matches!(
+            encode_parallel(small, 0),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn crc_detects_corruption() {
+        let data = b"checksummed payload ".repeat(50);
+        let mut enc = encode_with_options(&data, &EncodeOptions::new().with_crc(true)).unwrap();
+        let last = enc.len() - 6;
+        enc[last] ^= 0x01;
+        let opts = DecodeOptions::new().with_max_output(data.len() * 2);
+        let err = decode_with_options(&enc, &opts).unwrap_err();
+        assert!(
+            matches!(err, Error::ChecksumMismatch(_) | Error::LimitExceeded(_)),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn decode_options_limit_output() {
+        let data = vec![9u8; 1000];
+        let enc = encode(&data).unwrap();
+        let opts = DecodeOptions::new().with_max_output(999);
+        let err = decode_with_options(&enc, &opts).unwrap_err();
+        assert!(matches!(err, Error::LimitExceeded(_)), "{err}");
+        assert_eq!(err.to_string(), "range: output limit exceeded");
+        let opts = DecodeOptions::new().with_max_output(1000);
+        assert_bytes_eq(&data, &decode_with_options(&enc, &opts).unwrap());
+    }
+
+    #[test]
+    fn dictionary_beats_plain_encode_on_small_messages() {
+        let samples: Vec<Vec<u8>> = (0..50)
+            .map(|i| {
+                format!(
+                    r#"{{"id":{i},"status":"ok","items":[{{"name":"item{i}","price":{}}}]}}"#,
+                    i * 3
+                )
+                .into_bytes()
+            })
+            .collect();
+        let sample_refs: Vec<&[u8]> = samples.iter().map(|s| s.as_slice()).collect();
+        let dict = train_dictionary(&sample_refs);
+
+        let message = br#"{"id":777,"status":"ok","items":[{"name":"item777","price":12}]}"#;
+        let with_dict = encode_with_dictionary(message, &dict).unwrap();
+        let plain = encode(message).unwrap();
+        assert!(with_dict.len() < message.len());
+        assert!(with_dict.len() < plain.len());
+        assert_eq!(decode_with_dictionary(&with_dict, &dict).unwrap(), message);
+
+        // 字典中未出现过的字节仍可编码。
+        let odd = [0u8, 255, 128, b'{'];
+        let enc = encode_with_dictionary(&odd, &dict).unwrap();
+        assert_eq!(decode_with_dictionary(&enc, &dict).unwrap(), odd);
+
+        let restored = Dictionary::from_bytes(&dict.to_bytes()).unwrap();
+        assert_eq!(restored, dict);
+        let other = train_dictionary(&[b"completely different".as_slice()]);
+        let err = decode_with_dictionary(&with_dict, &other).unwrap_err();
+        assert!(
+            matches!(err, Error::InvalidModel("range: dictionary mismatch")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn shared_frequency_model_roundtrips_without_header() {
+        // 调用方自己由语料统计直方图，构造一次字典后用于所有消息。
+        let corpus = br#"{"user":"alice","action":"login","ok":true}"#.repeat(100);
+        let mut hist = vec![0u32; SYMBOL_LIMIT];
+        for &b in &corpus {
+            hist[b as usize] += 1;
+        }
+        let dict = Dictionary::from_frequencies(&hist).unwrap();
+        assert!(dict.freq().iter().all(|&f| f > 0));
+
+        for message in [
+            &br#"{"user":"bob","action":"logout","ok":false}"#[..],
+            b"",
+            &[0u8, 255, 7],
+        ] {
+            let raw = encode_with_dictionary_raw(message, &dict).unwrap();
+            let framed = encode_with_dictionary(message, &dict).unwrap();
+            assert_eq!(raw.len() + 8, framed.len());
+            assert_eq!(decode_with_dictionary_raw(&raw, &dict).unwrap(), message);
+        }
+        assert!(matches!(
+            Dictionary::from_frequencies(&hist[..256]),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn diagnostics_flag_skewed_large_histogram() {
+        // 一个符号占绝对多数，其余符号各出现 1 次：缩放后全部被提升为 1。
+        let mut hist = vec![1u32; SYMBOL_LIMIT];
+        hist[0] = 4_000_000_000;
+        let diag = model_diagnostics(&hist);
+        assert_eq!(diag.symbols, SYMBOL_LIMIT);
+        assert_eq!(diag.floored, SYMBOL_LIMIT - 1);
+        assert!(diag.total_ratio > 200.0);
+        assert!(diag.is_significant());
+    }
+
+    #[test]
+    fn diagnostics_clean_for_uniform_small_input() {
+        let data: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+        let (enc, stats) = encode_with_stats(&data).unwrap();
+        assert_eq!(enc, encode(&data).unwrap());
+        assert_eq!(stats.output_len, enc.len());
+        assert_eq!(stats.header_len, 8 + SYMBOL_LIMIT * 4);
+        assert_eq!(stats.diagnostics.floored, 0);
+        assert_eq!(stats.diagnostics.total_ratio, 1.0);
+        assert_eq!(stats.diagnostics.kl_bits_per_symbol, 0.0);
+        assert!(!stats.diagnostics.is_significant());
+    }
+
+    #[test]
+    fn decode_rejects_all_zero_frequency_table() {
+        let mut crafted = Vec::new();
+        write_header(&mut crafted, &[0u32; SYMBOL_LIMIT]);
+        crafted.extend_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(
+            decode(&crafted).unwrap_err().to_string(),
+            "range: empty frequency table"
+        );
+        assert_eq!(decode_lossy(&crafted), (Vec::new(), false));
+
+        let mut sparse = SPARSE_MAGIC.to_vec();
+        write_varint(&mut sparse, 0);
+        sparse.extend_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(
+            decode(&sparse).unwrap_err().to_string(),
+            "range: empty frequency table"
+        );
+    }
+
+    #[test]
+    fn error_variants_for_malformed_input() {
+        use std::error::Error as _;
+        let enc = encode(b"variants").unwrap();
+        assert!(matches!(decode(b"RC"), Err(Error::Truncated(_))));
+        assert!(matches!(decode(b"XXXX\x01\0\0\0"), Err(Error::BadMagic(_))));
+        assert!(matches!(decode(&enc[..20]), Err(Error::Truncated(_))));
+        let mut bad_count = enc.clone();
+        bad_count[4..8].copy_from_slice(&5000u32.to_le_bytes());
+        assert!(matches!(decode(&bad_count), Err(Error::InvalidModel(_))));
+        let mut frame =
+            encode_with_options(b"variants", &EncodeOptions::new().with_crc(true)).unwrap();
+        frame[4] = 0x80;
+        assert!(matches!(decode(&frame), Err(Error::BadMagic(_))));
+        assert!(matches!(
+            decode_with_limit(&enc, 3),
+            Err(Error::LimitExceeded(_))
+        ));
+        let mut checked =
+            encode_with_options(b"variants", &EncodeOptions::new().with_crc(true)).unwrap();
+        // 魔数与标志之后的 4 字节为原始数据的 CRC32。
+        checked[5] ^= 0x01;
+        assert!(matches!(decode(&checked), Err(Error::ChecksumMismatch(_))));
+        // RangeError 是 Error 的旧名称，可直接按变体匹配。
+        let old_name: RangeError = decode(b"XXXX\x01\0\0\0").unwrap_err();
+        assert_eq!(old_name.to_string(), "range: bad magic");
+
+        let missing = temp_path("missing.in");
+        let err = encode_file(
+            missing.to_str().unwrap(),
+            temp_path("missing.out").to_str().unwrap(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::Io(_)), "{err}");
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn decode_rejects_model_without_eof() {
+        let mut freq = vec![1u32; SYMBOL_LIMIT];
+        freq[EOF_SYMBOL] = 0;
+        let mut crafted = Vec::new();
+        write_header(&mut crafted, &freq);
+        crafted.extend_from_slice(&[0; 4]);
+        assert_eq!(
+            decode(&crafted).unwrap_err().to_string(),
+            "range: missing EOF symbol"
+        );
+    }
+
+    #[test]
+    fn empty_input_uses_internal_fallback_only() {
+        // 空输入的模型只有 EOF 一个非零频率，解码得到空输出。
+        assert_eq!(build_frequencies(&[])[EOF_SYMBOL], 1);
+        let enc = encode(&[]).unwrap();
+        assert_eq!(decode(&enc).unwrap(), Vec::<u8>::new());
+        // 编码端内部的均匀回退仍保留给全零表使用。
+        assert_eq!(build_cumulative(&[0, 0, 0]), vec![0, 1, 2, 3]);
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("rangecoder_test_{}_{name}", std::process::id()))
+    }
+
+    #[test]
+    fn file_roundtrip() {
+        let input = temp_path("file.in");
+        let encoded = temp_path("file.rc");
+        let decoded = temp_path("file.out");
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        fs::write(&input, &data).unwrap();
+        encode_file(input.to_str().unwrap(), encoded.to_str().unwrap()).unwrap();
+        assert_eq!(fs::read(&encoded).unwrap(), encode(&data).unwrap());
+        decode_file(encoded.to_str().unwrap(), decoded.to_str().unwrap()).unwrap();
+        assert_bytes_eq(&data, &fs::read(&decoded).unwrap());
+        for p in [input, encoded, decoded] {
+            let _ = fs::remove_file(p);
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap_encode_matches_buffered() {
+        let input = temp_path("mmap.in");
+        let buffered = temp_path("mmap.buffered");
+        let mapped = temp_path("mmap.mapped");
+        for data in [Vec::new(), b"mmap input ".repeat(1000)] {
+            fs::write(&input, &data).unwrap();
+            encode_file(input.to_str().unwrap(), buffered.to_str().unwrap()).unwrap();
+            encode_file_mmap(input.to_str().unwrap(), mapped.to_str().unwrap()).unwrap();
+            assert_eq!(fs::read(&mapped).unwrap(), fs::read(&buffered).unwrap());
+        }
+        for p in [input, buffered, mapped] {
+            let _ = fs::remove_file(p);
+        }
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn bytes_api_matches_vec_api() {
+        for data in [Vec::new(), b"bytes payload ".repeat(500)] {
+            let encoded = encode_bytes(&data).unwrap();
+            assert_bytes_eq(&encode(&data).unwrap(), &encoded);
+            assert_bytes_eq(&data, &decode_bytes(encoded).unwrap());
+        }
+        assert!(decode_bytes(bytes::Bytes::from_static(b"XXXX")).is_err());
+    }
+
+    #[test]
+    fn error_on_expansion_reports_incompressible_input() {
+        let opts = EncodeOptions::new().with_error_on_expansion(true);
+        let random = encoding_common::selftest::pseudo_random(4096, 0xDEAD_BEEF);
+        assert!(matches!(
+            encode_with_options(&random, &opts),
+            Err(Error::LimitExceeded(_))
+        ));
+        // 可压缩数据不受影响；与存储回退同时启用时以回退为准。
+        let text = b"compressible text ".repeat(500);
+        assert_bytes_eq(
+            &encode(&text).unwrap(),
+            &encode_with_options(&text, &opts).unwrap(),
+        );
+        let stored = encode_with_options(&random, &opts.with_stored_fallback(true)).unwrap();
+        assert_bytes_eq(&random, &decode(&stored).unwrap());
+    }
+
+    #[test]
+    fn budgeted_decode_matches_decode() {
+        let data: Vec<u8> = (0..200_000u64).map(|i| (i * i % 251) as u8).collect();
+        let single = vec![9u8; 1234];
+        let encodings = [
+            encode(&data).unwrap(),
+            encode_with_options(
+                &data,
+                &EncodeOptions::new().format(FormatVersion::SparseVarint),
+            )
+            .unwrap(),
+            encode_with_options(&data, &EncodeOptions::new().format(FormatVersion::Fixed16))
+                .unwrap(),
+            encode(&single).unwrap(),
+            encode(b"").unwrap(),
+        ];
+        for encoded in &encodings {
+            let mut state = DecodeState::new(encoded).unwrap();
+            let mut out = Vec::new();
+            let mut calls = 0;
+            while decode_budgeted(&mut state, 100, &mut out) == DecodeProgress::Pending {
+                calls += 1;
+                assert!(out.len() <= calls * 100);
+            }
+            assert_bytes_eq(&decode(encoded).unwrap(), &out);
+            assert_eq!(
+                decode_budgeted(&mut state, 100, &mut out),
+                DecodeProgress::Done
+            );
+        }
+
+        let dense = encode_with_options(
+            &data,
+            &EncodeOptions::new().format(FormatVersion::DenseRemap),
+        )
+        .unwrap();
+        assert!(matches!(
+            DecodeState::new(&dense),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn decode_iter_matches_decode() {
+        let data: Vec<u8> = (0..50_000u64).map(|i| (i * i % 251) as u8).collect();
+        for input in [&data[..], &[9u8; 300][..], b"x", b""] {
+            let encoded = encode(input).unwrap();
+            let decoded: Vec<u8> = DecodeIter::new(&encoded).unwrap().collect();
+            assert_bytes_eq(&decode(&encoded).unwrap(), &decoded);
+        }
+
+        // 解到 EOF 后保持结束。
+        let encoded = encode(b"fused").unwrap();
+        let mut iter = DecodeIter::new(&encoded).unwrap();
+        assert_eq!(iter.by_ref().take(2).collect::<Vec<u8>>(), b"fu");
+        assert_eq!(iter.by_ref().collect::<Vec<u8>>(), b"sed");
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+
+        // 只有头部、没有数据体的流是空迭代器。
+        let header_only = &encode(&data).unwrap()[..8 + 4 * SYMBOL_LIMIT];
+        assert_eq!(DecodeIter::new(header_only).unwrap().count(), 0);
+        assert!(matches!(DecodeIter::new(b"RCN"), Err(Error::Truncated(_))));
+    }
+
+    #[test]
+    fn verify_roundtrips_in_place() {
+        let data: Vec<u8> = (0..50_000u64).map(|i| (i * i % 251) as u8).collect();
+        for input in [&data[..], &[9u8; 300][..], b"x", b""] {
+            assert!(verify(input).unwrap());
+        }
+        // verify 的比较方式：与不同的数据比较时在第一个差异处得到 false。
+        let encoded = encode(b"verify me").unwrap();
+        assert!(!DecodeIter::new(&encoded)
+            .unwrap()
+            .eq(b"verify mE".iter().copied()));
+    }
+
+    #[test]
+    fn prefix_dictionary_shrinks_small_messages() {
+        let dict = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nServer: encoding\r\n\r\n"
+            .repeat(8);
+        let message = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\n";
+        assert_eq!(message.len(), 50);
+
+        let primed = encode_with_prefix(&dict, message);
+        assert_eq!(&primed[..4], b"RCNH");
+        assert_eq!(decode_with_prefix(&dict, &primed).unwrap(), message);
+        let cold = encode_with_prefix(b"", message);
+        assert_eq!(decode_with_prefix(b"", &cold).unwrap(), message);
+        assert!(
+            primed.len() + 10 < cold.len(),
+            "{} vs {} bytes",
+            primed.len(),
+            cold.len()
+        );
+        assert!(primed.len() < message.len(), "{} bytes", primed.len());
+
+        for input in [
+            &b""[..],
+            &encoding_common::selftest::pseudo_random(5000, 0x9F1E)[..],
+        ] {
+            let encoded = encode_with_prefix(&dict, input);
+            assert_eq!(decode_with_prefix(&dict, &encoded).unwrap(), input);
+        }
+        assert!(matches!(
+            decode_with_prefix(&dict[1..], &primed),
+            Err(Error::InvalidModel(_))
+        ));
+        assert!(matches!(
+            decode_with_prefix(
+                &dict,
+                &encode_adaptive(message, AdaptiveModel::new()).unwrap()
+            ),
+            Err(Error::BadMagic(_))
+        ));
+        for cut in [0, 4, 7, primed.len() - 1] {
+            assert!(matches!(
+                decode_with_prefix(&dict, &primed[..cut]),
+                Err(Error::Truncated(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn encode_cancelled_after_first_check() {
+        use std::cell::Cell;
+        // 常量输入会走单字节格式而不经过取消检查，这里用非常量数据。
+        let data: Vec<u8> = (0..CANCEL_CHECK_INTERVAL * 4)
+            .map(|i| (i % 7) as u8)
+            .collect();
+        let checks = Cell::new(0);
+        let err = encode_cancellable(&data, || {
+            checks.set(checks.get() + 1);
+            checks.get() > 2
+        })
+        .unwrap_err();
+        assert!(matches!(err, Error::Cancelled(_)), "{err}");
+        assert_eq!(checks.get(), 3);
+    }
+
+    #[test]
+    fn encode_cancellable_never_cancelled_matches_encode() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i * 31 + 7) as u8).collect();
+        let enc = encode_cancellable(&data, || false).unwrap();
+        assert_eq!(enc, encode(&data).unwrap());
+        assert_bytes_eq(&data, &decode(&enc).unwrap());
+    }
+
+    // 每次 read 只返回 1 个字节，覆盖流式接口对部分读取的处理。
+    struct OneByteReader<R>(R);
+
+    impl<R: Read> Read for OneByteReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(1);
+            self.0.read(&mut buf[..n])
+        }
+    }
+
+    impl<R: Seek> Seek for OneByteReader<R> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.0.seek(pos)
+        }
+    }
+
+    #[test]
+    fn streaming_api_matches_slice_api() {
+        let random = encoding_common::selftest::pseudo_random(3 * STREAM_CHUNK + 17, 0x5EED);
+        let skewed: Vec<u8> = random.iter().map(|&r| r.leading_zeros() as u8).collect();
+        let cases = [
+            Vec::new(),
+            b"z".to_vec(),
+            vec![9u8; STREAM_CHUNK + 1],
+            b"streaming range coder ".repeat(300),
+            skewed,
+            random,
+        ];
+        for data in &cases {
+            // 输入从非零偏移开始：编码只读取当前位置之后的部分。
+            let mut padded = b"prefix".to_vec();
+            padded.extend_from_slice(data);
+            let mut reader = OneByteReader(io::Cursor::new(padded));
+            reader.seek(SeekFrom::Start(6)).unwrap();
+            let mut encoded = Vec::new();
+            encode_to_writer(&mut reader, &mut encoded).unwrap();
+            assert_bytes_eq(&encode(data).unwrap(), &encoded);
+
+            let mut decoded = Vec::new();
+            decode_from_reader(&mut OneByteReader(&encoded[..]), &mut decoded).unwrap();
+            assert_bytes_eq(data, &decoded);
+        }
+
+        let encoded = encode(&cases[4]).unwrap();
+        let mut sink = Vec::new();
+        let truncated = &encoded[..encoded.len() - 100];
+        assert!(matches!(
+            decode_from_reader(&mut &truncated[..], &mut sink),
+            Err(Error::Truncated(_))
+        ));
+        let sparse = encode_with_options(
+            &cases[3],
+            &EncodeOptions::new().format(FormatVersion::SparseVarint),
+        )
+        .unwrap();
+        assert!(matches!(
+            decode_from_reader(&mut &sparse[..], &mut sink),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn decode_with_limit_stops_stream_without_eof() {
+        // 符号 0 占据几乎整个区间，全零数据体（及其后的补零）永远解不出 EOF。
+        let mut freq = vec![0u32; SYMBOL_LIMIT];
+        freq[0] = 1 << 20;
+        freq[EOF_SYMBOL] = 1;
+        let mut bomb = Vec::new();
+        write_header(&mut bomb, &freq);
+        bomb.extend_from_slice(&[0u8; 8]);
+        let err = decode_with_limit(&bomb, 1 << 16).unwrap_err();
+        assert_eq!(err.to_string(), "range: output limit exceeded");
+
+        let data = b"within the limit".repeat(10);
+        let encoded = encode(&data).unwrap();
+        assert_bytes_eq(&data, &decode_with_limit(&encoded, data.len()).unwrap());
+        assert!(matches!(
+            decode_with_limit(&encoded, data.len() - 1),
+            Err(Error::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn adaptive_random_roundtrip() {
+        // 随机长度、字母表大小与偏斜程度的输入，长输入会多次触发计数减半。
+        let mut seed = 0x2468_ACE0_1357_9BDFu64;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        for case in 0..120 {
+            let len = match case % 4 {
+                0 => (next() % 16) as usize,
+                1 => (next() % 1000) as usize,
+                _ => (next() % 20_000) as usize,
+            };
+            let alphabet = 1 + (next() % 256) as u32;
+            let skew = (next() % 4) as u32;
+            let data: Vec<u8> = (0..len)
+                .map(|_| {
+                    let r = (next() >> 40) as u32 % alphabet;
+                    (r >> skew.min(7)) as u8
+                })
+                .collect();
+            let encoded = encode_adaptive(&data, AdaptiveModel::new()).unwrap();
+            let decoded = decode_adaptive(&encoded, AdaptiveModel::new()).unwrap();
+            assert!(
+                decoded == data,
+                "case {case}: len {len}, alphabet {alphabet}"
+            );
+        }
+    }
+
+    #[test]
+    fn sparse16_header_roundtrip_and_validation() {
+        let opts = EncodeOptions::new().format(FormatVersion::Sparse16);
+        // 短 ASCII 字符串：头部只有出现的字节。
+        let text = b"hello, sparse header";
+        let enc = encode_with_options(text, &opts).unwrap();
+        assert_eq!(&enc[..4], b"RCN2");
+        assert!(enc.len() < 80, "{}", enc.len());
+        assert!(enc.len() < encode(text).unwrap().len());
+        assert_eq!(format_of(&enc).unwrap(), FormatVersion::Sparse16);
+        assert_bytes_eq(text, &decode(&enc).unwrap());
+
+        // 全部 256 个字节加 EOF 都出现，且计数超过 u16 时缩放。
+        let mut all: Vec<u8> = (0..=255u8).collect();
+        all.extend(std::iter::repeat_n(b'e', 100_000));
+        let enc = encode_with_options(&all, &opts).unwrap();
+        assert_eq!(u16::from_le_bytes([enc[4], enc[5]]) as usize, SYMBOL_LIMIT);
+        assert_bytes_eq(&all, &decode(&enc).unwrap());
+
+        // 只有一个字节值（加 EOF）的频率表。
+        let mut freq = vec![0u32; SYMBOL_LIMIT];
+        freq[b'x' as usize] = 7;
+        freq[EOF_SYMBOL] = 1;
+        let mut header = Vec::new();
+        write_sparse16_header(&mut header, &freq);
+        assert_eq!(header.len(), 4 + 2 + 3 + 2);
+        let mut pos = 0;
+        assert_eq!(read_sparse16_header(&header, &mut pos).unwrap(), freq);
+        assert_eq!(pos, header.len());
+
+        // 字节值重复（越界）、符号数为 0 或超过 SYMBOL_LIMIT、截断时拒绝。
+        let mut bad = header.clone();
+        bad[5] = 0;
+        bad[4] = 3;
+        bad.splice(9..9, [b'x', 1, 0]);
+        assert!(matches!(
+            read_sparse16_header(&bad, &mut 0),
+            Err(Error::InvalidModel("range: sparse symbol out of range"))
+        ));
+        for count in [0u16, 258] {
+            let mut bad = header.clone();
+            bad[4..6].copy_from_slice(&count.to_le_bytes());
+            assert!(matches!(
+                read_sparse16_header(&bad, &mut 0),
+                Err(Error::InvalidModel(_))
+            ));
+        }
+        assert!(matches!(
+            read_sparse16_header(&header[..8], &mut 0),
+            Err(Error::Truncated(_))
+        ));
+    }
+
+    #[test]
+    fn capped16_table_handles_dominant_symbol() {
+        // 99% 为同一个字节：该符号的计数远超 u16，整表按比例缩小到最大频率为 65535。
+        let noise = encoding_common::selftest::pseudo_random(200_000, 0x99);
+        let data: Vec<u8> = noise
+            .iter()
+            .enumerate()
+            .map(|(i, &r)| if i % 100 == 0 { r } else { b'z' })
+            .collect();
+        let opts = EncodeOptions::new().format(FormatVersion::Capped16);
+        let enc = encode_with_options(&data, &opts).unwrap();
+        assert_eq!(&enc[..4], b"RCNW");
+        assert_eq!(format_of(&enc).unwrap(), FormatVersion::Capped16);
+        let mut pos = 0;
+        let freq = read_u16_table(&enc, &mut pos).unwrap();
+        assert!(freq.iter().all(|&f| f <= u16::MAX as u32));
+        assert!(freq[b'z' as usize] >= u16::MAX as u32 - 256);
+        assert_bytes_eq(&data, &decode(&enc).unwrap());
+
+        // 头部比 RCNC 少 514 字节，数据体几乎不受截断影响。
+        let classic = encode(&data).unwrap();
+        assert!(
+            enc.len() + 514 <= classic.len() + 64,
+            "{} vs {}",
+            enc.len(),
+            classic.len()
+        );
+        assert!(matches!(decode(&enc[..100]), Err(Error::Truncated(_))));
+    }
+
+    #[test]
+    fn sized_roundtrip_without_eof() {
+        let text = b"fixed-length streams carry their size in the header. ".repeat(40);
+        for data in [
+            &b""[..],
+            b"x",
+            &text[..1024],
+            &encoding_common::selftest::pseudo_random(1 << 12, 0x51ED),
+        ] {
+            let encoded = encode_sized(data);
+            assert_eq!(&encoded[..4], b"RCNZ");
+            assert_bytes_eq(data, &decode_sized(&encoded).unwrap());
+        }
+        // 字节 255 与其他字节一样是普通符号。
+        let high: Vec<u8> = (0..=255u8).cycle().take(2048).collect();
+        assert_bytes_eq(&high, &decode_sized(&encode_sized(&high)).unwrap());
+
+        let encoded = encode_sized(&text);
+        assert!(matches!(
+            decode_sized(&encoded[..100]),
+            Err(Error::Truncated(_))
+        ));
+        assert!(matches!(
+            decode_sized(&encode(&text).unwrap()),
+            Err(Error::BadMagic(_))
+        ));
+        let cm = crate::cm::encode_cm(&text, crate::cm::DEFAULT_CM_ORDER).unwrap();
+        assert!(matches!(decode_sized(&cm), Err(Error::BadMagic(_))));
+        let mut zeroed = encoded.clone();
+        zeroed[12..12 + 4 * 256].fill(0);
+        assert!(matches!(decode_sized(&zeroed), Err(Error::InvalidModel(_))));
+
+        // 伪造的长度：超过上限时不解码；在上限之内时越过数据体末尾即返回，不以补零继续解码。
+        let mut forged = encoded.clone();
+        forged[4..12].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            decode_sized(&forged),
+            Err(Error::LimitExceeded(_))
+        ));
+        forged[4..12].copy_from_slice(&(1u64 << 24).to_le_bytes());
+        assert!(matches!(decode_sized(&forged), Err(Error::Truncated(_))));
+        assert!(matches!(
+            decode_sized(&encoded[..encoded.len() - 1]),
+            Err(Error::Truncated(_))
+        ));
+        let limits = DecodeLimits::new(SIZED_TABLE_BYTES + text.len() as u64);
+        assert_bytes_eq(&text, &decode_sized_with_limits(&encoded, &limits).unwrap());
+        let limits = DecodeLimits::new(SIZED_TABLE_BYTES + text.len() as u64 - 1);
+        assert!(matches!(
+            decode_sized_with_limits(&encoded, &limits),
+            Err(Error::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn no_eof_frequencies_save_the_eof_cost() {
+        // 偏斜数据：字节值越大越少见。
+        let skewed: Vec<u8> = encoding_common::selftest::pseudo_random(20_000, 0x5E0F)
+            .iter()
+            .map(|&r| ((r as u32 * r as u32) >> 12) as u8)
+            .collect();
+        let freq = build_frequencies_no_eof(&skewed);
+        assert_eq!(freq.len(), 256);
+        let with_eof = build_frequencies(&skewed);
+        assert_eq!(with_eof.len(), SYMBOL_LIMIT);
+        assert_eq!(with_eof[EOF_SYMBOL], 1);
+
+        // 两种头部同为 1036 字节，差别只在数据体：不预留 EOF 时略小。
+        let sized = encode_sized(&skewed);
+        let header: Vec<u32> = sized[12..12 + 4 * 256]
+            .chunks(4)
+            .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+        assert_eq!(header, freq);
+        let classic = encode(&skewed).unwrap();
+        assert_eq!(&classic[..4], b"RCNC");
+        assert!(
+            sized.len() < classic.len(),
+            "{} vs {} bytes",
+            sized.len(),
+            classic.len()
+        );
+        assert_bytes_eq(&skewed, &decode_sized(&sized).unwrap());
+        assert!(build_frequencies_no_eof(b"").iter().all(|&f| f == 1));
+    }
+
+    #[test]
+    fn interval_never_carries_across_emitted_bytes() {
+        // 随机的小模型（含频率为 1 与接近 MAX_TOTAL 的符号）与短序列，让区间反复跨越
+        // 最高字节边界；编码端的加法在 debug 构建下溢出即 panic，可同时证明不存在进位。
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..100_000 {
+            let symbols = 2 + (next() % 7) as usize;
+            let mut freq: Vec<u32> = (0..symbols)
+                .map(|_| match next() % 3 {
+                    0 => 1,
+                    1 => 1 + (next() % 256) as u32,
+                    _ => 1 + (next() % (MAX_TOTAL as u64 / 8)) as u32,
+                })
+                .collect();
+            settle_total_to(&mut freq, MAX_TOTAL, false);
+            let cumulative = build_cumulative(&freq);
+            let len = 1 + (next() % 32) as usize;
+            let message: Vec<u32> = (0..len).map(|_| (next() % symbols as u64) as u32).collect();
+
+            let mut out = Vec::new();
+            let mut enc = RangeEncoder::new(&mut out);
+            for &sym in &message {
+                enc.encode_symbol(sym, &cumulative);
+            }
+            enc.finish();
+            let mut dec = RangeDecoder::new(&out);
+            let decoded: Vec<u32> = (0..len).map(|_| dec.decode_symbol(&cumulative)).collect();
+            assert_eq!(decoded, message, "freq {freq:?}");
+        }
     }
 }
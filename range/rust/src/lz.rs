@@ -0,0 +1,394 @@
+// LZSS + 区间编码的通用压缩器（类似 deflate）：先用哈希链在滑动窗口中查找重复串，
+// 再用自适应模型对字面量 / 匹配记号流做区间编码。
+//
+// 格式（稳定）：魔数 RCNL + 1 字节窗口位数 w（LZ_MIN_WINDOW_BITS..=LZ_MAX_WINDOW_BITS）+ 编码数据。
+// 编码数据是一串记号，均由区间编码器编码，所有模型从均匀分布出发、每个符号后更新：
+//   - 主符号（258 个）：0..=255 为字面量，256 为匹配，257 为结束；
+//   - 匹配长度：长度 - LZ_MIN_MATCH，取值 0..=255（即长度 3..=258），独立的 256 符号模型；
+//   - 匹配距离：d = 距离 - 1 按比特数分槽，槽号 = d 的有效位数（0..=w），独立的 w + 1 符号模型；
+//     槽号 k >= 2 时随后以均匀分布编码 d 去掉最高位后的低 k - 1 位。
+// 距离不超过 2^w，且不超过已输出的字节数。
+
+use crate::{AdaptiveModel, DecodeLimits, Error, Model, RangeDecoder, RangeEncoder};
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+const LZ_MAGIC: &[u8; 4] = b"RCNL";
+
+// 默认 64 KiB 窗口。
+pub const DEFAULT_LZ_WINDOW_BITS: u32 = 16;
+pub const LZ_MIN_WINDOW_BITS: u32 = 8;
+pub const LZ_MAX_WINDOW_BITS: u32 = 20;
+pub const LZ_MIN_MATCH: usize = 3;
+pub const LZ_MAX_MATCH: usize = LZ_MIN_MATCH + 255;
+
+const MATCH_SYMBOL: usize = 256;
+const END_SYMBOL: usize = 257;
+const HASH_BITS: u32 = 15;
+// 每个位置最多沿哈希链比较的候选数，限制最坏情况下的编码时间。
+const MAX_CHAIN: usize = 64;
+
+struct TokenModels {
+    main: AdaptiveModel,
+    length: AdaptiveModel,
+    slot: AdaptiveModel,
+}
+
+impl TokenModels {
+    fn new(window_bits: u32) -> TokenModels {
+        TokenModels {
+            main: AdaptiveModel::with_symbols(END_SYMBOL + 1),
+            length: AdaptiveModel::with_symbols(LZ_MAX_MATCH - LZ_MIN_MATCH + 1),
+            slot: AdaptiveModel::with_symbols(window_bits as usize + 1),
+        }
+    }
+}
+
+fn encode_with(enc: &mut RangeEncoder<&mut Vec<u8>>, model: &mut AdaptiveModel, symbol: usize) {
+    enc.encode_symbol(symbol as u32, model.cumulative());
+    model.update(symbol);
+}
+
+fn decode_with(dec: &mut RangeDecoder, model: &mut AdaptiveModel) -> usize {
+    let symbol = dec.decode_symbol(model.cumulative()) as usize;
+    model.update(symbol);
+    symbol
+}
+
+fn hash3(data: &[u8], pos: usize) -> usize {
+    let v = (data[pos] as u32) << 16 | (data[pos + 1] as u32) << 8 | data[pos + 2] as u32;
+    (v.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+}
+
+// 哈希链匹配查找器：head 为每个哈希值最近出现的位置，prev 为同一哈希值的上一个位置（按窗口取模）。
+struct MatchFinder {
+    head: Vec<usize>,
+    prev: Vec<usize>,
+    window: usize,
+}
+
+impl MatchFinder {
+    fn new(window_bits: u32) -> MatchFinder {
+        MatchFinder {
+            head: vec![usize::MAX; 1 << HASH_BITS],
+            prev: vec![usize::MAX; 1 << window_bits],
+            window: 1 << window_bits,
+        }
+    }
+
+    fn insert(&mut self, data: &[u8], pos: usize) {
+        if pos + LZ_MIN_MATCH <= data.len() {
+            let h = hash3(data, pos);
+            self.prev[pos & (self.window - 1)] = self.head[h];
+            self.head[h] = pos;
+        }
+    }
+
+    // 返回 pos 处最长匹配的 (长度, 距离)，没有不短于 LZ_MIN_MATCH 的匹配时返回 None。
+    fn find(&self, data: &[u8], pos: usize) -> Option<(usize, usize)> {
+        if pos + LZ_MIN_MATCH > data.len() {
+            return None;
+        }
+        let max_len = LZ_MAX_MATCH.min(data.len() - pos);
+        let mut best: Option<(usize, usize)> = None;
+        let mut candidate = self.head[hash3(data, pos)];
+        for _ in 0..MAX_CHAIN {
+            if candidate == usize::MAX || pos - candidate > self.window {
+                break;
+            }
+            let len = data[candidate..]
+                .iter()
+                .zip(&data[pos..pos + max_len])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if len >= LZ_MIN_MATCH && best.is_none_or(|(l, _)| len > l) {
+                best = Some((len, pos - candidate));
+                if len == max_len {
+                    break;
+                }
+            }
+            let next = self.prev[candidate & (self.window - 1)];
+            // 链上的位置严格递减；回绕到更新的位置说明该槽已被覆盖。
+            if next == usize::MAX || next >= candidate {
+                break;
+            }
+            candidate = next;
+        }
+        best
+    }
+}
+
+// LZSS 记号：字面量，或复制此前 dist 字节处开始的 len 个字节（dist 可以小于 len，即重叠复制）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Literal(u8),
+    Match { len: u16, dist: u32 },
+}
+
+// 贪心解析：每个位置取哈希链上的最长匹配，没有不短于 LZ_MIN_MATCH 的匹配时输出字面量。
+fn parse(input: &[u8], window_bits: u32, mut emit: impl FnMut(Token)) {
+    let mut finder = MatchFinder::new(window_bits);
+    let mut pos = 0;
+    while pos < input.len() {
+        match finder.find(input, pos) {
+            Some((len, dist)) => {
+                emit(Token::Match {
+                    len: len as u16,
+                    dist: dist as u32,
+                });
+                for p in pos..pos + len {
+                    finder.insert(input, p);
+                }
+                pos += len;
+            }
+            None => {
+                emit(Token::Literal(input[pos]));
+                finder.insert(input, pos);
+                pos += 1;
+            }
+        }
+    }
+}
+
+// 只做 LZSS 解析、不做熵编码，记号可由调用方序列化后交给任意熵编码器；
+// encode_lz 即同一解析结果的区间编码。window 为窗口字节数，按 2 的幂向下取整，
+// 并限制在 2^LZ_MIN_WINDOW_BITS..=2^LZ_MAX_WINDOW_BITS 之间。
+pub fn lz_encode(data: &[u8], window: usize) -> Vec<Token> {
+    let window_bits = (window.max(1).ilog2()).clamp(LZ_MIN_WINDOW_BITS, LZ_MAX_WINDOW_BITS);
+    let mut tokens = Vec::with_capacity(data.len() / 4 + 1);
+    parse(data, window_bits, |token| tokens.push(token));
+    tokens
+}
+
+// 匹配的距离为 0 或超过已输出的字节数时返回 InvalidData。
+pub fn lz_decode(tokens: &[Token]) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::with_capacity(tokens.len() * 2);
+    for &token in tokens {
+        match token {
+            Token::Literal(b) => out.push(b),
+            Token::Match { len, dist } => copy_match(&mut out, len as usize, dist as usize)?,
+        }
+    }
+    Ok(out)
+}
+
+// 距离可能小于长度（重叠复制），只能逐字节复制。
+fn copy_match(out: &mut Vec<u8>, len: usize, dist: usize) -> Result<(), Error> {
+    if dist == 0 || dist > out.len() {
+        return Err(Error::InvalidData("range: bad match distance"));
+    }
+    let start = out.len() - dist;
+    for i in 0..len {
+        out.push(out[start + i]);
+    }
+    Ok(())
+}
+
+fn check_window_bits(window_bits: u32) -> Result<(), Error> {
+    if !(LZ_MIN_WINDOW_BITS..=LZ_MAX_WINDOW_BITS).contains(&window_bits) {
+        return Err(Error::InvalidInput("range: bad window size"));
+    }
+    Ok(())
+}
+
+// window_bits 为滑动窗口大小的以 2 为底的对数，一般使用 DEFAULT_LZ_WINDOW_BITS。
+pub fn encode_lz(input: &[u8], window_bits: u32) -> Result<Vec<u8>, Error> {
+    check_window_bits(window_bits)?;
+    let mut out = Vec::with_capacity(input.len() / 3 + 16);
+    out.extend_from_slice(LZ_MAGIC);
+    out.push(window_bits as u8);
+
+    let mut models = TokenModels::new(window_bits);
+    let mut enc = RangeEncoder::new(&mut out);
+    parse(input, window_bits, |token| match token {
+        Token::Match { len, dist } => {
+            encode_with(&mut enc, &mut models.main, MATCH_SYMBOL);
+            encode_with(&mut enc, &mut models.length, len as usize - LZ_MIN_MATCH);
+            let d = dist - 1;
+            let slot = (32 - d.leading_zeros()) as usize;
+            encode_with(&mut enc, &mut models.slot, slot);
+            if slot >= 2 {
+                let bits = slot as u32 - 1;
+                enc.encode_uniform(d & ((1 << bits) - 1), bits);
+            }
+        }
+        Token::Literal(b) => encode_with(&mut enc, &mut models.main, b as usize),
+    });
+    encode_with(&mut enc, &mut models.main, END_SYMBOL);
+    enc.finish();
+    Ok(out)
+}
+
+pub fn decode_lz(encoded: &[u8]) -> Result<Vec<u8>, Error> {
+    decode_lz_with_limits(encoded, &DecodeLimits::unlimited())
+}
+
+// 输出超过 limits 时返回 LimitExceeded；解码越过数据体末尾（数据被截断）时返回 Truncated。
+pub fn decode_lz_with_limits(encoded: &[u8], limits: &DecodeLimits) -> Result<Vec<u8>, Error> {
+    if encoded.len() < 5 {
+        return Err(Error::Truncated("range: input too short"));
+    }
+    if &encoded[0..4] != LZ_MAGIC {
+        return Err(Error::BadMagic("range: bad magic"));
+    }
+    let window_bits = encoded[4] as u32;
+    check_window_bits(window_bits).map_err(|_| Error::InvalidData("range: bad window size"))?;
+    let max_output = usize::try_from(limits.max_memory()).unwrap_or(usize::MAX);
+
+    let mut models = TokenModels::new(window_bits);
+    let mut dec = RangeDecoder::new(&encoded[5..]);
+    let mut out = Vec::with_capacity(encoded.len().saturating_mul(3).min(max_output));
+    loop {
+        let symbol = decode_with(&mut dec, &mut models.main);
+        let matched = match symbol {
+            MATCH_SYMBOL => {
+                let len = decode_with(&mut dec, &mut models.length) + LZ_MIN_MATCH;
+                let slot = decode_with(&mut dec, &mut models.slot) as u32;
+                let d = match slot {
+                    0 | 1 => slot,
+                    _ => (1 << (slot - 1)) | dec.decode_uniform(slot - 1),
+                };
+                Some((len, d as usize + 1))
+            }
+            _ => None,
+        };
+        // 截断的数据流之后只剩补位，继续解码只会凭空产生记号。
+        if dec.overrun() {
+            return Err(Error::Truncated("range: truncated data"));
+        }
+        let (len, dist) = match (symbol, matched) {
+            (END_SYMBOL, _) => break,
+            (_, Some(matched)) => matched,
+            (literal, None) => {
+                if out.len() >= max_output {
+                    return Err(Error::LimitExceeded("range: output limit exceeded"));
+                }
+                out.push(literal as u8);
+                continue;
+            }
+        };
+        if out.len() + len > max_output {
+            return Err(Error::LimitExceeded("range: output limit exceeded"));
+        }
+        copy_match(&mut out, len, dist)?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_common::selftest::pseudo_random;
+    use encoding_common::test_util::assert_bytes_eq;
+
+    fn samples() -> Vec<(&'static str, Vec<u8>)> {
+        let text = b"It was the best of times, it was the worst of times, it was the age of \
+                     wisdom, it was the age of foolishness. "
+            .repeat(200);
+        let source = include_bytes!("lib.rs").to_vec();
+        // 结构化二进制：小端递增计数与随机噪声交错。
+        let noise = pseudo_random(20_000, 0xC0FF_EE11);
+        let mut binary = Vec::new();
+        for i in 0..20_000u32 {
+            binary.extend_from_slice(&(i / 16).to_le_bytes());
+            binary.push(noise[i as usize] & 0x03);
+        }
+        vec![("text", text), ("source", source), ("binary", binary)]
+    }
+
+    #[test]
+    fn lz_roundtrip_and_beats_order0() {
+        for (name, data) in samples() {
+            for window_bits in [
+                LZ_MIN_WINDOW_BITS,
+                DEFAULT_LZ_WINDOW_BITS,
+                LZ_MAX_WINDOW_BITS,
+            ] {
+                let encoded = encode_lz(&data, window_bits).unwrap();
+                assert_bytes_eq(&data, &decode_lz(&encoded).unwrap());
+            }
+            let lz = encode_lz(&data, DEFAULT_LZ_WINDOW_BITS).unwrap().len();
+            let order0 = crate::encode(&data).unwrap().len();
+            assert!(lz < order0, "{name}: lz {lz} vs order-0 {order0}");
+        }
+        for data in [
+            &b""[..],
+            b"a",
+            b"aaaaaaaaaaaaaaaaaaaaaaaa",
+            &pseudo_random(5000, 3),
+        ] {
+            let encoded = encode_lz(data, DEFAULT_LZ_WINDOW_BITS).unwrap();
+            assert_bytes_eq(data, &decode_lz(&encoded).unwrap());
+        }
+
+        let encoded = encode_lz(&[7u8; 10_000], DEFAULT_LZ_WINDOW_BITS).unwrap();
+        assert!(matches!(
+            decode_lz_with_limits(&encoded, &DecodeLimits::new(5000)),
+            Err(Error::LimitExceeded(_))
+        ));
+        assert!(matches!(encode_lz(b"x", 7), Err(Error::InvalidInput(_))));
+        assert!(matches!(decode_lz(b"RCNL\x30"), Err(Error::InvalidData(_))));
+
+        let (_, text) = &samples()[0];
+        let encoded = encode_lz(text, DEFAULT_LZ_WINDOW_BITS).unwrap();
+        for cut in [5, 6, encoded.len() / 2, encoded.len() - 1] {
+            assert!(
+                matches!(decode_lz(&encoded[..cut]), Err(Error::Truncated(_))),
+                "cut {cut}"
+            );
+        }
+    }
+
+    #[test]
+    fn tokens_roundtrip_without_entropy_stage() {
+        let text = b"the rain in spain stays mainly in the plain. ".repeat(100);
+        let tokens = lz_encode(&text, 1 << 16);
+        assert_bytes_eq(&text, &lz_decode(&tokens).unwrap());
+        assert!(tokens.len() < text.len() / 20, "{} tokens", tokens.len());
+        assert!(tokens.contains(&Token::Match { len: 258, dist: 45 }));
+        assert_eq!(
+            lz_encode(b"abcabcabcx", 1 << 16),
+            [
+                Token::Literal(b'a'),
+                Token::Literal(b'b'),
+                Token::Literal(b'c'),
+                Token::Match { len: 6, dist: 3 },
+                Token::Literal(b'x'),
+            ]
+        );
+
+        // 随机数据几乎没有可用的匹配，退化为字面量（只有偶然重复的 3 字节串成为匹配）。
+        let random = pseudo_random(5000, 0x12E7);
+        let tokens = lz_encode(&random, 1 << 16);
+        let literals = tokens
+            .iter()
+            .filter(|t| matches!(t, Token::Literal(_)))
+            .count();
+        assert!(literals * 100 > random.len() * 99, "{literals} literals");
+        assert_bytes_eq(&random, &lz_decode(&tokens).unwrap());
+
+        // 窗口限制匹配距离；0 与过大的窗口被限制到允许范围内。
+        let far = [&pseudo_random(600, 9)[..], &pseudo_random(600, 9)[..]].concat();
+        assert!(lz_encode(&far, 512)
+            .iter()
+            .all(|t| !matches!(t, Token::Match { .. })));
+        assert!(lz_encode(&far, 1024).contains(&Token::Match {
+            len: 258,
+            dist: 600
+        }));
+        for window in [0, usize::MAX] {
+            assert_bytes_eq(&far, &lz_decode(&lz_encode(&far, window)).unwrap());
+        }
+
+        assert!(lz_decode(&[]).unwrap().is_empty());
+        for bad in [
+            Token::Match { len: 3, dist: 0 },
+            Token::Match { len: 3, dist: 2 },
+        ] {
+            assert!(matches!(
+                lz_decode(&[Token::Literal(1), bad]),
+                Err(Error::InvalidData(_))
+            ));
+        }
+    }
+}
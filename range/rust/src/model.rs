@@ -0,0 +1,203 @@
+// 自适应概率模型。编码端与解码端从相同的初始状态出发，每处理一个符号后以相同顺序调用
+// update，因此无需在数据流中保存频率表，两端的模型始终一致。
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+pub trait Model {
+    // 当前累积频率表，长度为符号数 + 1；最后一个符号为 EOF，总和不得超过 MAX_TOTAL。
+    fn cumulative(&self) -> &[u32];
+    // 编码或解码 symbol 之后调用。
+    fn update(&mut self, symbol: usize);
+}
+
+// 每个符号出现一次增加的计数。
+const INCREMENT: u32 = 32;
+// 总和超过此值时所有计数减半。
+const HALVE_LIMIT: u32 = 1 << 16;
+
+// 频率与累积表，供各模型共用。
+#[derive(Debug, Clone)]
+struct Counts {
+    freq: Vec<u32>,
+    cumulative: Vec<u32>,
+}
+
+impl Counts {
+    fn uniform(symbols: usize) -> Counts {
+        Counts {
+            freq: vec![1; symbols],
+            cumulative: (0..=symbols as u32).collect(),
+        }
+    }
+
+    fn total(&self) -> u32 {
+        *self.cumulative.last().unwrap()
+    }
+
+    fn add(&mut self, symbol: usize, inc: u32) {
+        self.freq[symbol] += inc;
+        for c in &mut self.cumulative[symbol + 1..] {
+            *c += inc;
+        }
+    }
+
+    // 对每个计数应用 scale（结果至少为 1，保证任意符号仍可编码）并重建累积表。
+    fn rescale(&mut self, scale: impl Fn(u32) -> u32) {
+        for i in 0..self.freq.len() {
+            self.freq[i] = scale(self.freq[i]).max(1);
+            self.cumulative[i + 1] = self.cumulative[i] + self.freq[i];
+        }
+    }
+}
+
+// 普通 order-0 自适应模型：计数只在总和溢出 HALVE_LIMIT 时减半。
+#[derive(Debug, Clone)]
+pub struct AdaptiveModel {
+    counts: Counts,
+}
+
+impl AdaptiveModel {
+    pub fn new() -> AdaptiveModel {
+        AdaptiveModel {
+            counts: Counts::uniform(crate::SYMBOL_LIMIT),
+        }
+    }
+
+    // 字母表大小为 symbols 的同类模型，供自带字母表的格式（如 lz 的记号流）使用。
+    pub(crate) fn with_symbols(symbols: usize) -> AdaptiveModel {
+        AdaptiveModel {
+            counts: Counts::uniform(symbols),
+        }
+    }
+
+    // 检查点保存的各符号计数。
+    #[cfg(feature = "std")]
+    pub(crate) fn counts(&self) -> &[u32] {
+        &self.counts.freq
+    }
+
+    // 由检查点中的计数恢复模型；计数须为 257 项、均非零且总和不超过 HALVE_LIMIT。
+    #[cfg(feature = "std")]
+    pub(crate) fn from_counts(freq: &[u32]) -> Option<AdaptiveModel> {
+        let total: u64 = freq.iter().map(|&f| f as u64).sum();
+        if freq.len() != crate::SYMBOL_LIMIT || freq.contains(&0) || total > HALVE_LIMIT as u64 {
+            return None;
+        }
+        let mut cumulative = vec![0; freq.len() + 1];
+        for (i, &f) in freq.iter().enumerate() {
+            cumulative[i + 1] = cumulative[i] + f;
+        }
+        Some(AdaptiveModel {
+            counts: Counts {
+                freq: freq.to_vec(),
+                cumulative,
+            },
+        })
+    }
+}
+
+impl Default for AdaptiveModel {
+    fn default() -> Self {
+        AdaptiveModel::new()
+    }
+}
+
+impl Model for AdaptiveModel {
+    fn cumulative(&self) -> &[u32] {
+        &self.counts.cumulative
+    }
+
+    fn update(&mut self, symbol: usize) {
+        self.counts.add(symbol, INCREMENT);
+        if self.counts.total() > HALVE_LIMIT {
+            self.counts.rescale(|f| f / 2);
+        }
+    }
+}
+
+// 指数衰减模型：每处理 period 个符号，所有计数乘以 1 - 2^-shift，
+// 旧统计的权重按几何级数衰减，统计特性随位置漂移时比 AdaptiveModel 适应得更快。
+// 衰减只用整数运算，编解码两端结果完全一致。
+#[derive(Debug, Clone)]
+pub struct DecayModel {
+    counts: Counts,
+    period: u32,
+    shift: u32,
+    seen: u32,
+}
+
+impl DecayModel {
+    // period 为 0 时按 1 处理；shift 取值 1..=31，越小衰减越快。
+    pub fn new(period: u32, shift: u32) -> DecayModel {
+        DecayModel {
+            counts: Counts::uniform(crate::SYMBOL_LIMIT),
+            period: period.max(1),
+            shift: shift.clamp(1, 31),
+            seen: 0,
+        }
+    }
+}
+
+impl Default for DecayModel {
+    // 每 1024 个符号衰减 1/4。
+    fn default() -> Self {
+        DecayModel::new(1024, 2)
+    }
+}
+
+impl Model for DecayModel {
+    fn cumulative(&self) -> &[u32] {
+        &self.counts.cumulative
+    }
+
+    fn update(&mut self, symbol: usize) {
+        self.counts.add(symbol, INCREMENT);
+        self.seen += 1;
+        if self.seen == self.period {
+            self.seen = 0;
+            let shift = self.shift;
+            self.counts.rescale(|f| f - (f >> shift));
+        }
+        if self.counts.total() > HALVE_LIMIT {
+            self.counts.rescale(|f| f / 2);
+        }
+    }
+}
+
+// 二元自适应模型，供逐比特编码使用；累积表为 [0, f0, f0 + f1]。
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BitModel {
+    cumulative: [u32; 3],
+}
+
+// 二元模型的计数上限较小，以便快速跟随局部变化。
+const BIT_INCREMENT: u32 = 24;
+const BIT_LIMIT: u32 = 1 << 12;
+
+impl BitModel {
+    pub(crate) fn new() -> BitModel {
+        BitModel {
+            cumulative: [0, 1, 2],
+        }
+    }
+
+    pub(crate) fn cumulative(&self) -> &[u32] {
+        &self.cumulative
+    }
+
+    pub(crate) fn update(&mut self, bit: u32) {
+        let mut f0 = self.cumulative[1];
+        let mut f1 = self.cumulative[2] - f0;
+        if bit == 0 {
+            f0 += BIT_INCREMENT;
+        } else {
+            f1 += BIT_INCREMENT;
+        }
+        if f0 + f1 > BIT_LIMIT {
+            f0 = (f0 / 2).max(1);
+            f1 = (f1 / 2).max(1);
+        }
+        self.cumulative = [0, f0, f0 + f1];
+    }
+}
@@ -0,0 +1,187 @@
+// 任意字母表的静态模型编码：输入为 0..alphabet 内的 u32 符号序列（2 位碱基、词表 ID 等），
+// 不再假定 256 个字节值 + EOF。
+//
+// 格式：魔数 RCNG + u32 字母表大小 + u64 符号个数 + alphabet 个 varint 频率 + 编码数据，整数均为小端。
+// 与 RCNZ 相同按个数解码、不编码 EOF；频率用 varint 存储，大字母表中未出现的符号只占 1 字节。
+
+use crate::{
+    build_cumulative, read_varint, scale_frequencies, write_u32_le, write_varint, DecodeLimits,
+    Error, RangeDecoder, RangeEncoder, MAX_TOTAL,
+};
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+use encoding_common::symbol::read_u32_le;
+
+const SYMBOLS_MAGIC: &[u8; 4] = b"RCNG";
+// 每个出现过的符号缩放后至少为 1，字母表过大时模型总和会远超 MAX_TOTAL。
+pub const MAX_ALPHABET: usize = 1 << 16;
+
+pub fn encode_symbols(symbols: &[u32], alphabet: usize) -> Result<Vec<u8>, Error> {
+    if !(1..=MAX_ALPHABET).contains(&alphabet) {
+        return Err(Error::InvalidInput("range: bad alphabet size"));
+    }
+    let mut freq = vec![0u32; alphabet];
+    for &s in symbols {
+        let f = freq
+            .get_mut(s as usize)
+            .ok_or(Error::InvalidInput("range: symbol out of alphabet"))?;
+        *f = f.saturating_add(1);
+    }
+    scale_frequencies(&mut freq);
+    let cumulative = build_cumulative(&freq);
+
+    let mut out = Vec::with_capacity(16 + alphabet + symbols.len());
+    out.extend_from_slice(SYMBOLS_MAGIC);
+    write_u32_le(&mut out, alphabet as u32);
+    out.extend_from_slice(&(symbols.len() as u64).to_le_bytes());
+    for &f in &freq {
+        write_varint(&mut out, f);
+    }
+    let mut enc = RangeEncoder::new(&mut out);
+    for &s in symbols {
+        enc.encode_symbol(s, &cumulative);
+    }
+    enc.finish();
+    Ok(out)
+}
+
+pub fn decode_symbols(encoded: &[u8]) -> Result<Vec<u32>, Error> {
+    decode_symbols_with_limits(encoded, &DecodeLimits::unlimited())
+}
+
+// 在 limits 约束下解码：频率表、累积表与输出的 u32 序列合计超过上限时在解码之前返回 LimitExceeded，
+// 解码越过数据体末尾（个数伪造或数据被截断）时返回 Truncated。
+pub fn decode_symbols_with_limits(
+    encoded: &[u8],
+    limits: &DecodeLimits,
+) -> Result<Vec<u32>, Error> {
+    if encoded.len() < 16 {
+        return Err(Error::Truncated("range: input too short"));
+    }
+    if &encoded[0..4] != SYMBOLS_MAGIC {
+        return Err(Error::BadMagic("range: bad magic"));
+    }
+    let mut pos = 4;
+    let alphabet =
+        read_u32_le(encoded, &mut pos).ok_or(Error::Truncated("range: truncated header"))? as usize;
+    if !(1..=MAX_ALPHABET).contains(&alphabet) {
+        return Err(Error::InvalidData("range: bad alphabet size"));
+    }
+    let count = u64::from_le_bytes(encoded[8..16].try_into().unwrap());
+    let symbol_bytes = core::mem::size_of::<u32>() as u64;
+    let budget = limits
+        .output_budget((2 * alphabet as u64 + 1) * symbol_bytes)
+        .ok_or(Error::LimitExceeded("range: memory limit exceeded"))?;
+    let count = usize::try_from(count)
+        .ok()
+        .filter(|&count| count as u64 <= budget / symbol_bytes)
+        .ok_or(Error::LimitExceeded("range: output limit exceeded"))?;
+    pos = 16;
+    let freq = (0..alphabet)
+        .map(|_| {
+            read_varint(encoded, &mut pos).ok_or(Error::Truncated("range: truncated frequencies"))
+        })
+        .collect::<Result<Vec<u32>, Error>>()?;
+    // 没有 EOF，validate_model 对 257 项表的 EOF 检查不适用；总和上限与编码端缩放结果一致。
+    let total: u64 = freq.iter().map(|&f| f as u64).sum();
+    if total == 0 || total > MAX_TOTAL as u64 + alphabet as u64 {
+        return Err(Error::InvalidModel("range: bad frequency total"));
+    }
+    let cumulative = build_cumulative(&freq);
+
+    let mut out = Vec::with_capacity(count.min(encoded.len().saturating_mul(8)));
+    let mut dec = RangeDecoder::new(&encoded[pos..]);
+    for _ in 0..count {
+        out.push(dec.decode_symbol(&cumulative));
+        if dec.overrun() {
+            return Err(Error::Truncated("range: truncated data"));
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_common::selftest::pseudo_random;
+
+    #[test]
+    fn symbols_roundtrip_for_various_alphabets() {
+        let noise = pseudo_random(20_000, 0x5EED);
+        for alphabet in [4usize, 1000, 257] {
+            // 偏斜分布：低编号符号更常见，并保证最大的符号出现。
+            let mut symbols: Vec<u32> = noise
+                .chunks(2)
+                .map(|c| (u16::from_le_bytes([c[0], c[1]]) as u32).pow(2) % alphabet as u32)
+                .collect();
+            symbols.push(alphabet as u32 - 1);
+            let encoded = encode_symbols(&symbols, alphabet).unwrap();
+            assert_eq!(
+                decode_symbols(&encoded).unwrap(),
+                symbols,
+                "alphabet {alphabet}"
+            );
+        }
+
+        // 2 位碱基序列约压缩到每符号 2 位。
+        let bases: Vec<u32> = noise.iter().map(|&r| (r & 3) as u32).collect();
+        let encoded = encode_symbols(&bases, 4).unwrap();
+        assert!(
+            encoded.len() < bases.len() / 4 + 64,
+            "{} bytes",
+            encoded.len()
+        );
+        assert_eq!(decode_symbols(&encoded).unwrap(), bases);
+
+        assert!(decode_symbols(&encode_symbols(&[], 10).unwrap())
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            encode_symbols(&[4], 4),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            encode_symbols(&[], 0),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            decode_symbols(&crate::encode_sized(b"bytes")),
+            Err(Error::BadMagic(_))
+        ));
+    }
+
+    #[test]
+    fn forged_symbol_counts_are_bounded() {
+        let symbols: Vec<u32> = (0..1000).map(|i| i % 7).collect();
+        let encoded = encode_symbols(&symbols, 7).unwrap();
+        let with_count = |count: u64| {
+            let mut forged = encoded.clone();
+            forged[8..16].copy_from_slice(&count.to_le_bytes());
+            forged
+        };
+        assert!(matches!(
+            decode_symbols(&with_count(u64::MAX)),
+            Err(Error::LimitExceeded(_))
+        ));
+        assert!(matches!(
+            decode_symbols(&with_count(1 << 24)),
+            Err(Error::Truncated(_))
+        ));
+        assert!(matches!(
+            decode_symbols(&encoded[..encoded.len() - 1]),
+            Err(Error::Truncated(_))
+        ));
+
+        let tables = (2 * 7 + 1) * 4;
+        let limits = DecodeLimits::new(tables + 4 * symbols.len() as u64);
+        assert_eq!(
+            decode_symbols_with_limits(&encoded, &limits).unwrap(),
+            symbols
+        );
+        let limits = DecodeLimits::new(tables + 4 * symbols.len() as u64 - 1);
+        assert!(matches!(
+            decode_symbols_with_limits(&encoded, &limits),
+            Err(Error::LimitExceeded(_))
+        ));
+    }
+}
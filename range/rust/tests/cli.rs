@@ -0,0 +1,48 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{self, Command};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("range_cli_{}_{name}", process::id()))
+}
+
+#[test]
+fn cli_roundtrips_and_reports_sizes() {
+    let [input, encoded, decoded] = ["in", "enc", "dec"].map(temp_path);
+    let data = b"range coder command line ".repeat(400);
+    fs::write(&input, &data).unwrap();
+
+    let run = |mode: &str, from: &PathBuf, to: &PathBuf| {
+        let output = Command::new(env!("CARGO_BIN_EXE_range_rust"))
+            .args([mode, from.to_str().unwrap(), to.to_str().unwrap()])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "range_rust {mode} failed");
+        String::from_utf8(output.stderr).unwrap()
+    };
+    let encoded_len = {
+        let report = run("encode", &input, &encoded);
+        let len = fs::metadata(&encoded).unwrap().len();
+        assert!(
+            report.starts_with(&format!("{} → {len} 字节 (", data.len())),
+            "{report}"
+        );
+        len
+    };
+    let report = run("decode", &encoded, &decoded);
+    assert!(
+        report.starts_with(&format!("{} → {encoded_len} 字节 (", data.len())),
+        "{report}"
+    );
+    assert_eq!(fs::read(&decoded).unwrap(), data);
+
+    let status = Command::new(env!("CARGO_BIN_EXE_range_rust"))
+        .args(["compress", "a", "b"])
+        .status()
+        .unwrap();
+    assert!(!status.success());
+
+    for path in [input, encoded, decoded] {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,61 @@
+use encoding_common::selftest::pseudo_random;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{self, Command};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("range_model_{}_{name}", process::id()))
+}
+
+// 同一领域的日志行：字段取值不同，字节分布相近。
+fn log_lines(lines: usize, seed: u64) -> Vec<u8> {
+    let levels = ["INFO", "WARN", "DEBUG", "ERROR"];
+    let mut out = Vec::new();
+    for (i, r) in pseudo_random(lines, seed).into_iter().enumerate() {
+        let line = format!(
+            "2026-10-15T12:{:02}:{:02}Z {} request id={} status={}\n",
+            i % 60,
+            r % 60,
+            levels[r as usize % levels.len()],
+            r as u32 * 7919 % 100_000,
+            200 + (r % 5) as u32 * 100
+        );
+        out.extend_from_slice(line.as_bytes());
+    }
+    out
+}
+
+#[test]
+fn trained_model_file_beats_per_file_model() {
+    let [corpus, model, input, encoded, decoded, per_file] =
+        ["corpus", "model", "input", "enc", "dec", "per_file"].map(temp_path);
+    let p = |p: &PathBuf| p.to_str().unwrap().to_string();
+    fs::write(&corpus, log_lines(2000, 0x1234_5678)).unwrap();
+    let data = log_lines(30, 0x8765_4321);
+    fs::write(&input, &data).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_bench"))
+        .args(["train", &p(&corpus), &p(&model)])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    rangecoder::encode_with_model_file(&p(&input), &p(&encoded), &p(&model)).unwrap();
+    rangecoder::decode_with_model_file(&p(&encoded), &p(&decoded), &p(&model)).unwrap();
+    assert_eq!(fs::read(&decoded).unwrap(), data);
+
+    rangecoder::encode_file(&p(&input), &p(&per_file)).unwrap();
+    let with_model = fs::metadata(&encoded).unwrap().len();
+    let without = fs::metadata(&per_file).unwrap().len();
+    assert!(with_model < without, "{with_model} vs {without}");
+
+    // 用其他模型解码时由字典 id 检出不匹配。
+    fs::write(&corpus, b"unrelated corpus").unwrap();
+    rangecoder::train_model_file(&p(&corpus), &p(&model)).unwrap();
+    let err = rangecoder::decode_with_model_file(&p(&encoded), &p(&decoded), &p(&model));
+    assert!(matches!(err, Err(rangecoder::Error::InvalidModel(_))));
+
+    for path in [corpus, model, input, encoded, decoded, per_file] {
+        let _ = fs::remove_file(path);
+    }
+}
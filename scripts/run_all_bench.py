@@ -7,6 +7,7 @@ import datetime
 # 统一基准测试脚本：
 # - 调用 tests/gen_testdata.py 生成测试数据
 # - 运行 Huffman / Arithmetic / Range / Run-Length 的基准测试
+# - 运行 Rust 跨编码器的内存占用 benchmark
 # - 将各自输出写入 reports/ 目录下的文本文件
 
 ROOT = Path(__file__).resolve().parent.parent
@@ -96,6 +97,16 @@ def main():
             title="Run-Length benchmark",
         )
 
+    # 跨编码器内存占用 benchmark（Rust，统计分配高水位）
+    bench_rust_dir = ROOT / "benchmark" / "rust"
+    if (bench_rust_dir / "Cargo.toml").is_file():
+        run_capture(
+            ["cargo", "run", "--bin", "memory", "--release", "--", str(input_file)],
+            cwd=bench_rust_dir,
+            report_path=REPORTS_DIR / f"memory_rust_report_{ts}.txt",
+            title="Rust peak memory benchmark",
+        )
+
     print("[run_all_bench] all done. Reports are in:")
     for p in sorted(REPORTS_DIR.glob("*_report_*.txt")):
         print("  ", p)